@@ -0,0 +1,87 @@
+use std::{env, ffi::OsString, fs::File};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tempfile::TempDir;
+
+use python_launcher::RequestedVersion;
+
+/// The numbers of directories on the synthetic `PATH`.
+const DIRECTORY_COUNTS: [usize; 3] = [10, 100, 500];
+
+/// A `PATH` of `count` temporary directories, each containing fake `pythonX.Y`
+/// executables along with some unrelated files.
+///
+/// The directories are deleted and the original `PATH` is restored on drop.
+struct SyntheticPath {
+    _directories: Vec<TempDir>,
+    original_path: Option<OsString>,
+}
+
+impl SyntheticPath {
+    fn new(count: usize) -> Self {
+        let directories: Vec<TempDir> = (0..count)
+            .map(|index| {
+                let directory = TempDir::new().unwrap();
+                let minor = index % 13;
+                for name in [
+                    format!("python3.{}", minor),
+                    format!("python2.{}", minor % 8),
+                    format!("pypy3.{}", minor),
+                    "python3".to_string(),
+                    "ls".to_string(),
+                ] {
+                    File::create(directory.path().join(name)).unwrap();
+                }
+                directory
+            })
+            .collect();
+
+        let original_path = env::var_os("PATH");
+        let path = env::join_paths(directories.iter().map(TempDir::path)).unwrap();
+        env::set_var("PATH", path);
+
+        Self {
+            _directories: directories,
+            original_path,
+        }
+    }
+}
+
+impl Drop for SyntheticPath {
+    fn drop(&mut self) {
+        match &self.original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+    }
+}
+
+fn discovery_benchmark(c: &mut Criterion) {
+    // Keep anything other than `PATH` from influencing discovery.
+    env::remove_var("PYLAUNCHER_PATH");
+    env::set_var("XDG_CONFIG_HOME", "/path/to/nowhere");
+
+    let mut group = c.benchmark_group("Discovery");
+    for count in DIRECTORY_COUNTS {
+        let _path = SyntheticPath::new(count);
+        group.bench_with_input(
+            BenchmarkId::new("all_executables", count),
+            &count,
+            |b, _| b.iter(python_launcher::all_executables),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("find_executable (any)", count),
+            &count,
+            |b, _| b.iter(|| python_launcher::find_executable(RequestedVersion::Any)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("find_executable (exact)", count),
+            &count,
+            |b, _| b.iter(|| python_launcher::find_executable(RequestedVersion::Exact(3, 7))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, discovery_benchmark);
+criterion_main!(benches);
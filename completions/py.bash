@@ -0,0 +1,21 @@
+# Bash completions for the Python Launcher for Unix.
+# Load with `source <(py --complete bash)`.
+
+_py() {
+    local current="${COMP_WORDS[COMP_CWORD]}"
+
+    # Only the first argument can be a launcher option.
+    if [[ ${COMP_CWORD} -eq 1 && ${current} == -* ]]; then
+        # Offer the `-X.Y` and `-X` flags for the currently-installed versions.
+        local versions
+        versions=$(py --list 2>/dev/null | awk -F '│' '{
+            gsub(/ /, "", $1)
+            split($1, parts, ".")
+            print "-" $1
+            print "-" parts[1]
+        }' | sort -u)
+        COMPREPLY=($(compgen -W "--help --list ${versions}" -- "${current}"))
+    fi
+}
+
+complete -o default -F _py py
@@ -0,0 +1,131 @@
+//! Caching of the default interpreter for shell prompts (`--resolve-cached`).
+//!
+//! Each entry lives at `$XDG_CACHE_HOME/py/resolved/KEY` (falling back to
+//! `~/.cache/py/resolved/KEY`), where `KEY` is a hash of everything which
+//! influences which interpreter is chosen by default: the current directory,
+//! any `.venv`, `.python-version`, or `pyproject.toml` in it or its parents,
+//! the relevant environment variables, the config file, and the directories
+//! searched. The modification times of directories and files are hashed rather than their
+//! contents, so checking the cache never lists a directory or runs an
+//! interpreter.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// The name of the directory holding the cache.
+static CACHE_DIR_NAME: &str = "py";
+
+/// The name of the directory (within [`CACHE_DIR_NAME`]) holding the entries
+/// for the default interpreter.
+static RESOLVED_DIR_NAME: &str = "resolved";
+
+/// Environment variables which influence the choice of interpreter but don't
+/// start with `PY` (all of which are included).
+static KEY_ENV_VARS: [&str; 5] = [
+    "PATH",
+    "VIRTUAL_ENV",
+    "CONDA_PREFIX",
+    "HOME",
+    "XDG_CONFIG_HOME",
+];
+
+/// Files in the current directory or its parents which influence the choice of
+/// interpreter.
+static ANCESTOR_FILE_NAMES: [&str; 3] = [
+    crate::cli::DEFAULT_VENV_DIR,
+    crate::cli::PYTHON_VERSION_FILE,
+    crate::cli::PYPROJECT_FILE,
+];
+
+/// Returns the directory holding the cached default interpreters.
+///
+/// `XDG_CACHE_HOME` is used if set, otherwise `~/.cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+
+    Some(cache_home.join(CACHE_DIR_NAME).join(RESOLVED_DIR_NAME))
+}
+
+/// Hashes the current inputs to choosing the default interpreter, with
+/// `directories` being those searched for executables.
+pub fn cache_key(directories: &[PathBuf]) -> String {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+
+    let mut env_vars: Vec<_> = env::vars_os()
+        .filter(|(name, _)| {
+            name.to_str()
+                .is_some_and(|name| name.starts_with("PY") || KEY_ENV_VARS.contains(&name))
+        })
+        .collect();
+    env_vars.sort();
+    env_vars.hash(&mut hasher);
+
+    if let Ok(current_dir) = env::current_dir() {
+        current_dir.hash(&mut hasher);
+        for directory in current_dir.ancestors() {
+            for name in ANCESTOR_FILE_NAMES.iter() {
+                hash_modified(&directory.join(name), &mut hasher);
+            }
+        }
+    }
+    if let Some(config_path) = crate::config::config_path() {
+        hash_modified(&config_path, &mut hasher);
+    }
+    for directory in directories {
+        directory.hash(&mut hasher);
+        hash_modified(directory, &mut hasher);
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+/// Hashes when `path` was last modified, or that it doesn't exist.
+fn hash_modified(path: &Path, hasher: &mut impl Hasher) {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .hash(hasher)
+}
+
+/// Returns the cached entry for `key`, if any.
+pub fn read(key: &str) -> Option<String> {
+    let path = cache_dir()?.join(key);
+    match fs::read_to_string(&path) {
+        Ok(contents) => {
+            log::debug!("Cache hit in {}", path.display());
+            Some(contents)
+        }
+        Err(error) => {
+            log::debug!("Cache miss for {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+/// Caches `contents` for `key`.
+///
+/// The entry is written to a temporary file which is then renamed so that a
+/// concurrent [`read`] never sees a partial entry.
+pub fn write(key: &str, contents: &str) {
+    let dir = match cache_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+    let temporary_path = dir.join(format!("{}.{}.tmp", key, process::id()));
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&temporary_path, contents))
+        .and_then(|_| fs::rename(&temporary_path, dir.join(key)));
+    if let Err(error) = result {
+        log::debug!("Can't write to the cache in {}: {}", dir.display(), error);
+        let _ = fs::remove_file(&temporary_path);
+    }
+}
@@ -1,24 +1,196 @@
 //! Parsing of CLI flags.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
+    ffi::OsStr,
     fmt::Write,
-    fs::File,
-    io::{BufRead, BufReader, Read},
+    fs::{self, File},
+    io::{self, BufRead, BufReader, IsTerminal, Read},
     iter::FromIterator,
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
     path::{Path, PathBuf},
     str::FromStr,
     string::ToString,
 };
 
-use comfy_table::{Table, TableComponent};
+use comfy_table::{Attribute, Cell, Color, Table, TableComponent};
+use serde_json::json;
 
-use crate::{ExactVersion, RequestedVersion};
+use crate::{
+    completions::Shell, config::Config, ExactVersion, Implementation, RequestedVersion,
+    SearchDirectories, Selection, VersionMatch,
+};
 
 /// The default directory searched for a virtual environment.
 pub static DEFAULT_VENV_DIR: &str = ".venv";
 
+/// The file in a virtual environment which records how it was created.
+static PYVENV_CFG: &str = "pyvenv.cfg";
+
+/// A step taken while choosing a Python executable, along with what it found.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ResolutionStep {
+    /// The `VIRTUAL_ENV` environment variable, with the executable it points
+    /// at.
+    ActivatedVirtualEnv(Option<PathBuf>),
+    /// The `CONDA_PREFIX` environment variable, with the executable it points
+    /// at.
+    ActivatedCondaEnv(Option<PathBuf>),
+    /// The search for a `.venv` directory, with the executable found in it.
+    VenvDirectory(Option<PathBuf>),
+    /// The shebang of the script being run, with the version it requests.
+    Shebang(Option<RequestedVersion>),
+    /// The search for a `.python-version` file, with the file found and the
+    /// version it requests.
+    PythonVersionFile(Option<(PathBuf, RequestedVersion)>),
+    /// The search for a `pyproject.toml` file with a `tool.py.version`, with
+    /// the file found and the version it requests.
+    PyprojectVersion(Option<(PathBuf, RequestedVersion)>),
+    /// The environment variable for the request (e.g. `PY_PYTHON`), with the
+    /// versions it specifies.
+    EnvVar(String, Vec<RequestedVersion>),
+    /// The config file's `default-version`.
+    ConfigDefault(Option<RequestedVersion>),
+    /// A search of the directories on `PATH` (and the like) for an executable
+    /// satisfying the version, with the executable chosen.
+    PathScan(RequestedVersion, Option<PathBuf>),
+}
+
+impl std::fmt::Display for ResolutionStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn found(path: &Option<PathBuf>) -> String {
+            path.as_ref()
+                .map_or_else(|| "none".to_string(), |path| path.display().to_string())
+        }
+
+        match self {
+            Self::ActivatedVirtualEnv(path) => {
+                write!(f, "Activated virtual environment: {}", found(path))
+            }
+            Self::ActivatedCondaEnv(path) => {
+                write!(f, "Activated Conda environment: {}", found(path))
+            }
+            Self::VenvDirectory(path) => {
+                write!(f, "`{}` directory: {}", DEFAULT_VENV_DIR, found(path))
+            }
+            Self::Shebang(Some(version)) => write!(f, "Shebang: {:#}", version),
+            Self::Shebang(None) => write!(f, "Shebang: none"),
+            Self::PythonVersionFile(Some((path, version))) => write!(
+                f,
+                "`{}` file: {:#} ({})",
+                PYTHON_VERSION_FILE,
+                version,
+                path.display()
+            ),
+            Self::PythonVersionFile(None) => write!(f, "`{}` file: none", PYTHON_VERSION_FILE),
+            Self::PyprojectVersion(Some((path, version))) => write!(
+                f,
+                "`{}` `tool.py.version`: {:#} ({})",
+                PYPROJECT_FILE,
+                version,
+                path.display()
+            ),
+            Self::PyprojectVersion(None) => {
+                write!(f, "`{}` `tool.py.version`: none", PYPROJECT_FILE)
+            }
+            Self::EnvVar(name, versions) if versions.is_empty() => write!(f, "{}: unset", name),
+            Self::EnvVar(name, versions) => {
+                let versions: Vec<String> = versions
+                    .iter()
+                    .map(|version| format!("{:#}", version))
+                    .collect();
+                write!(f, "{}: {}", name, versions.join(","))
+            }
+            Self::ConfigDefault(Some(version)) => write!(f, "`default-version`: {:#}", version),
+            Self::ConfigDefault(None) => write!(f, "`default-version`: unset"),
+            Self::PathScan(version, path) => {
+                write!(f, "Searched for {}: {}", version, found(path))
+            }
+        }
+    }
+}
+
+impl ResolutionStep {
+    /// What the step checks, as listed by `--precedence`.
+    fn description(&self) -> String {
+        match self {
+            Self::ActivatedVirtualEnv(_) => {
+                "Activated virtual environment (VIRTUAL_ENV)".to_string()
+            }
+            Self::ActivatedCondaEnv(_) => "Activated Conda environment (CONDA_PREFIX)".to_string(),
+            Self::VenvDirectory(_) => format!(
+                "`{}` directory in the current or a parent directory",
+                DEFAULT_VENV_DIR
+            ),
+            Self::Shebang(_) => "Shebang of the file passed as the first argument".to_string(),
+            Self::PythonVersionFile(_) => format!(
+                "`{}` file in the current or a parent directory",
+                PYTHON_VERSION_FILE
+            ),
+            Self::PyprojectVersion(_) => format!(
+                "`tool.py.version` in a `{}` file in the current or a parent directory",
+                PYPROJECT_FILE
+            ),
+            Self::EnvVar(name, _) => format!("{} environment variable", name),
+            Self::ConfigDefault(_) => "`default-version` in the config file".to_string(),
+            Self::PathScan(RequestedVersion::Any, _) => "Python executable on PATH".to_string(),
+            Self::PathScan(version, _) => format!("{} executable on PATH", version),
+        }
+    }
+
+    /// What the step found, if anything, as listed by `--precedence`.
+    fn finding(&self) -> Option<String> {
+        match self {
+            Self::ActivatedVirtualEnv(path)
+            | Self::ActivatedCondaEnv(path)
+            | Self::VenvDirectory(path)
+            | Self::PathScan(_, path) => path.as_ref().map(|path| path.display().to_string()),
+            Self::Shebang(version) | Self::ConfigDefault(version) => {
+                version.map(|version| version.to_string())
+            }
+            Self::PythonVersionFile(found) | Self::PyprojectVersion(found) => found
+                .as_ref()
+                .map(|(path, version)| format!("{} ({})", version, path.display())),
+            Self::EnvVar(_, versions) => Some(
+                versions
+                    .iter()
+                    .map(|version| version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .filter(|versions| !versions.is_empty()),
+        }
+    }
+}
+
+/// Records `step` in `trace`, logging it as well.
+fn record(trace: &mut Vec<ResolutionStep>, step: ResolutionStep) {
+    log::debug!("{}", step);
+    trace.push(step);
+}
+
+/// Chooses the CPython executable for `version` just as running the launcher
+/// without a script would (also searching `directories`), returning every step
+/// taken along the way.
+pub fn resolve_with_trace(
+    version: RequestedVersion,
+    directories: &SearchDirectories,
+) -> (Option<PathBuf>, Vec<ResolutionStep>) {
+    let mut trace = Vec::new();
+    let executable = find_executable_traced(
+        Implementation::CPython,
+        version,
+        &[],
+        directories,
+        &mut trace,
+    )
+    .map(|(executable, _)| executable)
+    .map_err(|error| log::debug!("{}", error))
+    .ok();
+    (executable, trace)
+}
+
 /// Represents the possible outcomes based on CLI arguments.
 #[derive(Clone, Debug, Hash, PartialEq)]
 pub enum Action {
@@ -27,6 +199,26 @@ pub enum Action {
     Help(String, PathBuf),
     /// A formatted string listing all found executables.
     List(String),
+    /// The table of executables for `--list`, which is written out a line at a
+    /// time (see [`ExecutableTable::write`]) so a reader which goes away stops
+    /// it early.
+    ListTable(ExecutableTable),
+    /// A formatted string describing the order in which a Python executable
+    /// is chosen and which steps currently apply.
+    Precedence(String),
+    /// A shell completion script.
+    Complete(String),
+    /// The version of the launcher itself.
+    Version(String),
+    /// Problems found with the environment variables and config file; none
+    /// means the configuration is valid.
+    CheckConfig(Vec<String>),
+    /// A JSON report of everything the launcher found while searching for
+    /// executables.
+    Info(String),
+    /// The shell-quoted command line which would be executed, for
+    /// `--show-command`.
+    ShowCommand(String),
     /// Details for executing a found Python executable.
     Execute {
         launcher_path: PathBuf,
@@ -37,55 +229,724 @@ pub enum Action {
 
 impl Action {
     /// Parses `argv` to determine what action should be taken.
+    ///
+    /// The launcher-wide flags (see [`LauncherFlags`]) are taken out first:
+    /// any `--prepend-path DIR`/`--append-path DIR` flags add to the
+    /// directories searched (see [`SearchDirectories`]), `--isolated` has the
+    /// interpreter run in isolated mode (see [`isolate`]), `--show-command`
+    /// prints what would be executed instead of executing it (see
+    /// [`show_command`]), and `--json` has `--list` and `--where` write JSON
+    /// (see [`json_requested`]).
     pub fn from_main(argv: &[String]) -> crate::Result<Self> {
+        let (flags, argv) = LauncherFlags::take(argv)?;
+        for directory in flags.prepend.iter().chain(&flags.append) {
+            let directory = crate::expand_home(directory);
+            if !directory.is_dir() {
+                notify(
+                    flags.quiet(),
+                    &format!("Skipping nonexistent directory {}", directory.display()),
+                );
+            }
+        }
+        let directories = flags.directories();
+        let launcher_path = PathBuf::from(&argv[0]);
+        if flags.json && !json_supported(&argv) {
+            return Err(crate::Error::IllegalArgument(
+                launcher_path,
+                JSON_FLAG.to_string(),
+            ));
+        }
+        let mut action = Self::from_arguments(&argv, &flags, &directories)?;
+        if flags.isolated {
+            action = isolate(action).ok_or_else(|| {
+                crate::Error::IllegalArgument(launcher_path.clone(), ISOLATED_FLAG.to_string())
+            })?;
+        }
+        if flags.show_command {
+            action = show_command(action).ok_or_else(|| {
+                crate::Error::IllegalArgument(launcher_path, SHOW_COMMAND_FLAG.to_string())
+            })?;
+        }
+        Ok(action)
+    }
+
+    /// Parses `argv` once the launcher-wide `flags` have been removed (with
+    /// JSON output if [`json_supported`]), searching `directories` too.
+    fn from_arguments(
+        argv: &[String],
+        flags: &LauncherFlags,
+        directories: &SearchDirectories,
+    ) -> crate::Result<Self> {
         let launcher_path = PathBuf::from(&argv[0]); // Strip the path to this executable.
+        let json = flags.json;
+        let quiet = flags.quiet();
 
         match argv.get(1) {
-            Some(flag) if flag == "-h" || flag == "--help" || flag == "--list" => {
+            Some(flag) if flag == "--list" => match &argv[2..] {
+                [modifier] if modifier == "--direnv" => {
+                    let (executable, _) = find_executable(
+                        Implementation::CPython,
+                        RequestedVersion::Any,
+                        &[],
+                        directories,
+                    )?;
+                    Ok(Action::List(direnv_block(&executable)))
+                }
+                modifiers => match list_filter(modifiers) {
+                    Some(options) => {
+                        list_action(RequestedVersion::Any, options?, json, directories)
+                    }
+                    None => Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    )),
+                },
+            },
+            Some(flag) if flag == "--list-verbose" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    let executables = all_implementation_sourced_executables(directories);
+                    Ok(Action::List(list_sourced_executables(
+                        activated_venv_entry().as_ref(),
+                        &executables,
+                        &shadowed_executables(&executables, directories),
+                    )?))
+                }
+            }
+            Some(flag)
+                if argv.get(2).is_some_and(|arg| arg == "--list")
+                    && version_from_flag(flag).is_some() =>
+            {
+                match (version_from_flag(flag), list_filter(&argv[3..])) {
+                    (Some(requested), Some(options)) => {
+                        list_action(requested, options?, json, directories)
+                    }
+                    _ => Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        "--list".to_string(),
+                    )),
+                }
+            }
+            Some(flag) if flag == "-h" || flag == "--help" || flag == "--precedence" => {
                 if argv.len() > 2 {
                     Err(crate::Error::IllegalArgument(
                         launcher_path,
                         flag.to_string(),
                     ))
-                } else if flag == "--list" {
-                    Ok(Action::List(list_executables(&crate::all_executables())?))
+                } else if flag == "--precedence" {
+                    Ok(Action::Precedence(format_precedence(&precedence_steps(
+                        directories,
+                    ))))
                 } else {
-                    crate::find_executable(RequestedVersion::Any)
-                        .ok_or(crate::Error::NoExecutableFound(RequestedVersion::Any))
-                        .map(|executable_path| {
-                            Action::Help(
-                                help_message(&launcher_path, &executable_path),
-                                executable_path,
-                            )
-                        })
+                    crate::find_executable_for(
+                        Implementation::CPython,
+                        RequestedVersion::Any,
+                        directories,
+                    )
+                    .ok_or(crate::Error::NoExecutableFound(RequestedVersion::Any))
+                    .map(|executable_path| {
+                        Action::Help(
+                            help_message(
+                                &launcher_path,
+                                &executable_path,
+                                RequestedVersion::from_env_default(),
+                                &all_implementation_executables(directories),
+                            ),
+                            executable_path,
+                        )
+                    })
                 }
             }
-            // TODO: Figure out how to store the result of the version_from_flag() call.
-            Some(version) if version_from_flag(version).is_some() => {
-                Ok(Action::Execute {
+            Some(flag) if flag == "--launcher-version" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    Ok(Action::Version(env!("CARGO_PKG_VERSION").to_string()))
+                }
+            }
+            Some(flag) if flag == "--check-config" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    Ok(Action::CheckConfig(configuration_problems()))
+                }
+            }
+            Some(flag) if flag == "--default" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    default_action(directories)
+                }
+            }
+            Some(flag) if flag == "--resolve-cached" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    resolve_cached_action(directories)
+                }
+            }
+            Some(flag) if flag == "--info" => {
+                if argv.len() > 2 {
+                    Err(crate::Error::IllegalArgument(
+                        launcher_path,
+                        flag.to_string(),
+                    ))
+                } else {
+                    Ok(Action::Info(info_report(directories)))
+                }
+            }
+            Some(flag) if flag == "--complete" => match argv.get(2) {
+                Some(shell) if argv.len() == 3 => Ok(Action::Complete(
+                    Shell::from_str(shell)?.script().to_string(),
+                )),
+                _ => Err(crate::Error::IllegalArgument(
                     launcher_path,
-                    // Make sure to skip the app path and version specification.
-                    executable: find_executable(version_from_flag(version).unwrap(), &argv[2..])?,
-                    args: argv[2..].to_vec(),
-                })
+                    flag.to_string(),
+                )),
+            },
+            Some(flag) if flag == "--where" => match &argv[2..] {
+                [version] => {
+                    where_action(RequestedVersion::from_user_str(version)?, json, directories)
+                }
+                _ => Err(crate::Error::IllegalArgument(
+                    launcher_path,
+                    flag.to_string(),
+                )),
+            },
+            Some(flag) if flag == "--venv-base" => match argv.get(2) {
+                Some(venv_root) => Self::execute_venv_base(
+                    launcher_path,
+                    Path::new(venv_root),
+                    &argv[3..],
+                    directories,
+                ),
+                None => Err(crate::Error::IllegalArgument(
+                    launcher_path,
+                    flag.to_string(),
+                )),
+            },
+            Some(flag) if flag == "--python" => match argv.get(2) {
+                Some(executable) => {
+                    Self::execute_explicit(launcher_path, PathBuf::from(executable), &argv[3..])
+                }
+                None => Err(crate::Error::IllegalArgument(
+                    launcher_path,
+                    flag.to_string(),
+                )),
+            },
+            Some(flag) if flag == "--pypy" => Self::execute(
+                launcher_path,
+                Implementation::PyPy,
+                &argv[2..],
+                directories,
+                quiet,
+            ),
+            Some(flag) if flag == "--spec" => match argv.get(2) {
+                Some(spec) => {
+                    let (implementation, version) = crate::parse_spec(spec)?;
+                    Self::execute_requested(
+                        launcher_path,
+                        implementation,
+                        version,
+                        &argv[3..],
+                        directories,
+                        quiet,
+                    )
+                }
+                None => Err(crate::Error::IllegalArgument(
+                    launcher_path,
+                    flag.to_string(),
+                )),
+            },
+            Some(flag) if spec_from_flag(flag).is_some() => {
+                let (implementation, version) = spec_from_flag(flag).unwrap()?;
+                Self::execute_requested(
+                    launcher_path,
+                    implementation,
+                    version,
+                    &argv[2..],
+                    directories,
+                    quiet,
+                )
             }
-            Some(_) | None => Ok(Action::Execute {
+            // Make sure to skip the app path.
+            Some(_) | None => Self::execute(
                 launcher_path,
-                // Make sure to skip the app path.
-                executable: find_executable(RequestedVersion::Any, &argv[1..])?,
-                args: argv[1..].to_vec(),
+                Implementation::CPython,
+                &argv[1..],
+                directories,
+                quiet,
+            ),
+        }
+    }
+
+    /// Determines the executable to run based on any version flag at the start
+    /// of `args`, along with the arguments to pass to it; any notice along the
+    /// way is left out if `quiet` (see [`notify`]).
+    fn execute(
+        launcher_path: PathBuf,
+        implementation: Implementation,
+        args: &[String],
+        directories: &SearchDirectories,
+        quiet: bool,
+    ) -> crate::Result<Self> {
+        if let Some(flag) = args.first().filter(|flag| is_micro_version_flag(flag)) {
+            return Err(crate::Error::MicroVersionFlag(
+                launcher_path,
+                flag.to_string(),
+            ));
+        }
+        let (version, args) = match args.first().and_then(|flag| version_from_flag(flag)) {
+            // Make sure to skip the version specification.
+            Some(version) => (version, &args[1..]),
+            None => (RequestedVersion::Any, args),
+        };
+        Self::execute_requested(
+            launcher_path,
+            implementation,
+            version,
+            args,
+            directories,
+            quiet,
+        )
+    }
+
+    /// Determines the executable to run for an already-parsed request, along
+    /// with the arguments to pass to it.
+    fn execute_requested(
+        launcher_path: PathBuf,
+        implementation: Implementation,
+        version: RequestedVersion,
+        args: &[String],
+        directories: &SearchDirectories,
+        quiet: bool,
+    ) -> crate::Result<Self> {
+        if let Some(executable) = python_override() {
+            log::info!(
+                "Using {} from PYLAUNCHER_PYTHON instead of searching for {}",
+                executable.display(),
+                version
+            );
+            return Self::execute_explicit(launcher_path, executable, args);
+        }
+        // A `--` ends the launcher's own arguments; it is only consumed here
+        // so that one following e.g. a script name reaches the interpreter.
+        let args = match args.first() {
+            Some(separator) if separator == "--" => &args[1..],
+            _ => {
+                if let Some(flag) = misplaced_version_flag(args) {
+                    return Err(crate::Error::MisplacedVersionFlag(
+                        launcher_path,
+                        flag.to_string(),
+                    ));
+                }
+                args
+            }
+        };
+        if version != RequestedVersion::Any {
+            if let Some((Some(venv_version), venv_executable)) = activated_venv_entry() {
+                if let Some(warning) = venv_conflict(venv_version, &venv_executable, version) {
+                    notify(quiet, &warning);
+                }
+            }
+        }
+        let (executable, mut interpreter_args) =
+            match find_executable(implementation, version, args, directories) {
+                Err(error) if requests_python2(version) => {
+                    log::debug!("{}", error);
+                    return Err(crate::Error::Python2NotInstalled(version));
+                }
+                result => result?,
+            };
+        if requests_python2(version) && !python2_note_disabled() {
+            notify(
+                quiet,
+                "Python 2 reached its end of life in 2020; set PYLAUNCHER_NO_PYTHON2_NOTE to hide this note",
+            );
+        }
+        interpreter_args.extend_from_slice(args);
+
+        Ok(Action::Execute {
+            launcher_path,
+            executable,
+            args: interpreter_args,
+        })
+    }
+
+    /// Executes the interpreter matching the one which created the virtual
+    /// environment at `venv_root`.
+    fn execute_venv_base(
+        launcher_path: PathBuf,
+        venv_root: &Path,
+        args: &[String],
+        directories: &SearchDirectories,
+    ) -> crate::Result<Self> {
+        let version = venv_base_version(venv_root)?;
+        let executable =
+            find_requested_executable(Implementation::CPython, version.into(), directories)?;
+
+        Ok(Action::Execute {
+            launcher_path,
+            executable,
+            args: args.to_vec(),
+        })
+    }
+
+    /// Executes `executable` without any discovery, although a script's
+    /// shebang arguments are still passed along.
+    fn execute_explicit(
+        launcher_path: PathBuf,
+        executable: PathBuf,
+        args: &[String],
+    ) -> crate::Result<Self> {
+        let is_executable = executable
+            .metadata()
+            .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false);
+        if !is_executable {
+            return Err(crate::Error::NotExecutable(executable));
+        }
+
+        let args = match args.first() {
+            Some(separator) if separator == "--" => &args[1..],
+            _ => args,
+        };
+        let mut interpreter_args = script_shebang(args)
+            .map(|(_, shebang_args)| shebang_args)
+            .unwrap_or_default();
+        interpreter_args.extend_from_slice(args);
+
+        Ok(Action::Execute {
+            launcher_path,
+            executable,
+            args: interpreter_args,
+        })
+    }
+}
+
+/// The order in which `--list` shows executables.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ListOrder {
+    /// Newest version first (the default).
+    VersionDescending,
+    /// Oldest version first.
+    VersionAscending,
+    /// Grouped by directory, newest version first within each.
+    Path,
+}
+
+impl ListOrder {
+    /// Sorts `executables` into this order; executables which compare equal
+    /// (e.g. implementations sharing a version) keep their relative order.
+    fn sort(self, executables: &mut [(ExactVersion, PathBuf)]) {
+        match self {
+            Self::VersionDescending => executables.sort_by(|(a, _), (b, _)| b.cmp(a)),
+            Self::VersionAscending => executables.sort_by_key(|(version, _)| *version),
+            Self::Path => executables.sort_by(|(a_version, a_path), (b_version, b_path)| {
+                a_path
+                    .parent()
+                    .cmp(&b_path.parent())
+                    .then_with(|| b_version.cmp(a_version))
+            }),
+        }
+    }
+}
+
+/// What may follow `--list`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct ListOptions {
+    /// Only list executables of this [`Implementation`].
+    implementation: Option<Implementation>,
+    /// The order to list executables in.
+    order: ListOrder,
+    /// Whether to run every executable to show its full version.
+    full: bool,
+    /// Whether to group executables by major version, marking the one which
+    /// `py -MAJOR` would choose.
+    grouped: bool,
+}
+
+/// Parses what may follow `--list`: `--impl IMPL` to only list executables of
+/// that [`Implementation`], `--sort ORDER` (`version-desc`, `version-asc`, or
+/// `path`), `--full` to show each executable's full version, and `--grouped`
+/// to group them by major version, each at most once and in any order.
+/// Returns `None` if anything else follows.
+fn list_filter(modifiers: &[String]) -> Option<crate::Result<ListOptions>> {
+    let mut implementation = None;
+    let mut order = None;
+    let mut full = false;
+    let mut grouped = false;
+    let mut modifiers = modifiers.iter();
+    while let Some(flag) = modifiers.next() {
+        match flag.as_str() {
+            "--impl" if implementation.is_none() => {
+                implementation = Some(Implementation::from_str(modifiers.next()?))
+            }
+            "--sort" if order.is_none() => {
+                order = Some(match modifiers.next()?.as_str() {
+                    "version-desc" => ListOrder::VersionDescending,
+                    "version-asc" => ListOrder::VersionAscending,
+                    "path" => ListOrder::Path,
+                    _ => return None,
+                })
+            }
+            "--full" if !full => full = true,
+            "--grouped" if !grouped => grouped = true,
+            _ => return None,
+        }
+    }
+    Some(
+        implementation
+            .transpose()
+            .map(|implementation| ListOptions {
+                implementation,
+                order: order.unwrap_or(ListOrder::VersionDescending),
+                full,
+                grouped,
             }),
+    )
+}
+
+/// Lists the executables (also searching `directories`) which satisfy
+/// `requested` as `options` specify, of every [`Implementation`] unless it
+/// names one, as a table or `json`.
+fn list_action(
+    requested: RequestedVersion,
+    options: ListOptions,
+    json: bool,
+    directories: &SearchDirectories,
+) -> crate::Result<Action> {
+    let color = !json && color_enabled();
+    // Only needed for highlighting.
+    let default = color
+        .then(|| {
+            find_executable(
+                Implementation::CPython,
+                RequestedVersion::Any,
+                &[],
+                directories,
+            )
+        })
+        .and_then(Result::ok)
+        .map(|(executable, _)| executable);
+    let implementations = match options.implementation {
+        Some(implementation) => vec![implementation],
+        None => vec![Implementation::CPython, Implementation::PyPy],
+    };
+    let executables: Vec<(ExactVersion, PathBuf)> = implementations
+        .iter()
+        .flat_map(|&implementation| crate::all_executables_for(implementation, directories))
+        .filter(|(version, _)| requested.matches(version) != VersionMatch::NotAtAll)
+        .collect();
+    if executables.is_empty() {
+        return Err(crate::Error::NoExecutableFound(requested));
+    }
+    // Running every executable is slow, hence it's opt-in.
+    let full_versions = options
+        .full
+        .then(|| crate::reported_versions(executables.iter().map(|(_, path)| path.as_path())));
+    if options.grouped {
+        let defaults = major_defaults(&executables, directories);
+        return Ok(Action::List(if json {
+            list_grouped_json(
+                executables,
+                options.order,
+                full_versions.as_ref(),
+                &defaults,
+            )
+        } else {
+            list_grouped(
+                &executables,
+                options.order,
+                full_versions.as_ref(),
+                &defaults,
+            )
+        }));
+    }
+    if json {
+        return Ok(Action::List(list_json(
+            executables,
+            options.order,
+            full_versions.as_ref(),
+        )));
+    }
+    // Those which don't report their version still get a row, but can't
+    // satisfy a request for a specific version.
+    let unknown = if requested == RequestedVersion::Any {
+        implementations
+            .iter()
+            .flat_map(|&implementation| {
+                crate::unknown_version_executables_for(implementation, directories)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(Action::ListTable(ExecutableTable {
+        executables,
+        unknown,
+        order: options.order,
+        full_versions: full_versions.map(BTreeMap::from_iter),
+        default_request: RequestedVersion::from_env_default(),
+        default,
+        color,
+    }))
+}
+
+/// Finds the executable which `py -MAJOR` would run for the major version of
+/// each of `executables`, exactly as running it would (e.g. honouring
+/// `PY_PYTHON3`).
+fn major_defaults(
+    executables: &[(ExactVersion, PathBuf)],
+    directories: &SearchDirectories,
+) -> HashMap<u16, PathBuf> {
+    let mut majors: Vec<u16> = executables
+        .iter()
+        .map(|(version, _)| version.major)
+        .collect();
+    majors.sort_unstable();
+    majors.dedup();
+    majors
+        .into_iter()
+        .filter_map(|major| {
+            find_executable(
+                Implementation::CPython,
+                RequestedVersion::MajorOnly(major),
+                &[],
+                directories,
+            )
+            .ok()
+            .map(|(path, _)| (major, path))
+        })
+        .collect()
+}
+
+/// Lists the path of every CPython executable satisfying `requested`, one per
+/// line (or as a `json` array), in the order they are searched (including
+/// `directories`).
+fn where_action(
+    requested: RequestedVersion,
+    json: bool,
+    directories: &SearchDirectories,
+) -> crate::Result<Action> {
+    let executables =
+        crate::all_matching_executables_for(Implementation::CPython, requested, directories);
+    if executables.is_empty() {
+        return Err(crate::Error::NoExecutableFound(requested));
+    }
+    if json {
+        let paths: Vec<_> = executables
+            .iter()
+            .map(|executable| json_path(executable))
+            .collect();
+        return Ok(Action::List(
+            serde_json::to_string_pretty(&paths).unwrap() + "\n",
+        ));
+    }
+    let mut output = String::new();
+    for executable in executables {
+        writeln!(output, "{}", executable.display()).unwrap();
+    }
+    Ok(Action::List(output))
+}
+
+/// Prints the version and path, separated by a tab, of the CPython executable
+/// chosen when no version is requested.
+///
+/// The version comes from the executable's file name or, for a virtual
+/// environment's `python`, its `pyvenv.cfg`; the executable is never run, so
+/// a version which can't be determined that way is shown as `?`.
+fn default_action(directories: &SearchDirectories) -> crate::Result<Action> {
+    let (executable, _) = find_executable(
+        Implementation::CPython,
+        RequestedVersion::Any,
+        &[],
+        directories,
+    )?;
+    let version = ExactVersion::from_path(&executable)
+        .ok()
+        .or_else(|| {
+            let venv_root = executable.parent()?.parent()?;
+            venv_base_version(venv_root)
+                .map_err(|error| log::debug!("{}", error))
+                .ok()
+        })
+        .map_or_else(|| "?".to_string(), |version| version.to_string());
+    Ok(Action::List(format!(
+        "{}\t{}\n",
+        version,
+        executable.display()
+    )))
+}
+
+/// Like [`default_action`], but using the [cache](crate::cache) when nothing
+/// which influences the choice has changed and the cached executable still
+/// exists.
+fn resolve_cached_action(directories: &SearchDirectories) -> crate::Result<Action> {
+    let searched: Vec<PathBuf> = crate::path_entries(directories)
+        .into_iter()
+        .map(|(directory, _)| directory)
+        .collect();
+    let key = crate::cache::cache_key(&searched);
+    if let Some(output) = crate::cache::read(&key) {
+        let cached_executable = output
+            .trim_end_matches('\n')
+            .split_once('\t')
+            .map(|(_, executable)| Path::new(executable));
+        if cached_executable.is_some_and(Path::exists) {
+            return Ok(Action::List(output));
         }
+        log::debug!("Ignoring the cached {:?} as it no longer exists", output);
     }
+    let action = default_action(directories)?;
+    if let Action::List(output) = &action {
+        crate::cache::write(&key, output);
+    }
+    Ok(action)
 }
 
-fn help_message(launcher_path: &Path, executable_path: &Path) -> String {
+fn help_message(
+    launcher_path: &Path,
+    executable_path: &Path,
+    default_request: RequestedVersion,
+    executables: &[(ExactVersion, PathBuf)],
+) -> String {
+    // Doubling as a discovery aid means showing what `--list` would, or why
+    // there is nothing to show.
+    let installed = list_executables(&ExecutableTable {
+        executables: executables.to_vec(),
+        unknown: Vec::new(),
+        order: ListOrder::VersionDescending,
+        full_versions: None,
+        default_request,
+        default: None,
+        color: false,
+    })
+    .unwrap_or_else(|error| format!("{}\n", error));
     let mut message = String::new();
     writeln!(
         message,
         include_str!("HELP.txt"),
         env!("CARGO_PKG_VERSION"),
         launcher_path.to_string_lossy(),
+        format!("{:#}", default_request),
+        installed,
         executable_path.to_string_lossy()
     )
     .unwrap();
@@ -96,23 +957,714 @@ fn help_message(launcher_path: &Path, executable_path: &Path) -> String {
 ///
 /// It is assumed that the flag from the command-line is passed as-is
 /// (i.e. the flag starts with `-`).
+///
+/// A bare `-` is how Python is told to read from stdin, so it is never treated
+/// as a version specifier.
 fn version_from_flag(arg: &str) -> Option<RequestedVersion> {
-    if !arg.starts_with('-') {
-        None
+    match arg.strip_prefix('-') {
+        Some(version) if !version.is_empty() => match RequestedVersion::from_str(version) {
+            Ok(requested_version) => Some(requested_version),
+            Err(error) => {
+                // Only a flag starting with a digit could have been meant as a
+                // version; anything else (e.g. `-u`) is one of Python's flags.
+                if version.starts_with(|c: char| c.is_ascii_digit()) {
+                    log::debug!("Not treating {:?} as a version flag: {}", arg, error);
+                }
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Whether `arg` is a version flag with a micro version (e.g. `-3.11.4`); only
+/// `X.Y` may be requested, but such a flag is clearly meant as one rather than
+/// for the interpreter.
+fn is_micro_version_flag(arg: &str) -> bool {
+    arg.strip_prefix('-')
+        .and_then(|version| version.rsplit_once('.'))
+        .is_some_and(|(major_minor, micro)| {
+            ExactVersion::from_str(major_minor).is_ok()
+                && !micro.is_empty()
+                && micro.bytes().all(|byte| byte.is_ascii_digit())
+        })
+}
+
+/// Parses a CLI flag which is a version spec naming an implementation (e.g.
+/// `-pypy@3.9`; see [`crate::parse_spec`]), or returns `None` if the flag isn't
+/// one.
+fn spec_from_flag(arg: &str) -> Option<crate::Result<(Implementation, RequestedVersion)>> {
+    arg.strip_prefix('-')
+        .filter(|spec| spec.contains('@'))
+        .map(crate::parse_spec)
+}
+
+/// Collects the executables of every [`Implementation`], with CPython first.
+fn all_implementation_executables(directories: &SearchDirectories) -> Vec<(ExactVersion, PathBuf)> {
+    [Implementation::CPython, Implementation::PyPy]
+        .iter()
+        .flat_map(|&implementation| crate::all_executables_for(implementation, directories))
+        .collect()
+}
+
+/// Like [`all_implementation_executables`], but with where each executable was
+/// found.
+fn all_implementation_sourced_executables(
+    directories: &SearchDirectories,
+) -> Vec<(ExactVersion, PathBuf, crate::Source)> {
+    [Implementation::CPython, Implementation::PyPy]
+        .iter()
+        .flat_map(|&implementation| crate::all_sourced_executables_for(implementation, directories))
+        .map(|(version, (path, source))| (version, path, source))
+        .collect()
+}
+
+/// The executables of every [`Implementation`] which are never chosen because
+/// one in `executables` (as found by [`all_implementation_sourced_executables`])
+/// provides the same version.
+fn shadowed_executables(
+    executables: &[(ExactVersion, PathBuf, crate::Source)],
+    directories: &SearchDirectories,
+) -> Vec<(ExactVersion, PathBuf, crate::Source)> {
+    let is_chosen = |path: &PathBuf| executables.iter().any(|(_, chosen, _)| chosen == path);
+    [Implementation::CPython, Implementation::PyPy]
+        .iter()
+        .flat_map(|&implementation| {
+            crate::all_sourced_executables_in_search_order(implementation, directories)
+        })
+        .filter(|(_, path, _)| !is_chosen(path))
+        .collect()
+}
+
+/// Writes `notice` to stderr unless `quiet`: something worth knowing about
+/// the launcher's choice, but not an error, so it doesn't depend on
+/// `PYLAUNCH_DEBUG` like logging does.
+fn notify(quiet: bool, notice: &str) {
+    if !quiet {
+        eprintln!("{}", notice);
+    }
+}
+
+/// A warning that the activated virtual environment, whose interpreter is
+/// `venv_executable`, doesn't satisfy the explicitly `requested` version which
+/// is used instead; `None` if it does.
+fn venv_conflict(
+    venv_version: ExactVersion,
+    venv_executable: &Path,
+    requested: RequestedVersion,
+) -> Option<String> {
+    (!venv_version.supports(requested)).then(|| {
+        format!(
+            "the activated virtual environment ({}) is Python {}; using {} as requested",
+            venv_executable.display(),
+            venv_version,
+            requested
+        )
+    })
+}
+
+/// The version (if it can be determined) and executable of the activated
+/// virtual environment.
+///
+/// The version comes from its `pyvenv.cfg`, or else from running its
+/// executable.
+fn activated_venv_entry() -> Option<(Option<ExactVersion>, PathBuf)> {
+    let venv_root = PathBuf::from(env::var_os("VIRTUAL_ENV")?);
+    let executable = activated_venv()?;
+    let version = venv_base_version(&venv_root)
+        .map_err(|error| log::debug!("{}", error))
+        .ok()
+        .or_else(|| crate::reported_exact_version(&executable));
+    Some((version, executable))
+}
+
+/// The launcher flag which silences the launcher's own output to stderr.
+static QUIET_FLAG: &str = "--quiet";
+
+/// The launcher flags which add a directory to search for interpreters.
+static PREPEND_PATH_FLAG: &str = "--prepend-path";
+static APPEND_PATH_FLAG: &str = "--append-path";
+
+/// The launcher's commands which don't launch an interpreter, so every
+/// argument following one is the launcher's own.
+static COMMAND_FLAGS: [&str; 12] = [
+    "--list",
+    "--list-verbose",
+    "--where",
+    "--info",
+    "-h",
+    "--help",
+    "--precedence",
+    "--launcher-version",
+    "--check-config",
+    "--default",
+    "--resolve-cached",
+    "--complete",
+];
+
+/// The launcher's flags which choose the interpreter to launch and are
+/// followed by a value (e.g. `--python PATH`).
+static SELECTOR_FLAGS: [&str; 3] = ["--python", "--venv-base", "--spec"];
+
+/// The launcher-wide flags, which apply whatever else is requested and may be
+/// given in any order anywhere among the launcher's own arguments.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct LauncherFlags {
+    /// `--quiet` (see [`LauncherFlags::quiet`]).
+    quiet: bool,
+    /// The directories of any `--prepend-path DIR` flags.
+    prepend: Vec<PathBuf>,
+    /// The directories of any `--append-path DIR` flags.
+    append: Vec<PathBuf>,
+    /// `--show-command` (see [`show_command`]).
+    show_command: bool,
+    /// `--isolated` (see [`isolate`]).
+    isolated: bool,
+    /// `--json` (see [`json_requested`]).
+    json: bool,
+}
+
+impl LauncherFlags {
+    /// Whether `--quiet` applies: it was given, and `PYLAUNCH_DEBUG` doesn't
+    /// explicitly ask for logging, which wins over it.
+    fn quiet(&self) -> bool {
+        self.quiet && log_verbosity(env::var_os("PYLAUNCH_DEBUG").as_deref()) == DEFAULT_VERBOSITY
+    }
+
+    /// The directories added to the search by `--prepend-path` and
+    /// `--append-path`.
+    fn directories(&self) -> SearchDirectories {
+        SearchDirectories::new(&self.prepend, &self.append)
+    }
+
+    /// Removes the launcher-wide flags from `argv`, returning them along with
+    /// what remains; a path flag without a directory is an error.
+    ///
+    /// They are taken from among the launcher's own arguments: those choosing
+    /// the interpreter (e.g. `-3` or `--python PATH`) and, as nothing is
+    /// launched, everything from a command such as `--list` onwards. The first
+    /// other argument (e.g. a script, or `--`) is the interpreter's, as is
+    /// everything after it.
+    fn take(argv: &[String]) -> crate::Result<(Self, Vec<String>)> {
+        let mut flags = Self::default();
+        let mut remaining = vec![argv[0].clone()];
+        let mut command = false;
+        let mut index = 1;
+        while let Some(flag) = argv.get(index) {
+            match flag.as_str() {
+                flag if flag == QUIET_FLAG => flags.quiet = true,
+                flag if flag == SHOW_COMMAND_FLAG => flags.show_command = true,
+                flag if flag == ISOLATED_FLAG => flags.isolated = true,
+                flag if flag == JSON_FLAG => flags.json = true,
+                flag if flag == PREPEND_PATH_FLAG || flag == APPEND_PATH_FLAG => {
+                    let directory = argv.get(index + 1).ok_or_else(|| {
+                        crate::Error::IllegalArgument(PathBuf::from(&argv[0]), flag.to_string())
+                    })?;
+                    if flag == PREPEND_PATH_FLAG {
+                        flags.prepend.push(PathBuf::from(directory));
+                    } else {
+                        flags.append.push(PathBuf::from(directory));
+                    }
+                    index += 1;
+                }
+                flag if SELECTOR_FLAGS.contains(&flag) && !command => {
+                    remaining.extend(argv[index..].iter().take(2).cloned());
+                    index += 1;
+                }
+                flag if command
+                    || COMMAND_FLAGS.contains(&flag)
+                    || flag == "--pypy"
+                    || version_from_flag(flag).is_some()
+                    || spec_from_flag(flag).is_some()
+                    || is_micro_version_flag(flag) =>
+                {
+                    command = command || COMMAND_FLAGS.contains(&flag);
+                    remaining.push(flag.to_string());
+                }
+                _ => break,
+            }
+            index += 1;
+        }
+
+        remaining.extend_from_slice(&argv[index.min(argv.len())..]);
+        Ok((flags, remaining))
+    }
+}
+
+/// The launcher flag which prints what would be executed instead of executing
+/// it.
+static SHOW_COMMAND_FLAG: &str = "--show-command";
+
+/// Turns an [`Action::Execute`] into an [`Action::ShowCommand`] with the
+/// interpreter's path followed by every argument it would be given (including
+/// those from a script's shebang), each quoted by [`shell_quote`].
+///
+/// Any other action doesn't execute anything, so `None` is returned.
+fn show_command(action: Action) -> Option<Action> {
+    match action {
+        Action::Execute {
+            executable, args, ..
+        } => {
+            let words: Vec<String> = Some(executable.to_string_lossy().into_owned())
+                .into_iter()
+                .chain(args)
+                .map(|word| shell_quote(&word))
+                .collect();
+            Some(Action::ShowCommand(words.join(" ")))
+        }
+        _ => None,
+    }
+}
+
+/// The launcher flag which runs the interpreter in isolated mode.
+static ISOLATED_FLAG: &str = "--isolated";
+
+/// Python's own flag for isolated mode.
+static PYTHON_ISOLATED_FLAG: &str = "-I";
+
+/// Adds Python's `-I` flag to the start of the interpreter arguments of an
+/// [`Action::Execute`], unless they already include it (e.g. from a script's
+/// shebang).
+///
+/// Any other action can't be isolated, so `None` is returned.
+fn isolate(action: Action) -> Option<Action> {
+    match action {
+        Action::Execute {
+            launcher_path,
+            executable,
+            mut args,
+        } => {
+            // Only the arguments ahead of e.g. a script are the interpreter's.
+            let isolated = args
+                .iter()
+                .take_while(|arg| arg.starts_with('-'))
+                .any(|arg| arg == PYTHON_ISOLATED_FLAG);
+            if !isolated {
+                args.insert(0, PYTHON_ISOLATED_FLAG.to_string());
+            }
+            Some(Action::Execute {
+                launcher_path,
+                executable,
+                args,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The launcher flag which has informational output and errors written as JSON.
+static JSON_FLAG: &str = "--json";
+
+/// Whether `argv` has a `--json` flag among its launcher-wide flags, in
+/// which case an error should be reported with [`json_error`].
+pub fn json_requested(argv: &[String]) -> bool {
+    LauncherFlags::take(argv).is_ok_and(|(flags, _)| flags.json)
+}
+
+/// Whether the command in `argv` (sans launcher-wide flags) can write JSON:
+/// `--list` (optionally after a version flag, but not with `--direnv`),
+/// `--where`, and `--info` (which always does).
+fn json_supported(argv: &[String]) -> bool {
+    match argv.get(1).map(String::as_str) {
+        Some("--list") => argv.get(2).is_none_or(|modifier| modifier != "--direnv"),
+        Some("--where") | Some("--info") => true,
+        Some(flag) => {
+            version_from_flag(flag).is_some() && argv.get(2).is_some_and(|arg| arg == "--list")
+        }
+        None => false,
+    }
+}
+
+/// Formats `error` as a JSON object with an `error` message and the versions
+/// of Python which are `available` (newest first, including any in the
+/// directories added by `argv`) for choosing another.
+pub fn json_error(error: &crate::Error, argv: &[String]) -> String {
+    let mut available: Vec<ExactVersion> = match error {
+        crate::Error::NoMatchingExecutable(_, versions) => versions.clone(),
+        _ => {
+            let directories = LauncherFlags::take(argv)
+                .map(|(flags, _)| flags.directories())
+                .unwrap_or_default();
+            crate::all_executables_for(Implementation::CPython, &directories)
+                .into_keys()
+                .collect()
+        }
+    };
+    available.sort_unstable_by(|a, b| b.cmp(a));
+    available.dedup();
+    let report = json!({
+        "error": error.to_string(),
+        "available": available.iter().map(ExactVersion::to_string).collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+/// The [`log_verbosity`] when no logging is requested: only errors.
+pub const DEFAULT_VERBOSITY: usize = 0;
+
+/// Whether `argv` has a `--quiet` flag among its launcher-wide flags (and
+/// `PYLAUNCH_DEBUG` doesn't ask for logging, which wins), in which case the
+/// launcher's notices (e.g. a skipped `--prepend-path` directory) and the
+/// error for an interpreter which can't be found (see
+/// [`crate::Error::is_not_found`]) are left out of stderr, leaving the exit
+/// status to report the failure; any other error is still written.
+pub fn quiet_requested(argv: &[String]) -> bool {
+    LauncherFlags::take(argv).is_ok_and(|(flags, _)| flags.quiet())
+}
+
+/// The [`stderrlog`](https://docs.rs/stderrlog) verbosity for the value of
+/// the `PYLAUNCH_DEBUG` environment variable.
+///
+/// `1` logs only the interpreter chosen and why (e.g. which environment
+/// variable requested its version), while `2` (or higher) also logs every
+/// check made along with each directory and candidate examined. Any other value means
+/// verbose, as setting the variable at all originally did.
+pub fn log_verbosity(pylaunch_debug: Option<&OsStr>) -> usize {
+    // [error, warn, info, debug, trace]
+    match pylaunch_debug.map(|value| value.to_string_lossy().parse::<u32>()) {
+        None | Some(Ok(0)) => DEFAULT_VERBOSITY,
+        Some(Ok(1)) => 2,
+        Some(_) => 3,
+    }
+}
+
+/// Whether output should be colorized, based on whether stdout is a terminal
+/// and the `NO_COLOR` and `CLICOLOR_FORCE` environment variables (see
+/// [`color_wanted`]).
+fn color_enabled() -> bool {
+    color_wanted(
+        io::stdout().is_terminal(),
+        env::var_os("NO_COLOR").as_deref(),
+        env::var_os("CLICOLOR_FORCE").as_deref(),
+    )
+}
+
+/// Decides whether to colorize output: a non-empty `no_color` always disables
+/// color, then a `clicolor_force` which is neither empty nor `0` enables it
+/// even when not writing to a terminal, and otherwise color is only used for a
+/// terminal.
+fn color_wanted(
+    is_terminal: bool,
+    no_color: Option<&OsStr>,
+    clicolor_force: Option<&OsStr>,
+) -> bool {
+    if no_color.is_some_and(|value| !value.is_empty()) {
+        false
+    } else if clicolor_force.is_some_and(|value| !value.is_empty() && value != "0") {
+        true
     } else {
-        RequestedVersion::from_str(&arg[1..]).ok()
+        is_terminal
     }
 }
 
-fn list_executables(executables: &HashMap<ExactVersion, PathBuf>) -> crate::Result<String> {
-    if executables.is_empty() {
+/// The table of executables `--list` shows (see [`executables_table`]).
+#[derive(Clone, Debug, Hash, PartialEq)]
+pub struct ExecutableTable {
+    pub executables: Vec<(ExactVersion, PathBuf)>,
+    /// Executables whose version couldn't be determined, which are listed
+    /// last as [`UNKNOWN_VERSION`].
+    pub unknown: Vec<PathBuf>,
+    pub order: ListOrder,
+    pub full_versions: Option<BTreeMap<PathBuf, String>>,
+    pub default_request: RequestedVersion,
+    pub default: Option<PathBuf>,
+    pub color: bool,
+}
+
+impl ExecutableTable {
+    /// Writes the table to `writer` (e.g. a locked stdout) a line at a time;
+    /// see [`write_executables`].
+    pub fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        write_executables(writer, self)
+    }
+}
+
+impl std::fmt::Display for ExecutableTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut output = Vec::new();
+        self.write(&mut output)
+            .expect("writing to a Vec can't fail");
+        f.write_str(std::str::from_utf8(&output).expect("the table is built from strings"))
+    }
+}
+
+/// Formats `listing` as a table (see [`executables_table`]).
+fn list_executables(listing: &ExecutableTable) -> crate::Result<String> {
+    if listing.executables.is_empty() && listing.unknown.is_empty() {
+        return Err(crate::Error::NoExecutableFound(RequestedVersion::Any));
+    }
+
+    Ok(executables_table(listing).to_string() + "\n")
+}
+
+/// Writes the table which [`list_executables`] returns for `listing` to
+/// `writer` (e.g. a locked stdout).
+///
+/// The table is rendered as a whole, as sorting the rows and aligning the
+/// columns depends on every executable, but it's written a line at a time so a
+/// reader which goes away (e.g. `py --list | head -1`) stops the output with
+/// the resulting error.
+pub fn write_executables(writer: &mut impl io::Write, listing: &ExecutableTable) -> io::Result<()> {
+    for line in executables_table(listing).to_string().lines() {
+        writer.write_all(format!("{}\n", line).as_bytes())?;
+    }
+    Ok(())
+}
+
+/// The table of the executables in `listing`, in its `order`, followed by
+/// those whose version is unknown.
+///
+/// With `full_versions`, a third column shows the full version each executable
+/// reported (or `?` if it reported none).
+///
+/// With `color`, the table gains a bold header naming `default_request` (the
+/// version searched for when none is specified) and `default` (the executable
+/// which would be chosen by default) is highlighted; otherwise the output is
+/// plain text which is meant to be easy to parse.
+fn executables_table(listing: &ExecutableTable) -> Table {
+    let mut executable_pairs = listing.executables.clone();
+    listing.order.sort(&mut executable_pairs);
+    let rows = executable_pairs
+        .into_iter()
+        .map(|(version, path)| (version.to_string(), path))
+        .chain(
+            listing
+                .unknown
+                .iter()
+                .map(|path| (UNKNOWN_VERSION.to_string(), path.clone())),
+        );
+
+    let mut table = plain_table();
+    if listing.color {
+        table.enforce_styling();
+        let mut header = vec![
+            Cell::new(format!("Version (default: {:#})", listing.default_request))
+                .add_attribute(Attribute::Bold),
+            Cell::new("Path").add_attribute(Attribute::Bold),
+        ];
+        if listing.full_versions.is_some() {
+            header.push(Cell::new("Full version").add_attribute(Attribute::Bold));
+        }
+        table.set_header(header);
+    }
+
+    for (version, path) in rows {
+        // Shims which couldn't be resolved run whichever interpreter pyenv
+        // selects, which may not be the listed version.
+        let path_cell = if crate::is_pyenv_shim(&path) {
+            Cell::new(format!("{} (pyenv shim)", path.display()))
+        } else {
+            Cell::new(path.display())
+        };
+        let mut cells = vec![Cell::new(version), path_cell];
+        if let Some(full_versions) = &listing.full_versions {
+            cells.push(Cell::new(
+                full_versions.get(&path).map_or("?", String::as_str),
+            ));
+        }
+        if listing.color && listing.default.as_ref() == Some(&path) {
+            cells = cells
+                .into_iter()
+                .map(|cell| cell.fg(Color::Green).add_attribute(Attribute::Bold))
+                .collect();
+        }
+        table.add_row(cells);
+    }
+    table
+}
+
+/// Represents `path` in JSON output: a string if it is valid UTF-8, otherwise
+/// an object holding both a `lossy` string (with invalid sequences replaced
+/// by U+FFFD) and the path's exact `bytes`, so that consumers can't mistake
+/// the lossy form for the real path.
+fn json_path(path: &Path) -> serde_json::Value {
+    match path.to_str() {
+        Some(path) => json!(path),
+        None => json!({
+            "lossy": path.to_string_lossy(),
+            "bytes": path.as_os_str().as_bytes(),
+        }),
+    }
+}
+
+/// Formats `executables` as a JSON array of objects with the `version` and
+/// `path` of each, in the specified `order`, along with each `full_version`
+/// (`null` if unknown) if `full_versions` is provided.
+fn list_json(
+    mut executables: Vec<(ExactVersion, PathBuf)>,
+    order: ListOrder,
+    full_versions: Option<&HashMap<PathBuf, String>>,
+) -> String {
+    order.sort(&mut executables);
+    serde_json::to_string_pretty(&json_entries(&executables, full_versions)).unwrap() + "\n"
+}
+
+/// The objects which [`list_json`] lists `executables` as.
+fn json_entries(
+    executables: &[(ExactVersion, PathBuf)],
+    full_versions: Option<&HashMap<PathBuf, String>>,
+) -> Vec<serde_json::Value> {
+    executables
+        .iter()
+        .map(|(version, path)| {
+            let mut entry = json!({
+                "version": version.to_string(),
+                "path": json_path(path),
+            });
+            if let Some(full_versions) = full_versions {
+                entry["full_version"] = json!(full_versions.get(path));
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Splits `executables` by major version, newest first (oldest first when
+/// `order` is [`ListOrder::VersionAscending`]), with each group sorted in
+/// `order`.
+fn group_by_major(
+    executables: &[(ExactVersion, PathBuf)],
+    order: ListOrder,
+) -> Vec<(u16, Vec<(ExactVersion, PathBuf)>)> {
+    let mut majors: Vec<u16> = executables
+        .iter()
+        .map(|(version, _)| version.major)
+        .collect();
+    majors.sort_unstable();
+    majors.dedup();
+    if order != ListOrder::VersionAscending {
+        majors.reverse();
+    }
+    majors
+        .into_iter()
+        .map(|major| {
+            let mut group: Vec<_> = executables
+                .iter()
+                .filter(|(version, _)| version.major == major)
+                .cloned()
+                .collect();
+            order.sort(&mut group);
+            (major, group)
+        })
+        .collect()
+}
+
+/// Formats `executables` as a table per major version, each under a
+/// `Python MAJOR` line, like [`list_executables`] does without color. A last
+/// column marks the executable which `py -MAJOR` runs according to `defaults`
+/// (which may not be listed, e.g. an unversioned `python3`).
+fn list_grouped(
+    executables: &[(ExactVersion, PathBuf)],
+    order: ListOrder,
+    full_versions: Option<&HashMap<PathBuf, String>>,
+    defaults: &HashMap<u16, PathBuf>,
+) -> String {
+    let mut output = String::new();
+    for (major, group) in group_by_major(executables, order) {
+        let mut table = plain_table();
+        for (version, path) in group {
+            let mut cells = vec![version.to_string(), path.display().to_string()];
+            if let Some(full_versions) = full_versions {
+                cells.push(
+                    full_versions
+                        .get(&path)
+                        .map_or("?", String::as_str)
+                        .to_string(),
+                );
+            }
+            cells.push(if defaults.get(&major) == Some(&path) {
+                format!("py -{}", major)
+            } else {
+                String::new()
+            });
+            table.add_row(cells);
+        }
+        output.push_str(&format!("Python {}\n{}\n", major, table));
+    }
+    output
+}
+
+/// Formats `executables` as a JSON array with an object per major version
+/// (ordered as [`list_grouped`] orders them) holding the `major` version, the
+/// `default` path which `py -MAJOR` runs (`null` if it isn't listed), and the
+/// `interpreters` as [`list_json`] lists them.
+fn list_grouped_json(
+    executables: Vec<(ExactVersion, PathBuf)>,
+    order: ListOrder,
+    full_versions: Option<&HashMap<PathBuf, String>>,
+    defaults: &HashMap<u16, PathBuf>,
+) -> String {
+    let groups: Vec<_> = group_by_major(&executables, order)
+        .into_iter()
+        .map(|(major, group)| {
+            let default = defaults
+                .get(&major)
+                .filter(|default| group.iter().any(|(_, path)| path == *default));
+            json!({
+                "major": major,
+                "default": default.map(|path| json_path(path)),
+                "interpreters": json_entries(&group, full_versions),
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&groups).unwrap() + "\n"
+}
+
+/// What the version column of a listing says for an executable whose version
+/// couldn't be determined.
+static UNKNOWN_VERSION: &str = "unknown";
+
+/// Formats `executables` as a table like [`list_executables`] does, but with a
+/// third column stating where each executable came from. The activated virtual
+/// environment, if any, comes first (labeled [`UNKNOWN_VERSION`] if its version
+/// is unknown), and each of the `shadowed` executables follows the one in
+/// `executables` with the same version (in the order given).
+fn list_sourced_executables(
+    venv: Option<&(Option<ExactVersion>, PathBuf)>,
+    executables: &[(ExactVersion, PathBuf, crate::Source)],
+    shadowed: &[(ExactVersion, PathBuf, crate::Source)],
+) -> crate::Result<String> {
+    if venv.is_none() && executables.is_empty() {
         return Err(crate::Error::NoExecutableFound(RequestedVersion::Any));
     }
 
-    let mut executable_pairs = Vec::from_iter(executables);
-    executable_pairs.sort_unstable();
-    executable_pairs.reverse();
+    let mut table = plain_table();
+    if let Some((version, path)) = venv {
+        let version = version.map_or_else(
+            || UNKNOWN_VERSION.to_string(),
+            |version| version.to_string(),
+        );
+        table.add_row(vec![
+            version,
+            path.display().to_string(),
+            "activated virtual environment (VIRTUAL_ENV)".to_string(),
+        ]);
+    }
+
+    let mut rows: Vec<_> = executables
+        .iter()
+        .map(|(version, path, source)| (version, path, source.to_string()))
+        .chain(
+            shadowed
+                .iter()
+                .map(|(version, path, source)| (version, path, format!("{} (shadowed)", source))),
+        )
+        .collect();
+    // A stable sort keeps shadowed executables after the one chosen instead.
+    rows.sort_by(|(a, _, _), (b, _, _)| b.cmp(a));
+    for (version, path, source) in rows {
+        table.add_row(vec![
+            version.to_string(),
+            path.display().to_string(),
+            source,
+        ]);
+    }
+
+    Ok(table.to_string() + "\n")
+}
 
+/// A table with no decorations other than vertical lines between columns.
+fn plain_table() -> Table {
     let mut table = Table::new();
     table.load_preset(comfy_table::presets::NOTHING);
     // Using U+2502/"Box Drawings Light Vertical" over
@@ -120,12 +1672,33 @@ fn list_executables(executables: &HashMap<ExactVersion, PathBuf>) -> crate::Resu
     // Leaving out a header and other decorations to make it easier
     // parse the output.
     table.set_style(TableComponent::VerticalLines, '│');
+    // Styling is decided by the caller rather than by comfy-table's own
+    // terminal detection.
+    table.force_no_tty();
+    table
+}
 
-    for (version, path) in executable_pairs {
-        table.add_row(vec![version.to_string(), path.display().to_string()]);
-    }
+/// Quotes `text` for use as a single word in a bash-compatible shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
 
-    Ok(table.to_string() + "\n")
+/// Formats a [direnv](https://direnv.net/) `.envrc` snippet which makes the
+/// default executable's directory and version the ones used in a directory.
+fn direnv_block(executable: &Path) -> String {
+    let mut block = String::new();
+    if let Ok(version) = ExactVersion::from_path(executable) {
+        writeln!(block, "export PY_PYTHON={}", version).unwrap();
+    }
+    if let Some(directory) = executable.parent() {
+        writeln!(
+            block,
+            "PATH_add {}",
+            shell_quote(&directory.to_string_lossy())
+        )
+        .unwrap();
+    }
+    block
 }
 
 fn relative_venv_path(add_default: bool) -> PathBuf {
@@ -138,130 +1711,933 @@ fn relative_venv_path(add_default: bool) -> PathBuf {
     path
 }
 
+/// The names a virtual environment's executable may have in its `bin`
+/// directory, in order of preference.
+static VENV_EXECUTABLE_NAMES: [&str; 2] = ["python", "python3"];
+
 /// Returns the path to the activated virtual environment's executable.
 ///
 /// A virtual environment is determined to be activated based on the
 /// existence of the `VIRTUAL_ENV` environment variable.
+///
+/// If none of [`VENV_EXECUTABLE_NAMES`] exist then `bin/python` is returned
+/// regardless, so that the missing executable is what gets reported.
 fn venv_executable_path(venv_root: &str) -> PathBuf {
+    let bin_dir = PathBuf::from(venv_root).join("bin");
+    if let Some(executable) = VENV_EXECUTABLE_NAMES
+        .iter()
+        .map(|name| bin_dir.join(name))
+        .find(|executable| executable.is_file())
+    {
+        return executable;
+    }
+
+    // A virtual environment created on Windows (e.g. on a shared volume) can't
+    // be used, but it's worth explaining why.
+    let windows_executable = PathBuf::from(venv_root).join("Scripts").join("python.exe");
+    if windows_executable.is_file() {
+        log::debug!(
+            "{} is from a virtual environment created on Windows",
+            windows_executable.display()
+        );
+    }
     PathBuf::from(venv_root).join(relative_venv_path(false))
-    // XXX: Do a is_file() check first?
 }
 
-fn activated_venv() -> Option<PathBuf> {
-    log::info!("Checking for VIRTUAL_ENV environment variable");
-    env::var_os("VIRTUAL_ENV").map(|venv_root| {
-        log::debug!("VIRTUAL_ENV set to {:?}", venv_root);
-        venv_executable_path(&venv_root.to_string_lossy())
-    })
+fn activated_venv() -> Option<PathBuf> {
+    log::debug!("Checking for VIRTUAL_ENV environment variable");
+    env::var_os("VIRTUAL_ENV").map(|venv_root| {
+        log::debug!("VIRTUAL_ENV set to {:?}", venv_root);
+        venv_executable_path(&venv_root.to_string_lossy())
+    })
+}
+
+/// The `python` executable of the activated Conda environment (per the
+/// `CONDA_PREFIX` environment variable), if it exists.
+fn activated_conda() -> Option<PathBuf> {
+    log::debug!("Checking for CONDA_PREFIX environment variable");
+    let conda_prefix = env::var_os("CONDA_PREFIX").filter(|prefix| !prefix.is_empty())?;
+    log::debug!("CONDA_PREFIX set to {:?}", conda_prefix);
+    let executable = PathBuf::from(conda_prefix).join("bin").join("python");
+    if executable.is_file() {
+        Some(executable)
+    } else {
+        log::debug!("{} does not exist", executable.display());
+        None
+    }
+}
+
+fn venv_path_search() -> Option<PathBuf> {
+    let cwd = env::current_dir().unwrap();
+    log::debug!(
+        "Searching for a venv in {} and parent directories",
+        cwd.display()
+    );
+    cwd.ancestors().find_map(|path| {
+        let venv_path = path.join(relative_venv_path(true));
+        log::debug!("Checking {}", venv_path.display());
+        venv_path.is_file().then_some(venv_path)
+    })
+}
+
+fn venv_executable(trace: &mut Vec<ResolutionStep>) -> Option<PathBuf> {
+    let activated = activated_venv();
+    record(
+        trace,
+        ResolutionStep::ActivatedVirtualEnv(activated.clone()),
+    );
+    if activated.is_some() {
+        return activated;
+    }
+    let conda = activated_conda();
+    record(trace, ResolutionStep::ActivatedCondaEnv(conda.clone()));
+    if conda.is_some() {
+        return conda;
+    }
+    let found = venv_path_search();
+    record(trace, ResolutionStep::VenvDirectory(found.clone()));
+    found
+}
+
+/// Whether the virtual environment whose interpreter is `venv_executable`
+/// (i.e. `bin/python` within it) satisfies `requested`, per the version its
+/// `pyvenv.cfg` records; one whose version is unknown (e.g. a Conda
+/// environment) is assumed to.
+fn venv_supports(venv_executable: &Path, requested: RequestedVersion) -> bool {
+    if requested == RequestedVersion::Any {
+        return true;
+    }
+    let venv_root = match venv_executable.parent().and_then(Path::parent) {
+        Some(venv_root) => venv_root,
+        None => return true,
+    };
+    match venv_base_version(venv_root) {
+        Ok(venv_version) => venv_version.supports(requested),
+        Err(error) => {
+            log::debug!("{}", error);
+            true
+        }
+    }
+}
+
+/// The file pyenv uses to record the version of Python for a project.
+pub static PYTHON_VERSION_FILE: &str = ".python-version";
+
+/// The file declaring a Python project, whose `tool.py.version` is the
+/// version of Python for the project.
+pub static PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// The current directory and its parents, up to (and including) the home
+/// directory, nearest first.
+fn project_directories() -> Vec<PathBuf> {
+    let cwd = match env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return Vec::new(),
+    };
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let mut directories = Vec::new();
+    for directory in cwd.ancestors() {
+        directories.push(directory.to_path_buf());
+        if home.as_deref() == Some(directory) {
+            break;
+        }
+    }
+    directories
+}
+
+/// The version requested by the project in the current directory, from a
+/// `.python-version` file or else a `pyproject.toml` (see
+/// [`python_version_file`] and [`pyproject_version`]); each one checked is
+/// recorded in `trace`.
+pub(crate) fn project_version(
+    trace: &mut Vec<ResolutionStep>,
+) -> Option<(PathBuf, RequestedVersion)> {
+    let version_file = python_version_file();
+    record(
+        trace,
+        ResolutionStep::PythonVersionFile(version_file.clone()),
+    );
+    version_file.or_else(|| {
+        let pyproject = pyproject_version();
+        record(trace, ResolutionStep::PyprojectVersion(pyproject.clone()));
+        pyproject
+    })
+}
+
+/// Searches the current directory and its parents, up to the home directory,
+/// for a `.python-version` file, returning its path and the version it
+/// requests.
+///
+/// The nearest file wins, even if its contents aren't understood.
+fn python_version_file() -> Option<(PathBuf, RequestedVersion)> {
+    log::debug!(
+        "Searching for a {} file in the current and parent directories",
+        PYTHON_VERSION_FILE
+    );
+    for directory in project_directories() {
+        let path = directory.join(PYTHON_VERSION_FILE);
+        log::debug!("Checking {}", path.display());
+        if path.is_file() {
+            let version = fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| parse_python_version_file(&contents));
+            if version.is_none() {
+                log::debug!("Ignoring unrecognized contents of {}", path.display());
+            }
+            return version.map(|version| (path, version));
+        }
+    }
+    None
+}
+
+/// Searches the current directory and its parents, up to the home directory,
+/// for a `pyproject.toml` file with a `tool.py.version`, returning its path
+/// and the version it requests.
+///
+/// Files without the key (e.g. those of a subproject) are skipped.
+fn pyproject_version() -> Option<(PathBuf, RequestedVersion)> {
+    log::debug!(
+        "Searching for a {} file with tool.py.version in the current and parent directories",
+        PYPROJECT_FILE
+    );
+    project_directories().into_iter().find_map(|directory| {
+        let path = directory.join(PYPROJECT_FILE);
+        log::debug!("Checking {}", path.display());
+        let contents = fs::read_to_string(&path).ok()?;
+        let version = parse_pyproject_version(&contents);
+        if version.is_none() {
+            log::debug!("No usable tool.py.version in {}", path.display());
+        }
+        version.map(|version| (path, version))
+    })
+}
+
+/// Parses `tool.py.version` (e.g. `"3.11"`) out of the contents of a
+/// `pyproject.toml` file, ignoring everything else in it; any micro version
+/// is ignored.
+fn parse_pyproject_version(contents: &str) -> Option<RequestedVersion> {
+    let pyproject = contents.parse::<toml::Value>().ok()?;
+    let version = pyproject.get("tool")?.get("py")?.get("version")?.as_str()?;
+    parse_python_version_file(version)
+}
+
+/// Parses the version from the first line of a `.python-version` file (e.g.
+/// `3.11` or `3.11.4`); any micro version is ignored.
+fn parse_python_version_file(contents: &str) -> Option<RequestedVersion> {
+    let line = contents.lines().next()?.trim();
+    let major_minor: Vec<&str> = line.splitn(3, '.').take(2).collect();
+    match RequestedVersion::from_str(&major_minor.join(".")) {
+        Ok(RequestedVersion::Any) | Err(_) => None,
+        Ok(version) => Some(version),
+    }
+}
+
+/// Python's options which take the following argument as their value.
+static PYTHON_OPTIONS_WITH_VALUES: [&str; 3] = ["-W", "-X", "--check-hash-based-pycs"];
+
+/// Finds a version flag among the options for the interpreter at the start of
+/// `args` (e.g. the `-3.11` of `-u -3.11 script.py`), which Python would reject
+/// as an unknown option.
+///
+/// Anything from a script, `-c`, or `-m` on is left alone as it's meant for
+/// the script, command, or module (e.g. `script.py -3.11`).
+fn misplaced_version_flag(args: &[String]) -> Option<&str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" || arg == "--" || arg == "-c" || arg == "-m" {
+            return None;
+        } else if version_from_flag(arg).is_some() || spec_from_flag(arg).is_some() {
+            return Some(arg);
+        } else if PYTHON_OPTIONS_WITH_VALUES.contains(&arg.as_str()) {
+            args.next();
+        }
+    }
+    None
+}
+
+/// The file name of `env`, which runs the interpreter named after it.
+static ENV_FILE_NAME: &str = "env";
+
+/// The file name of a Python interpreter, which may be followed by a version
+/// (e.g. `python3.9`).
+static PYTHON_FILE_NAME: &str = "python";
+
+/// The byte order mark some Windows editors write at the start of a UTF-8 file.
+static UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+
+/// The most bytes of a shebang line (after `#!`, including the newline) that
+/// are read; kernels only honour a fraction of this (e.g. 256 bytes on Linux
+/// and 512 on macOS), so a longer first line isn't treated as a shebang.
+const MAX_SHEBANG_LENGTH: usize = 4096;
+
+/// Parses the Python shebang of the script being run, if any.
+fn script_shebang(args: &[String]) -> Option<(RequestedVersion, Vec<String>)> {
+    // Using the first argument because it's the simplest and sanest.
+    // We can't use the last argument because that could actually be an argument
+    // to the Python module being executed. This is the same reason we can't go
+    // searching for the first/last file path that we find. The only safe way to
+    // get the file path regardless of its position is to replicate Python's arg
+    // parsing and that's a **lot** of work for little gain. Hence we only care
+    // about the first argument.
+    let possible_file = args.first()?;
+    log::debug!("Checking {:?} for a shebang", possible_file);
+    let mut open_file = File::open(possible_file).ok()?;
+    parse_python_shebang(&mut open_file)
+}
+
+// https://en.m.wikipedia.org/wiki/Shebang_(Unix)
+/// Parses the shebang on the first line read from `reader`, reading no more
+/// than a byte order mark, `#!`, and [`MAX_SHEBANG_LENGTH`] bytes from it.
+fn parse_python_shebang(reader: &mut impl Read) -> Option<(RequestedVersion, Vec<String>)> {
+    log::debug!("Looking for a Python-related shebang");
+    // One byte beyond the longest line allowed shows whether it was exceeded.
+    let limit = UTF8_BOM.len() + 2 + MAX_SHEBANG_LENGTH + 1;
+    let mut buffered_reader = BufReader::new(reader.take(limit as u64));
+    let start = buffered_reader.fill_buf().ok()?;
+    let bom_length = if start.starts_with(UTF8_BOM) {
+        log::debug!("Skipping the UTF-8 byte order mark at the start of the file");
+        UTF8_BOM.len()
+    } else {
+        0
+    };
+    if !start[bom_length..].starts_with(b"#!") {
+        // Doesn't start w/ `#!` in ASCII/UTF-8.
+        log::debug!("No '#!' at the start of the first line of the file");
+        return None;
+    }
+    buffered_reader.consume(bom_length + 2);
+
+    let mut first_line = Vec::new();
+    if buffered_reader.read_until(b'\n', &mut first_line).is_err() {
+        log::debug!("Can't read first line of the file");
+        return None;
+    };
+    if first_line.len() > MAX_SHEBANG_LENGTH {
+        log::debug!(
+            "First line of the file is longer than {} bytes",
+            MAX_SHEBANG_LENGTH
+        );
+        return None;
+    }
+    let first_line = match String::from_utf8(first_line) {
+        Ok(first_line) => first_line,
+        Err(error) => {
+            log::debug!("Can't read first line of the file: {}", error);
+            return None;
+        }
+    };
+
+    // Files written on Windows end their lines with `\r\n`.
+    split_shebang(first_line.trim_end_matches(&['\n', '\r'][..]))
+}
+
+/// Splits a shebang line (sans `#!`) into the requested version of Python and
+/// any arguments meant for the interpreter.
+fn split_shebang(line: &str) -> Option<(RequestedVersion, Vec<String>)> {
+    // Whitespace between `#!` and the path is allowed.
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?;
+    if file_name(interpreter) == ENV_FILE_NAME {
+        // Skip `env`'s own `-S` (to split the rest of the line into multiple
+        // arguments) and any environment variable assignments.
+        interpreter = words.find(|word| *word != "-S" && !word.contains('='))?;
+    }
+
+    // Only the file name matters as Python may be installed anywhere (e.g.
+    // `/opt/homebrew/bin/python3`).
+    let version = match file_name(interpreter).strip_prefix(PYTHON_FILE_NAME) {
+        Some(version) => version,
+        None => {
+            if line.contains("python") {
+                log::debug!("Ignoring unrecognized Python shebang: {}", line.trim());
+            }
+            return None;
+        }
+    };
+    // `pythonw` is the windowed (GUI) variant of `python` on Windows and
+    // macOS; Unix has no such distinction, so the regular interpreter is used.
+    let version = version.strip_prefix('w').unwrap_or(version);
+
+    log::debug!("Found shebang: {}", interpreter);
+    log::debug!("Found version: {}", version);
+    let requested_version = match RequestedVersion::from_str(version) {
+        Ok(requested_version) => requested_version,
+        Err(error) => {
+            log::debug!("Ignoring shebang version {:?}: {}", version, error);
+            return None;
+        }
+    };
+    let args: Vec<String> = words.map(String::from).collect();
+    if !args.is_empty() {
+        log::debug!("Found interpreter arguments: {:?}", args);
+    }
+    Some((requested_version, args))
+}
+
+/// The final component of a `/`-separated path.
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Returns how many times discovery should be retried, based on the
+/// `PY_RESOLVE_RETRY` environment variable (defaulting to 0).
+fn discovery_retries() -> u32 {
+    log::debug!("Checking for PY_RESOLVE_RETRY environment variable");
+    match env::var("PY_RESOLVE_RETRY") {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            log::debug!("Ignoring invalid PY_RESOLVE_RETRY value {:?}", value);
+            0
+        }),
+        Err(_) => 0,
+    }
+}
+
+/// Returns which of the executables satisfying a request to choose, based on
+/// the `PYLAUNCHER_SELECT` environment variable (defaulting to the newest).
+fn selection() -> Selection {
+    log::debug!("Checking for PYLAUNCHER_SELECT environment variable");
+    match env::var("PYLAUNCHER_SELECT") {
+        Ok(value) if value == "min" => Selection::Oldest,
+        Ok(value) if value.is_empty() || value == "max" => Selection::Newest,
+        Ok(value) => {
+            log::debug!("Ignoring unrecognized PYLAUNCHER_SELECT value {:?}", value);
+            Selection::Newest
+        }
+        Err(_) => Selection::Newest,
+    }
+}
+
+/// Returns the versions specified, in order of preference, by the environment
+/// variable associated with the requested version (e.g. `PY_PYTHON` for
+/// [`RequestedVersion::Any`]).
+fn env_var_versions(requested_version: RequestedVersion) -> Vec<RequestedVersion> {
+    let env_var = match requested_version.env_var() {
+        Some(env_var) => env_var,
+        None => return Vec::new(),
+    };
+    log::debug!("Checking for {} environment variable", env_var);
+    match env::var(&env_var) {
+        Ok(env_var_value) => {
+            log::debug!("{} set to {}", env_var, env_var_value);
+            parse_version_list(&env_var_value)
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Parses a comma-separated list of versions (e.g. `3.12,3.11`), skipping any
+/// which are empty or malformed.
+fn parse_version_list(value: &str) -> Vec<RequestedVersion> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match RequestedVersion::from_user_str(entry) {
+            Ok(version) => Some(version),
+            Err(error) => {
+                log::debug!("Skipping invalid version {:?}: {}", entry, error);
+                None
+            }
+        })
+        .collect()
 }
 
-fn venv_path_search() -> Option<PathBuf> {
-    let cwd = env::current_dir().unwrap();
-    log::info!(
-        "Searching for a venv in {} and parent directories",
-        cwd.display()
+/// Lists, in order, the steps taken to choose a Python executable when no
+/// version is explicitly requested, along with what each step found (if
+/// anything); steps after the one which decides aren't taken, so aren't listed.
+fn precedence_steps(directories: &SearchDirectories) -> Vec<(String, Option<String>)> {
+    let override_step = (
+        "PYLAUNCHER_PYTHON environment variable".to_string(),
+        python_override().map(|path| path.display().to_string()),
     );
-    cwd.ancestors().find_map(|path| {
-        let venv_path = path.join(relative_venv_path(true));
-        log::info!("Checking {}", venv_path.display());
-        // bool::then_some() makes more sense, but still experimental.
-        venv_path.is_file().then(|| venv_path)
-    })
-}
+    if override_step.1.is_some() {
+        return vec![override_step];
+    }
 
-fn venv_executable() -> Option<PathBuf> {
-    activated_venv().or_else(venv_path_search)
+    let (_, trace) = resolve_with_trace(RequestedVersion::Any, directories);
+    std::iter::once(override_step)
+        .chain(
+            trace
+                .iter()
+                .map(|step| (step.description(), step.finding())),
+        )
+        .collect()
 }
 
-// https://en.m.wikipedia.org/wiki/Shebang_(Unix)
-fn parse_python_shebang(reader: &mut impl Read) -> Option<RequestedVersion> {
-    let mut shebang_buffer = [0; 2];
-    log::info!("Looking for a Python-related shebang");
-    if reader.read(&mut shebang_buffer).is_err() || shebang_buffer != [0x23, 0x21] {
-        // Doesn't start w/ `#!` in ASCII/UTF-8.
-        log::debug!("No '#!' at the start of the first line of the file");
-        return None;
-    }
+/// A JSON report of the directories searched, the executables found in them,
+/// any activated virtual environment, and which executable a default run
+/// would choose.
+fn info_report(directories: &SearchDirectories) -> String {
+    let path: Vec<_> = crate::path_entries(directories)
+        .into_iter()
+        .map(|(directory, source)| {
+            json!({
+                "directory": json_path(&directory),
+                "source": source.to_string(),
+            })
+        })
+        .collect();
 
-    let mut buffered_reader = BufReader::new(reader);
-    let mut first_line = String::new();
+    let mut interpreters = Vec::new();
+    for &implementation in [Implementation::CPython, Implementation::PyPy].iter() {
+        let mut executables: Vec<_> =
+            crate::all_sourced_executables_for(implementation, directories)
+                .into_iter()
+                .collect();
+        executables.sort_by(|(a, _), (b, _)| b.cmp(a));
+        interpreters.extend(executables.into_iter().map(|(version, (path, source))| {
+            json!({
+                "implementation": implementation.to_string(),
+                "version": version.to_string(),
+                "path": json_path(&path),
+                "source": source.to_string(),
+            })
+        }));
+    }
 
-    if buffered_reader.read_line(&mut first_line).is_err() {
-        log::debug!("Can't read first line of the file");
-        return None;
-    };
+    let virtual_env = activated_venv_entry().map(|(version, path)| {
+        json!({
+            "version": version.map(|version| version.to_string()),
+            "path": json_path(&path),
+        })
+    });
 
-    // Whitespace between `#!` and the path is allowed.
-    let line = first_line.trim();
+    let chosen = find_executable(
+        Implementation::CPython,
+        RequestedVersion::Any,
+        &[],
+        directories,
+    )
+    .map(|(executable, _)| json_path(&executable))
+    .map_err(|error| log::debug!("{}", error))
+    .ok();
 
-    let accepted_paths = [
-        "python",
-        "/usr/bin/python",
-        "/usr/local/bin/python",
-        "/usr/bin/env python",
-    ];
+    let report = json!({
+        "launcher_version": env!("CARGO_PKG_VERSION"),
+        "path": path,
+        "interpreters": interpreters,
+        "virtual_env": virtual_env,
+        "default_request": format!("{:#}", RequestedVersion::from_env_default()),
+        "chosen": chosen,
+    });
+    serde_json::to_string_pretty(&report).unwrap()
+}
 
-    for acceptable_path in &accepted_paths {
-        if !line.starts_with(acceptable_path) {
-            continue;
+fn format_precedence(steps: &[(String, Option<String>)]) -> String {
+    let mut output = String::new();
+    for (index, (description, value)) in steps.iter().enumerate() {
+        match value {
+            Some(value) => writeln!(output, "{}. [x] {}: {}", index + 1, description, value),
+            None => writeln!(output, "{}. [ ] {}", index + 1, description),
         }
-
-        log::debug!("Found shebang: {}", acceptable_path);
-        let version = line[acceptable_path.len()..].to_string();
-        log::debug!("Found version: {}", version);
-        return RequestedVersion::from_str(&version).ok();
+        .unwrap();
     }
+    output
+}
 
-    None
+/// Finds the executable to run (also searching `directories`), along with any
+/// arguments for it which were specified in the shebang of the file in `args`
+/// (if one was used).
+fn find_executable(
+    implementation: Implementation,
+    version: RequestedVersion,
+    args: &[String],
+    directories: &SearchDirectories,
+) -> crate::Result<(PathBuf, Vec<String>)> {
+    find_executable_traced(implementation, version, args, directories, &mut Vec::new())
 }
 
-fn find_executable(version: RequestedVersion, args: &[String]) -> crate::Result<PathBuf> {
+/// Like [`find_executable`], but records each step taken in `trace`.
+fn find_executable_traced(
+    implementation: Implementation,
+    version: RequestedVersion,
+    args: &[String],
+    directories: &SearchDirectories,
+    trace: &mut Vec<ResolutionStep>,
+) -> crate::Result<(PathBuf, Vec<String>)> {
     let mut requested_version = version;
+    // What `RequestedVersion::from_env_default` finds, when it applies.
+    let mut default_versions: Option<Vec<RequestedVersion>> = None;
     let mut chosen_path: Option<PathBuf> = None;
+    let mut shebang_args: Option<Vec<String>> = None;
 
     if requested_version == RequestedVersion::Any {
-        if let Some(venv_path) = venv_executable() {
-            chosen_path = Some(venv_path);
-        } else if !args.is_empty() {
-            // Using the first argument because it's the simplest and sanest.
-            // We can't use the last argument because that could actually be an argument
-            // to the Python module being executed. This is the same reason we can't go
-            // searching for the first/last file path that we find. The only safe way to
-            // get the file path regardless of its position is to replicate Python's arg
-            // parsing and that's a **lot** of work for little gain. Hence we only care
-            // about the first argument.
-            let possible_file = &args[0];
-            log::info!("Checking {:?} for a shebang", possible_file);
-            if let Ok(mut open_file) = File::open(possible_file) {
-                if let Some(shebang_version) = parse_python_shebang(&mut open_file) {
+        // A virtual environment's interpreter isn't necessarily of the
+        // requested implementation, so only consider it for the default.
+        let venv_path = if implementation == Implementation::CPython {
+            venv_executable(trace)
+        } else {
+            None
+        };
+        if let Some(venv_path) = venv_path {
+            // Like running the script with the environment's `python` on
+            // `PATH`, unless its shebang asks for a version which the
+            // environment is known not to be.
+            let shebang = if args.is_empty() {
+                None
+            } else {
+                script_shebang(args)
+            };
+            match shebang {
+                Some((shebang_version, args)) if !venv_supports(&venv_path, shebang_version) => {
+                    log::info!(
+                        "{} doesn't satisfy the shebang's request for {}",
+                        venv_path.display(),
+                        shebang_version
+                    );
+                    record(trace, ResolutionStep::Shebang(Some(shebang_version)));
+                    requested_version = shebang_version;
+                    shebang_args = Some(args);
+                }
+                shebang => {
+                    chosen_path = Some(venv_path);
+                    shebang_args = shebang.map(|(_, args)| args);
+                }
+            }
+        } else {
+            if !args.is_empty() {
+                let shebang = script_shebang(args);
+                record(
+                    trace,
+                    ResolutionStep::Shebang(shebang.as_ref().map(|(version, _)| *version)),
+                );
+                if let Some((shebang_version, args)) = shebang {
                     requested_version = shebang_version;
+                    shebang_args = Some(args);
                 }
             }
+            if shebang_args.is_none() && implementation == Implementation::CPython {
+                default_versions = Some(RequestedVersion::env_defaults_traced(trace));
+            }
         }
     }
 
-    if chosen_path.is_none() {
-        if let Some(env_var) = requested_version.env_var() {
-            log::info!("Checking for {} environment variable", env_var);
-            if let Ok(env_var_value) = env::var(&env_var) {
-                if !env_var_value.is_empty() {
-                    log::debug!("{} set to {}", env_var, env_var_value);
-                    if let Ok(env_requested_version) = RequestedVersion::from_str(&env_var_value) {
-                        requested_version = env_requested_version;
-                    }
+    if let Some(venv_path) = chosen_path {
+        log::info!("Chose {} from a virtual environment", venv_path.display());
+        return Ok((venv_path, shebang_args.unwrap_or_default()));
+    }
+
+    let requested_versions = match default_versions {
+        Some(default_versions) => default_versions,
+        None => effective_requests(requested_version, trace),
+    };
+    let executable = match find_preferred_executable_traced(
+        implementation,
+        &requested_versions,
+        directories,
+        trace,
+    ) {
+        Err(error) if shebang_args.is_some() && requests_python2(requested_version) => {
+            if python2_upgrade_enabled() {
+                log::info!(
+                    "{}; upgrading to Python 3 as PY_SHEBANG_UPGRADE_PYTHON2 is set",
+                    error
+                );
+                find_requested_executable_traced(
+                    implementation,
+                    RequestedVersion::MajorOnly(3),
+                    directories,
+                    trace,
+                )
+            } else if shebang_fallback_enabled() {
+                log::info!(
+                    "{}; falling back to the default executable as PY_SHEBANG_FALLBACK is set",
+                    error
+                );
+                find_requested_executable_traced(
+                    implementation,
+                    RequestedVersion::Any,
+                    directories,
+                    trace,
+                )
+            } else {
+                Err(crate::Error::Python2Unavailable(
+                    PathBuf::from(&args[0]),
+                    requested_version,
+                ))
+            }
+        }
+        Err(error) if shebang_args.is_some() && shebang_fallback_enabled() => {
+            log::info!(
+                "{}; falling back to the default executable as PY_SHEBANG_FALLBACK is set",
+                error
+            );
+            find_requested_executable_traced(
+                implementation,
+                RequestedVersion::Any,
+                directories,
+                trace,
+            )
+        }
+        result => result,
+    }?;
+
+    Ok((executable, shebang_args.unwrap_or_default()))
+}
+
+/// Applies any environment variable or config file default to
+/// `requested_version`, returning the versions to search for in order of
+/// preference; each source checked is recorded in `trace`.
+pub(crate) fn effective_requests(
+    requested_version: RequestedVersion,
+    trace: &mut Vec<ResolutionStep>,
+) -> Vec<RequestedVersion> {
+    let env_versions = env_var_versions(requested_version);
+    if let Some(env_var) = requested_version.env_var() {
+        if !env_versions.is_empty() {
+            let versions: Vec<String> = env_versions
+                .iter()
+                .map(|version| format!("{:#}", version))
+                .collect();
+            log::info!("Using {} from {}", versions.join(","), env_var);
+        }
+        record(trace, ResolutionStep::EnvVar(env_var, env_versions.clone()));
+    }
+    default_requests(requested_version, env_versions, || {
+        let config_version = Config::current().default_version();
+        if let Some(version) = config_version {
+            log::info!("Using {:#} from the config file's default-version", version);
+        }
+        record(trace, ResolutionStep::ConfigDefault(config_version));
+        config_version
+    })
+}
+
+/// The precedence applied by [`effective_requests`]: the versions listed by the
+/// environment variable for `requested_version` (`env_versions`) replace it,
+/// and then any [`RequestedVersion::Any`] is replaced by the config file's
+/// default, which is only looked up (via `config_version`) when needed.
+fn default_requests(
+    requested_version: RequestedVersion,
+    env_versions: Vec<RequestedVersion>,
+    config_version: impl FnOnce() -> Option<RequestedVersion>,
+) -> Vec<RequestedVersion> {
+    let mut requested_versions = env_versions;
+    if requested_versions.is_empty() {
+        requested_versions.push(requested_version);
+    }
+
+    if requested_versions.contains(&RequestedVersion::Any) {
+        if let Some(config_version) = config_version() {
+            for version in requested_versions.iter_mut() {
+                if *version == RequestedVersion::Any {
+                    *version = config_version;
                 }
+            }
+        }
+    }
+
+    requested_versions
+}
+
+/// Searches for an executable satisfying `requested_version`, after applying
+/// any environment variable or config file default; when several versions are
+/// preferred, the first one which is installed is used.
+fn find_requested_executable(
+    implementation: Implementation,
+    requested_version: RequestedVersion,
+    directories: &SearchDirectories,
+) -> crate::Result<PathBuf> {
+    find_requested_executable_traced(
+        implementation,
+        requested_version,
+        directories,
+        &mut Vec::new(),
+    )
+}
+
+/// Like [`find_requested_executable`], but records each step taken in `trace`.
+fn find_requested_executable_traced(
+    implementation: Implementation,
+    requested_version: RequestedVersion,
+    directories: &SearchDirectories,
+    trace: &mut Vec<ResolutionStep>,
+) -> crate::Result<PathBuf> {
+    let requested_versions = effective_requests(requested_version, trace);
+    find_preferred_executable_traced(implementation, &requested_versions, directories, trace)
+}
+
+/// Searches for an executable satisfying the first of `requested_versions`
+/// (in order of preference) which is installed, recording each search in
+/// `trace`.
+fn find_preferred_executable_traced(
+    implementation: Implementation,
+    requested_versions: &[RequestedVersion],
+    directories: &SearchDirectories,
+    trace: &mut Vec<ResolutionStep>,
+) -> crate::Result<PathBuf> {
+    let selection = selection();
+    let last_index = requested_versions.len() - 1;
+
+    requested_versions
+        .iter()
+        .enumerate()
+        .find_map(|(index, &version)| {
+            log::debug!("Requested version: {:#}", version);
+            // Only wait for interpreters to appear once every preference has
+            // been tried.
+            let retries = if index == last_index {
+                discovery_retries()
+            } else {
+                0
             };
+            let found = crate::find_executable_with_retries(
+                implementation,
+                version,
+                selection,
+                retries,
+                directories,
+            );
+            record(trace, ResolutionStep::PathScan(version, found.clone()));
+            found.inspect(|executable| {
+                log::info!(
+                    "Chose {} as the best match for {}",
+                    executable.display(),
+                    version
+                )
+            })
+        })
+        .ok_or_else(|| {
+            // Point out what could have been requested instead.
+            let mut available: Vec<ExactVersion> =
+                crate::all_executables_for(implementation, directories)
+                    .into_keys()
+                    .collect();
+            if available.is_empty() {
+                crate::Error::NoExecutableFound(requested_versions[0])
+            } else {
+                available.sort();
+                crate::Error::NoMatchingExecutable(requested_versions[0], available)
+            }
+        })
+}
+
+/// Whether only a version of Python 2 satisfies `requested_version`.
+fn requests_python2(requested_version: RequestedVersion) -> bool {
+    matches!(
+        requested_version,
+        RequestedVersion::MajorOnly(2) | RequestedVersion::Exact(2, _)
+    )
+}
+
+/// The executable named by the `PYLAUNCHER_PYTHON` environment variable,
+/// which is run instead of searching for one.
+fn python_override() -> Option<PathBuf> {
+    log::debug!("Checking for PYLAUNCHER_PYTHON environment variable");
+    env::var_os("PYLAUNCHER_PYTHON")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Whether the note about Python 2's end of life, shown when it is explicitly
+/// requested, is turned off by the `PYLAUNCHER_NO_PYTHON2_NOTE` environment
+/// variable.
+fn python2_note_disabled() -> bool {
+    log::debug!("Checking for PYLAUNCHER_NO_PYTHON2_NOTE environment variable");
+    env::var_os("PYLAUNCHER_NO_PYTHON2_NOTE").is_some_and(|value| !value.is_empty())
+}
+
+/// Whether a script whose shebang requests Python 2 which isn't installed
+/// should be run with Python 3 instead, based on the
+/// `PY_SHEBANG_UPGRADE_PYTHON2` environment variable.
+fn python2_upgrade_enabled() -> bool {
+    log::debug!("Checking for PY_SHEBANG_UPGRADE_PYTHON2 environment variable");
+    env::var_os("PY_SHEBANG_UPGRADE_PYTHON2").is_some_and(|value| !value.is_empty())
+}
+
+/// Whether a script whose shebang requests a version which can't be found
+/// should be run with the default executable instead, based on the
+/// `PY_SHEBANG_FALLBACK` environment variable.
+fn shebang_fallback_enabled() -> bool {
+    match env::var("PY_SHEBANG_FALLBACK") {
+        Ok(value) if value == "default" => true,
+        Ok(value) => {
+            log::debug!(
+                "Ignoring unrecognized PY_SHEBANG_FALLBACK value {:?}",
+                value
+            );
+            false
         }
+        Err(_) => false,
+    }
+}
 
-        if let Some(executable_path) = crate::find_executable(requested_version) {
-            chosen_path = Some(executable_path);
+/// Describes every problem with the environment variables and config file
+/// which influence how a Python executable is chosen.
+fn configuration_problems() -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut env_vars: Vec<(String, String)> = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect();
+    env_vars.sort();
+
+    for (name, value) in env_vars {
+        let valid = if name == "PY_PYTHON"
+            || name
+                .strip_prefix("PY_PYTHON")
+                .is_some_and(|major| major.parse::<u16>().is_ok())
+        {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .all(|entry| RequestedVersion::from_user_str(entry).is_ok())
+        } else if name == "PY_RESOLVE_RETRY" {
+            value.parse::<u32>().is_ok()
+        } else if name == "PY_SHEBANG_FALLBACK" {
+            value == "default"
+        } else if name == "PYLAUNCHER_SELECT" {
+            ["", "min", "max"].contains(&value.as_str())
+        } else if name == "PYLAUNCHER_PREFER" {
+            ["", "path", "system"].contains(&value.as_str())
+        } else {
+            continue;
+        };
+
+        if !valid {
+            problems.push(format!("Invalid {} value {:?}", name, value));
         }
     }
 
-    chosen_path.ok_or(crate::Error::NoExecutableFound(requested_version))
+    if let Some(path) = crate::config::config_path() {
+        problems.extend(Config::problems_in(&path));
+    }
+
+    problems
+}
+
+/// Reads the `major.minor` version of the interpreter which created the
+/// virtual environment at `venv_root` from its `pyvenv.cfg`.
+fn venv_base_version(venv_root: &Path) -> crate::Result<ExactVersion> {
+    let cfg_path = venv_root.join(PYVENV_CFG);
+    log::debug!(
+        "Checking {} for the base interpreter version",
+        cfg_path.display()
+    );
+    let contents = fs::read_to_string(&cfg_path).map_err(|error| {
+        log::debug!("Can't read {}: {}", cfg_path.display(), error);
+        crate::Error::InvalidPyvenvCfg(cfg_path.clone())
+    })?;
+
+    // `venv` writes e.g. `version = 3.11.4` while some tools write
+    // `version_info = 3.11.4.final.0`.
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find_map(|(key, value)| match key.trim() {
+            "version" | "version_info" => {
+                let mut components = value.trim().split('.');
+                let version = format!("{}.{}", components.next()?, components.next()?);
+                log::debug!("Found base version: {}", version);
+                ExactVersion::from_str(&version).ok()
+            }
+            _ => None,
+        })
+        .ok_or(crate::Error::InvalidPyvenvCfg(cfg_path))
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)] // `test_case` generates a trailing `()` for tests without a return value.
 mod tests {
     use test_case::test_case;
 
@@ -269,60 +2645,450 @@ mod tests {
 
     #[test_case(&["py".to_string(), "--help".to_string(), "--list".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--help".to_string())))]
     #[test_case(&["py".to_string(), "--list".to_string(), "--help".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--list".to_string())))]
+    #[test_case(&["py".to_string(), "--list".to_string(), "--direnv".to_string(), "--help".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--list".to_string())) ; "--list --direnv with an extra flag")]
+    #[test_case(&["py".to_string(), "--complete".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--complete".to_string())) ; "--complete missing a shell")]
+    #[test_case(&["py".to_string(), "--complete".to_string(), "bash".to_string(), "zsh".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--complete".to_string())) ; "--complete with too many shells")]
+    #[test_case(&["py".to_string(), "--complete".to_string(), "tcsh".to_string()] => Err(crate::Error::UnsupportedShell("tcsh".to_string())) ; "--complete with an unsupported shell")]
+    #[test_case(&["py".to_string(), "--precedence".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--precedence".to_string())))]
+    #[test_case(&["py".to_string(), "--venv-base".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--venv-base".to_string())) ; "--venv-base missing a directory")]
+    #[test_case(&["py".to_string(), "--launcher-version".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--launcher-version".to_string())))]
+    #[test_case(&["py".to_string(), "--python".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--python".to_string())) ; "--python missing a path")]
+    #[test_case(&["py".to_string(), "--check-config".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--check-config".to_string())))]
+    #[test_case(&["py".to_string(), "--info".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--info".to_string())))]
+    #[test_case(&["py".to_string(), "--default".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--default".to_string())))]
+    #[test_case(&["py".to_string(), "--resolve-cached".to_string(), "-3".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--resolve-cached".to_string())))]
+    #[test_case(&["py".to_string(), "--spec".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--spec".to_string())) ; "--spec missing a spec")]
+    #[test_case(&["py".to_string(), "--prepend-path".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--prepend-path".to_string())) ; "--prepend-path missing a directory")]
+    #[test_case(&["py".to_string(), "--prepend-path".to_string(), "/".to_string(), "--append-path".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--append-path".to_string())) ; "--append-path missing a directory")]
+    #[test_case(&["py".to_string(), "--list".to_string(), "--impl".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--list".to_string())) ; "--list --impl missing an implementation")]
+    #[test_case(&["py".to_string(), "-3".to_string(), "--list".to_string(), "--impl".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--list".to_string())) ; "-3 --list --impl missing an implementation")]
+    #[test_case(&["py".to_string(), "--list".to_string(), "--impl".to_string(), "jython".to_string()] => Err(crate::Error::UnknownImplementation("jython".to_string())) ; "--list --impl with an unknown implementation")]
+    #[test_case(&["py".to_string(), "--where".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--where".to_string())) ; "--where missing a version")]
+    #[test_case(&["py".to_string(), "--where".to_string(), "3.11".to_string(), "3.12".to_string()] => Err(crate::Error::IllegalArgument(PathBuf::from("py"), "--where".to_string())) ; "--where with too many versions")]
     fn from_main_illegal_argument_tests(argv: &[String]) -> crate::Result<Action> {
         Action::from_main(argv)
     }
 
+    #[test]
+    fn from_main_launcher_version() {
+        assert_eq!(
+            Action::from_main(&["py".to_string(), "--launcher-version".to_string()]),
+            Ok(Action::Version(env!("CARGO_PKG_VERSION").to_string()))
+        );
+    }
+
+    #[test]
+    fn from_main_quiet() {
+        assert_eq!(
+            Action::from_main(&[
+                "py".to_string(),
+                "--quiet".to_string(),
+                "--launcher-version".to_string()
+            ]),
+            Ok(Action::Version(env!("CARGO_PKG_VERSION").to_string()))
+        );
+        assert_eq!(
+            Action::from_main(&[
+                "py".to_string(),
+                "--quiet".to_string(),
+                "--launcher-version".to_string(),
+                "-3".to_string()
+            ]),
+            Err(crate::Error::IllegalArgument(
+                PathBuf::from("py"),
+                "--launcher-version".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn show_command_tests() {
+        let action = Action::Execute {
+            launcher_path: PathBuf::from("py"),
+            executable: PathBuf::from("/usr/bin/python3.11"),
+            args: vec!["-u".to_string(), "it's.py".to_string(), "a b".to_string()],
+        };
+        assert_eq!(
+            show_command(action),
+            Some(Action::ShowCommand(
+                "'/usr/bin/python3.11' '-u' 'it'\\''s.py' 'a b'".to_string()
+            ))
+        );
+        assert_eq!(show_command(Action::Version("1.0".to_string())), None);
+    }
+
+    #[test_case(&[] => vec!["-I"] ; "no arguments")]
+    #[test_case(&["script.py", "-I"] => vec!["-I", "script.py", "-I"] ; "script argument")]
+    #[test_case(&["-u", "-I", "script.py"] => vec!["-u", "-I", "script.py"] ; "already isolated")]
+    #[test_case(&["-I"] => vec!["-I"] ; "only isolated")]
+    fn isolate_tests(args: &[&str]) -> Vec<String> {
+        let action = Action::Execute {
+            launcher_path: PathBuf::from("py"),
+            executable: PathBuf::from("python3"),
+            args: args.iter().map(ToString::to_string).collect(),
+        };
+        match isolate(action) {
+            Some(Action::Execute { args, .. }) => args,
+            _ => panic!("Action::Execute wasn't isolated"),
+        }
+    }
+
+    #[test]
+    fn isolate_other_actions() {
+        assert_eq!(isolate(Action::Version("1.0".to_string())), None);
+    }
+
+    #[test_case(&["-u", "-3.11", "script.py"] => Some("-3.11".to_string()) ; "after an option")]
+    #[test_case(&["-u", "-E", "-3"] => Some("-3".to_string()) ; "after several options")]
+    #[test_case(&["-X", "dev", "-3.11+"] => Some("-3.11+".to_string()) ; "after an option with a value")]
+    #[test_case(&["-u", "-pypy@3.10"] => Some("-pypy@3.10".to_string()) ; "implementation spec")]
+    #[test_case(&["-W", "-3.11"] => None ; "option value")]
+    #[test_case(&["script.py", "-3.11"] => None ; "script argument")]
+    #[test_case(&["-u", "script.py", "-3.11"] => None ; "script argument after an option")]
+    #[test_case(&["-c", "-3.11"] => None ; "command")]
+    #[test_case(&["-m", "module", "-3.11"] => None ; "module argument")]
+    #[test_case(&["-", "-3.11"] => None ; "stdin argument")]
+    #[test_case(&["-u", "-v"] => None ; "no version")]
+    fn misplaced_version_flag_tests(args: &[&str]) -> Option<String> {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        misplaced_version_flag(&args).map(ToString::to_string)
+    }
+
+    #[test_case(&["py", "--json", "--list"] => true ; "leading")]
+    #[test_case(&["py", "--quiet", "--prepend-path", "/bin", "--append-path", "/usr/bin", "--json", "--list"] => true ; "after the other launcher-wide flags")]
+    #[test_case(&["py", "--json", "--prepend-path", "/bin", "--isolated", "--list"] => true ; "before the other launcher-wide flags")]
+    #[test_case(&["py", "--prepend-path", "--json", "--list"] => false ; "path flag's directory")]
+    #[test_case(&["py", "--list", "--json"] => true ; "after a command")]
+    #[test_case(&["py", "-3", "--list", "--sort", "path", "--json"] => true ; "after a command's modifiers")]
+    #[test_case(&["py", "-3", "--json", "--list"] => true ; "after a version flag")]
+    #[test_case(&["py", "--python", "--json", "--list"] => false ; "selector's value")]
+    #[test_case(&["py", "-3", "--", "--json"] => false ; "after a separator")]
+    #[test_case(&["py", "script.py", "--json"] => false ; "script argument")]
+    #[test_case(&["py"] => false ; "no arguments")]
+    fn json_requested_tests(argv: &[&str]) -> bool {
+        let argv: Vec<String> = argv.iter().map(ToString::to_string).collect();
+        json_requested(&argv)
+    }
+
+    #[test_case(&["py", "--list"] => true ; "list")]
+    #[test_case(&["py", "--list", "--impl", "pypy"] => true ; "list with a modifier")]
+    #[test_case(&["py", "--list", "--direnv"] => false ; "direnv")]
+    #[test_case(&["py", "-3.11", "--list"] => true ; "version and list")]
+    #[test_case(&["py", "--where", "3"] => true ; "where flag")]
+    #[test_case(&["py", "--info"] => true ; "info")]
+    #[test_case(&["py", "-3.11"] => false ; "version")]
+    #[test_case(&["py", "--list-verbose"] => false ; "list verbose")]
+    #[test_case(&["py"] => false ; "nothing")]
+    fn json_supported_tests(argv: &[&str]) -> bool {
+        let argv: Vec<String> = argv.iter().map(ToString::to_string).collect();
+        json_supported(&argv)
+    }
+
+    #[test_case(b"/usr/bin/python3.11" => json!("/usr/bin/python3.11") ; "UTF-8")]
+    #[test_case(b"/opt/\xe2\x98\x83/python3.11" => json!("/opt/\u{2603}/python3.11") ; "non-ASCII UTF-8")]
+    #[test_case(b"/opt/\xff/python3.11" => json!({"lossy": "/opt/\u{fffd}/python3.11", "bytes": [47, 111, 112, 116, 47, 255, 47, 112, 121, 116, 104, 111, 110, 51, 46, 49, 49]}) ; "non-UTF-8")]
+    fn json_path_tests(path: &[u8]) -> serde_json::Value {
+        json_path(Path::new(OsStr::from_bytes(path)))
+    }
+
+    #[test_case(&["py".to_string(), "--quiet".to_string(), "-3".to_string()] => true ; "leading")]
+    #[test_case(&["py".to_string(), "--quiet".to_string()] => true ; "alone")]
+    #[test_case(&["py".to_string(), "--show-command".to_string(), "--quiet".to_string()] => true ; "after another launcher-wide flag")]
+    #[test_case(&["py".to_string(), "-3".to_string(), "--quiet".to_string()] => true ; "after a version flag")]
+    #[test_case(&["py".to_string(), "script.py".to_string(), "--quiet".to_string()] => false ; "script argument")]
+    #[test_case(&["py".to_string()] => false ; "no arguments")]
+    fn quiet_requested_tests(argv: &[String]) -> bool {
+        quiet_requested(argv)
+    }
+
+    #[test_case("bash" => true ; "bash")]
+    #[test_case("zsh" => true ; "zsh")]
+    fn from_main_complete_tests(shell: &str) -> bool {
+        match Action::from_main(&[
+            "py".to_string(),
+            "--complete".to_string(),
+            shell.to_string(),
+        ]) {
+            Ok(Action::Complete(script)) => script.contains("_py()"),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn from_main_complete_fish() {
+        match Action::from_main(&[
+            "py".to_string(),
+            "--complete".to_string(),
+            "fish".to_string(),
+        ]) {
+            Ok(Action::Complete(script)) => assert!(script.contains("complete -c py")),
+            _ => panic!("'--complete fish' did not return Action::Complete"),
+        }
+    }
+
+    #[test]
+    fn test_format_precedence() {
+        let steps = vec![
+            ("First step".to_string(), None),
+            ("Second step".to_string(), Some("3.6".to_string())),
+        ];
+
+        assert_eq!(
+            format_precedence(&steps),
+            "1. [ ] First step\n2. [x] Second step: 3.6\n"
+        );
+    }
+
+    #[test_case("3.11\n" => Some(RequestedVersion::Exact(3, 11)) ; "major.minor")]
+    #[test_case("3.11.4\n" => Some(RequestedVersion::Exact(3, 11)) ; "micro version ignored")]
+    #[test_case("3" => Some(RequestedVersion::MajorOnly(3)) ; "major only without a newline")]
+    #[test_case("  3.12.0  \n3.11.4\n" => Some(RequestedVersion::Exact(3, 12)) ; "only the first line is used")]
+    #[test_case("system\n" => None ; "system")]
+    #[test_case("pypy3.10-7.3.15\n" => None ; "other implementation")]
+    #[test_case("3.13-dev\n" => None ; "development version")]
+    #[test_case("" => None ; "empty file")]
+    #[test_case("\n3.11\n" => None ; "empty first line")]
+    fn parse_python_version_file_tests(contents: &str) -> Option<RequestedVersion> {
+        parse_python_version_file(contents)
+    }
+
+    #[test_case("[project]\nname = \"spam\"\n\n[tool.py]\nversion = \"3.11\"\n" => Some(RequestedVersion::Exact(3, 11)) ; "tool.py table")]
+    #[test_case("[tool.py]\nversion = \"3.11.4\"\n" => Some(RequestedVersion::Exact(3, 11)) ; "micro version ignored")]
+    #[test_case("[tool.py]\nversion = \"3\"\n" => Some(RequestedVersion::MajorOnly(3)) ; "major only")]
+    #[test_case("tool.py.version = \"3.12\"\n" => Some(RequestedVersion::Exact(3, 12)) ; "dotted key")]
+    #[test_case("[tool.black]\nline-length = 88\n[tool.py]\nversion = \"3.10\"\n" => Some(RequestedVersion::Exact(3, 10)) ; "other tools ignored")]
+    #[test_case("[project]\nrequires-python = \">=3.8\"\n" => None ; "missing key")]
+    #[test_case("[tool.py]\nversion = 3.11\n" => None ; "not a string")]
+    #[test_case("[tool.py]\nversion = \"system\"\n" => None ; "unrecognized version")]
+    #[test_case("[tool.py\nversion = \"3.11\"\n" => None ; "malformed file")]
+    #[test_case("" => None ; "empty file")]
+    fn parse_pyproject_version_tests(contents: &str) -> Option<RequestedVersion> {
+        parse_pyproject_version(contents)
+    }
+
+    #[test_case("3.6" => vec![RequestedVersion::Exact(3, 6)] ; "single version")]
+    #[test_case("3.12,3.11,3.10" => vec![RequestedVersion::Exact(3, 12), RequestedVersion::Exact(3, 11), RequestedVersion::Exact(3, 10)] ; "ordered list")]
+    #[test_case(" 3.12 , python3.11 " => vec![RequestedVersion::Exact(3, 12), RequestedVersion::Exact(3, 11)] ; "whitespace and prefixes")]
+    #[test_case("3.12,bogus,,3" => vec![RequestedVersion::Exact(3, 12), RequestedVersion::MajorOnly(3)] ; "malformed and empty entries skipped")]
+    #[test_case("" => Vec::<RequestedVersion>::new() ; "empty")]
+    fn parse_version_list_tests(value: &str) -> Vec<RequestedVersion> {
+        parse_version_list(value)
+    }
+
+    #[test_case(true, None, None => true ; "terminal")]
+    #[test_case(false, None, None => false ; "not a terminal")]
+    #[test_case(true, Some("1"), None => false ; "NO_COLOR with a terminal")]
+    #[test_case(false, Some("1"), None => false ; "NO_COLOR without a terminal")]
+    #[test_case(true, Some(""), None => true ; "empty NO_COLOR with a terminal")]
+    #[test_case(false, None, Some("1") => true ; "CLICOLOR_FORCE without a terminal")]
+    #[test_case(true, None, Some("1") => true ; "CLICOLOR_FORCE with a terminal")]
+    #[test_case(false, None, Some("0") => false ; "CLICOLOR_FORCE of 0 without a terminal")]
+    #[test_case(false, None, Some("") => false ; "empty CLICOLOR_FORCE without a terminal")]
+    #[test_case(true, None, Some("0") => true ; "CLICOLOR_FORCE of 0 with a terminal")]
+    #[test_case(false, Some("1"), Some("1") => false ; "NO_COLOR wins over CLICOLOR_FORCE without a terminal")]
+    #[test_case(true, Some("1"), Some("1") => false ; "NO_COLOR wins over CLICOLOR_FORCE with a terminal")]
+    #[test_case(false, Some(""), Some("1") => true ; "empty NO_COLOR with CLICOLOR_FORCE")]
+    fn color_wanted_tests(
+        is_terminal: bool,
+        no_color: Option<&str>,
+        clicolor_force: Option<&str>,
+    ) -> bool {
+        color_wanted(
+            is_terminal,
+            no_color.map(OsStr::new),
+            clicolor_force.map(OsStr::new),
+        )
+    }
+
+    #[test_case(None => 0 ; "unset only logs errors")]
+    #[test_case(Some("0") => 0 ; "zero only logs errors")]
+    #[test_case(Some("1") => 2 ; "one logs a summary")]
+    #[test_case(Some("2") => 3 ; "two logs verbosely")]
+    #[test_case(Some("10") => 3 ; "higher levels log verbosely")]
+    #[test_case(Some("yes") => 3 ; "non-numeric value logs verbosely")]
+    #[test_case(Some("") => 3 ; "empty value logs verbosely")]
+    fn log_verbosity_tests(pylaunch_debug: Option<&str>) -> usize {
+        log_verbosity(pylaunch_debug.map(OsStr::new))
+    }
+
+    #[test_case(RequestedVersion::Any, vec![], None => vec![RequestedVersion::Any] ; "nothing set")]
+    #[test_case(RequestedVersion::Any, vec![RequestedVersion::Exact(3, 11)], None => vec![RequestedVersion::Exact(3, 11)] ; "environment variable")]
+    #[test_case(RequestedVersion::Any, vec![], Some(RequestedVersion::Exact(3, 10)) => vec![RequestedVersion::Exact(3, 10)] ; "config default")]
+    #[test_case(RequestedVersion::Any, vec![RequestedVersion::Exact(3, 11)], Some(RequestedVersion::Exact(3, 10)) => vec![RequestedVersion::Exact(3, 11)] ; "environment variable wins over config")]
+    #[test_case(RequestedVersion::Any, vec![RequestedVersion::Exact(3, 12), RequestedVersion::Any], Some(RequestedVersion::Exact(3, 10)) => vec![RequestedVersion::Exact(3, 12), RequestedVersion::Exact(3, 10)] ; "config fills in an Any from a list")]
+    #[test_case(RequestedVersion::MajorOnly(3), vec![RequestedVersion::Exact(3, 9)], None => vec![RequestedVersion::Exact(3, 9)] ; "major version environment variable")]
+    #[test_case(RequestedVersion::MajorOnly(3), vec![], Some(RequestedVersion::Exact(3, 10)) => vec![RequestedVersion::MajorOnly(3)] ; "config only applies to Any")]
+    #[test_case(RequestedVersion::Exact(3, 8), vec![], Some(RequestedVersion::Exact(3, 10)) => vec![RequestedVersion::Exact(3, 8)] ; "exact version is kept")]
+    fn default_requests_tests(
+        requested: RequestedVersion,
+        env_versions: Vec<RequestedVersion>,
+        config_version: Option<RequestedVersion>,
+    ) -> Vec<RequestedVersion> {
+        default_requests(requested, env_versions, || config_version)
+    }
+
+    #[test]
+    fn default_requests_only_loads_config_when_needed() {
+        default_requests(RequestedVersion::Exact(3, 8), Vec::new(), || {
+            panic!("config looked up")
+        });
+    }
+
+    #[test_case(RequestedVersion::Exact(3, 10) => None ; "same version")]
+    #[test_case(RequestedVersion::MajorOnly(3) => None ; "same major version")]
+    #[test_case(RequestedVersion::AtLeast(3, 8) => None ; "newer than the minimum")]
+    #[test_case(RequestedVersion::Exact(3, 12) => Some("the activated virtual environment (/venv/bin/python) is Python 3.10; using Python 3.12 as requested".to_string()) ; "different minor version")]
+    #[test_case(RequestedVersion::MajorOnly(2) => Some("the activated virtual environment (/venv/bin/python) is Python 3.10; using Python 2 as requested".to_string()) ; "different major version")]
+    #[test_case(RequestedVersion::AtLeast(3, 11) => Some("the activated virtual environment (/venv/bin/python) is Python 3.10; using Python 3.11+ as requested".to_string()) ; "older than the minimum")]
+    fn venv_conflict_tests(requested: RequestedVersion) -> Option<String> {
+        venv_conflict(
+            ExactVersion::new(3, 10),
+            Path::new("/venv/bin/python"),
+            requested,
+        )
+    }
+
+    #[test_case("-" => None ; "bare dash for stdin is None")]
+    #[test_case("3.6" => None ; "missing leading dash is None")]
     #[test_case("-S" => None ; "unrecognized short flag is None")]
     #[test_case("--something" => None ; "unrecognized long flag is None")]
     #[test_case("-3" => Some(RequestedVersion::MajorOnly(3)) ; "major version")]
     #[test_case("-3.6" => Some(RequestedVersion::Exact(3, 6)) ; "Exact/major.minor")]
     #[test_case("-42.13" => Some(RequestedVersion::Exact(42, 13)) ; "double-digit major & minor versions")]
+    #[test_case("-3.10" => Some(RequestedVersion::Exact(3, 10)) ; "double-digit minor version")]
+    #[test_case("-3.8+" => Some(RequestedVersion::AtLeast(3, 8)) ; "minimum version")]
     #[test_case("-3.6.4" => None ; "version flag with micro version is None")]
+    #[test_case("-3." => None ; "trailing dot is None")]
+    #[test_case("-.6" => None ; "leading dot is None")]
+    #[test_case("-3..6" => None ; "empty component between dots is None")]
+    #[test_case("-+3" => None ; "signed major version is None")]
+    #[test_case("-3.+6" => None ; "signed minor version is None")]
+    #[test_case("-03.006" => Some(RequestedVersion::Exact(3, 6)) ; "leading zeros are normalized")]
+    #[test_case("-3.12-64" => Some(RequestedVersion::Exact(3, 12)) ; "64-bit architecture suffix is ignored")]
+    #[test_case("-3-32" => Some(RequestedVersion::MajorOnly(3)) ; "32-bit architecture suffix is ignored")]
     fn version_from_flag_tests(flag: &str) -> Option<RequestedVersion> {
         version_from_flag(flag)
     }
 
+    #[test_case("-3.11.4" => true ; "micro version")]
+    #[test_case("-3.11" => false ; "major.minor")]
+    #[test_case("-3" => false ; "major only")]
+    #[test_case("-3.11.x" => false ; "non-digit micro version")]
+    #[test_case("-3.11." => false ; "empty micro version")]
+    #[test_case("-3.11.4.1" => false ; "four components")]
+    #[test_case("3.11.4" => false ; "missing leading dash")]
+    fn is_micro_version_flag_tests(flag: &str) -> bool {
+        is_micro_version_flag(flag)
+    }
+
+    #[test_case("-cpython@3.11" => Some(Ok((Implementation::CPython, RequestedVersion::Exact(3, 11)))) ; "CPython")]
+    #[test_case("-pypy@3.9" => Some(Ok((Implementation::PyPy, RequestedVersion::Exact(3, 9)))) ; "PyPy")]
+    #[test_case("-3.11" => None ; "version without an implementation is left to version_from_flag")]
+    #[test_case("pypy@3.9" => None ; "missing leading dash is None")]
+    #[test_case("-jython@2.7" => Some(Err(crate::Error::UnknownImplementation("jython".to_string()))) ; "unknown implementation is an error")]
+    fn spec_from_flag_tests(
+        flag: &str,
+    ) -> Option<crate::Result<(Implementation, RequestedVersion)>> {
+        spec_from_flag(flag)
+    }
+
     #[test]
     fn test_help_message() {
         let launcher_path = "/some/path/to/launcher";
         let python_path = "/a/path/to/python";
 
-        let help = help_message(&PathBuf::from(launcher_path), &PathBuf::from(python_path));
+        let executables = vec![(ExactVersion::new(3, 11), PathBuf::from(python_path))];
+
+        let help = help_message(
+            &PathBuf::from(launcher_path),
+            &PathBuf::from(python_path),
+            RequestedVersion::MajorOnly(3),
+            &executables,
+        );
         assert!(help.contains(env!("CARGO_PKG_VERSION")));
+        assert!(help.contains("Installed interpreters (default version: 3):"));
         assert!(help.contains(launcher_path));
         assert!(help.contains(python_path));
+        assert!(help.contains("3.11"));
+    }
+
+    #[test]
+    fn test_help_message_no_executables() {
+        let help = help_message(
+            &PathBuf::from("/some/path/to/launcher"),
+            &PathBuf::from("/a/path/to/python"),
+            RequestedVersion::Any,
+            &[],
+        );
+        assert!(help.contains("Installed interpreters (default version: *):"));
+        assert!(help.contains("No executable found for Python\n"));
+    }
+
+    /// The [`ExecutableTable`] of `executables`, none of which are of unknown
+    /// version.
+    fn listing(
+        executables: &[(ExactVersion, PathBuf)],
+        order: ListOrder,
+        full_versions: Option<&BTreeMap<PathBuf, String>>,
+        default_request: RequestedVersion,
+        default: Option<&Path>,
+        color: bool,
+    ) -> ExecutableTable {
+        ExecutableTable {
+            executables: executables.to_vec(),
+            unknown: Vec::new(),
+            order,
+            full_versions: full_versions.cloned(),
+            default_request,
+            default: default.map(Path::to_path_buf),
+            color,
+        }
     }
 
     #[test]
     fn test_list_executables() {
-        let mut executables: HashMap<ExactVersion, PathBuf> = HashMap::new();
+        let mut executables: Vec<(ExactVersion, PathBuf)> = Vec::new();
 
         assert_eq!(
-            list_executables(&executables),
+            list_executables(&listing(
+                &executables,
+                ListOrder::VersionDescending,
+                None,
+                RequestedVersion::Any,
+                None,
+                false
+            )),
             Err(crate::Error::NoExecutableFound(RequestedVersion::Any))
         );
 
         let python27_path = "/path/to/2/7/python";
-        executables.insert(
+        executables.push((
             ExactVersion { major: 2, minor: 7 },
             PathBuf::from(python27_path),
-        );
+        ));
         let python36_path = "/path/to/3/6/python";
-        executables.insert(
+        executables.push((
             ExactVersion { major: 3, minor: 6 },
             PathBuf::from(python36_path),
-        );
+        ));
         let python37_path = "/path/to/3/7/python";
-        executables.insert(
+        executables.push((
             ExactVersion { major: 3, minor: 7 },
             PathBuf::from(python37_path),
-        );
+        ));
 
         // Tests try not to make any guarantees about explicit formatting, just
         // that the interpreters are in descending order of version and the
         // interpreter version comes before the path (i.e. in column order).
-        let executables_list = list_executables(&executables).unwrap();
+        let executables_list = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            None,
+            RequestedVersion::Any,
+            None,
+            false,
+        ))
+        .unwrap();
         // No critical data is missing.
         assert!(executables_list.contains("2.7"));
         assert!(executables_list.contains(python27_path));
@@ -344,11 +3110,461 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_list_executables_full_versions() {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 11), PathBuf::from("/bin/python3.11")),
+        ];
+        let full_versions =
+            BTreeMap::from([(PathBuf::from("/bin/python3.11"), "3.11.4".to_string())]);
+
+        let executables_list = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            Some(&full_versions),
+            RequestedVersion::Any,
+            None,
+            false,
+        ))
+        .unwrap();
+        let lines: Vec<&str> = executables_list.lines().map(str::trim_end).collect();
+        assert!(lines[0].contains("/bin/python3.11") && lines[0].ends_with("3.11.4"));
+        // Executables which didn't report a version get a placeholder.
+        assert!(lines[1].contains("/bin/python3.8") && lines[1].ends_with('?'));
+
+        let colored = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            Some(&full_versions),
+            RequestedVersion::Any,
+            None,
+            true,
+        ))
+        .unwrap();
+        assert!(colored.lines().next().unwrap().contains("Full version"));
+    }
+
+    #[test]
+    fn test_list_executables_shared_version() {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/python3.9")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/pypy3.9")),
+        ];
+
+        let executables_list = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            None,
+            RequestedVersion::Any,
+            None,
+            false,
+        ))
+        .unwrap();
+        let python39_index = executables_list.find("/bin/python3.9").unwrap();
+        let pypy39_index = executables_list.find("/bin/pypy3.9").unwrap();
+        let python38_index = executables_list.find("/bin/python3.8").unwrap();
+        assert!(python39_index < pypy39_index);
+        assert!(pypy39_index < python38_index);
+    }
+
+    #[test]
+    fn test_list_grouped() {
+        let executables = vec![
+            (ExactVersion::new(2, 7), PathBuf::from("/bin/python2.7")),
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/python3.9")),
+        ];
+        let defaults = HashMap::from([
+            (2, PathBuf::from("/bin/python2.7")),
+            (3, PathBuf::from("/bin/python3.8")),
+        ]);
+
+        let grouped = list_grouped(&executables, ListOrder::VersionDescending, None, &defaults);
+        let lines: Vec<Vec<&str>> = grouped
+            .lines()
+            .map(|line| line.split('│').map(str::trim).collect())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                vec!["Python 3"],
+                vec!["3.9", "/bin/python3.9", ""],
+                vec!["3.8", "/bin/python3.8", "py -3"],
+                vec!["Python 2"],
+                vec!["2.7", "/bin/python2.7", "py -2"],
+            ]
+        );
+
+        let ascending = list_grouped(
+            &executables,
+            ListOrder::VersionAscending,
+            None,
+            &HashMap::new(),
+        );
+        assert!(ascending.find("Python 2").unwrap() < ascending.find("Python 3").unwrap());
+        assert!(!ascending.contains("py -"));
+    }
+
+    #[test]
+    fn test_list_grouped_json() {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/python3.9")),
+            (ExactVersion::new(2, 7), PathBuf::from("/bin/python2.7")),
+        ];
+        // A default which isn't listed (e.g. an unversioned `python3`).
+        let defaults = HashMap::from([
+            (2, PathBuf::from("/bin/python2.7")),
+            (3, PathBuf::from("/bin/python3")),
+        ]);
+
+        let grouped: serde_json::Value = serde_json::from_str(&list_grouped_json(
+            executables,
+            ListOrder::VersionDescending,
+            None,
+            &defaults,
+        ))
+        .unwrap();
+        assert_eq!(
+            grouped,
+            json!([
+                {
+                    "major": 3,
+                    "default": null,
+                    "interpreters": [
+                        {"version": "3.9", "path": "/bin/python3.9"},
+                        {"version": "3.8", "path": "/bin/python3.8"},
+                    ],
+                },
+                {
+                    "major": 2,
+                    "default": "/bin/python2.7",
+                    "interpreters": [{"version": "2.7", "path": "/bin/python2.7"}],
+                },
+            ])
+        );
+    }
+
+    #[test_case(ListOrder::VersionDescending => vec!["/b/python3.11", "/a/pypy3.11", "/a/python3.10", "/b/python3.9", "/a/python3.8"] ; "version descending")]
+    #[test_case(ListOrder::VersionAscending => vec!["/a/python3.8", "/b/python3.9", "/a/python3.10", "/b/python3.11", "/a/pypy3.11"] ; "version ascending")]
+    #[test_case(ListOrder::Path => vec!["/a/pypy3.11", "/a/python3.10", "/a/python3.8", "/b/python3.11", "/b/python3.9"] ; "path")]
+    fn list_order_tests(order: ListOrder) -> Vec<String> {
+        let mut executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/a/python3.8")),
+            (ExactVersion::new(3, 11), PathBuf::from("/b/python3.11")),
+            (ExactVersion::new(3, 10), PathBuf::from("/a/python3.10")),
+            (ExactVersion::new(3, 11), PathBuf::from("/a/pypy3.11")),
+            (ExactVersion::new(3, 9), PathBuf::from("/b/python3.9")),
+        ];
+        order.sort(&mut executables);
+        executables
+            .into_iter()
+            .map(|(_, path)| path.display().to_string())
+            .collect()
+    }
+
+    #[test_case(&[] => Some(Ok(ListOptions { implementation: None, order: ListOrder::VersionDescending, full: false, grouped: false })) ; "nothing")]
+    #[test_case(&["--sort", "path"] => Some(Ok(ListOptions { implementation: None, order: ListOrder::Path, full: false, grouped: false })) ; "sort")]
+    #[test_case(&["--sort", "version-asc", "--impl", "pypy"] => Some(Ok(ListOptions { implementation: Some(Implementation::PyPy), order: ListOrder::VersionAscending, full: false, grouped: false })) ; "sort and implementation")]
+    #[test_case(&["--impl", "cpython", "--sort", "version-desc"] => Some(Ok(ListOptions { implementation: Some(Implementation::CPython), order: ListOrder::VersionDescending, full: false, grouped: false })) ; "implementation and sort")]
+    #[test_case(&["--full"] => Some(Ok(ListOptions { implementation: None, order: ListOrder::VersionDescending, full: true, grouped: false })) ; "full")]
+    #[test_case(&["--impl", "pypy", "--full", "--sort", "path"] => Some(Ok(ListOptions { implementation: Some(Implementation::PyPy), order: ListOrder::Path, full: true, grouped: false })) ; "full between the others")]
+    #[test_case(&["--grouped", "--full"] => Some(Ok(ListOptions { implementation: None, order: ListOrder::VersionDescending, full: true, grouped: true })) ; "grouped")]
+    #[test_case(&["--grouped", "--grouped"] => None ; "grouped twice")]
+    #[test_case(&["--full", "--full"] => None ; "repeated full")]
+    #[test_case(&["--full", "yes"] => None ; "full with a value")]
+    #[test_case(&["--sort", "size"] => None ; "unknown order")]
+    #[test_case(&["--sort"] => None ; "missing order")]
+    #[test_case(&["--sort", "path", "--sort", "path"] => None ; "repeated sort")]
+    #[test_case(&["--impl", "jython"] => Some(Err(crate::Error::UnknownImplementation("jython".to_string()))) ; "unknown implementation")]
+    fn list_filter_tests(modifiers: &[&str]) -> Option<crate::Result<ListOptions>> {
+        let modifiers: Vec<String> = modifiers.iter().map(ToString::to_string).collect();
+        list_filter(&modifiers)
+    }
+
+    /// Records each write separately, failing with `BrokenPipe` once `limit`
+    /// writes have been made.
+    struct RecordingWriter {
+        writes: Vec<String>,
+        limit: usize,
+    }
+
+    impl io::Write for RecordingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.writes.len() == self.limit {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            self.writes.push(String::from_utf8(buf.to_vec()).unwrap());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test_case(false => vec![
+        " 3.11 │ /bin/python3.11 \n",
+        " 3.9  │ /bin/pypy3.9    \n",
+        " 3.8  │ /bin/python3.8  \n",
+    ] ; "plain")]
+    #[test_case(true => vec![
+        "\x1b[1m Version (default: 3) \x1b[0m│\x1b[1m Path            \x1b[0m\n",
+        "\x1b[38;5;10m\x1b[1m 3.11                 \x1b[0m│\x1b[38;5;10m\x1b[1m /bin/python3.11 \x1b[0m\n",
+        " 3.9                  │ /bin/pypy3.9    \n",
+        " 3.8                  │ /bin/python3.8  \n",
+    ] ; "color")]
+    fn test_write_executables(color: bool) -> Vec<String> {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 11), PathBuf::from("/bin/python3.11")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/pypy3.9")),
+        ];
+
+        // Each line is written as a whole.
+        let mut writer = RecordingWriter {
+            writes: Vec::new(),
+            limit: usize::MAX,
+        };
+        write_executables(
+            &mut writer,
+            &listing(
+                &executables,
+                ListOrder::VersionDescending,
+                None,
+                RequestedVersion::MajorOnly(3),
+                Some(Path::new("/bin/python3.11")),
+                color,
+            ),
+        )
+        .unwrap();
+        writer.writes
+    }
+
+    #[test_case(false, false ; "plain")]
+    #[test_case(true, false ; "color")]
+    #[test_case(false, true ; "full versions")]
+    #[test_case(true, true ; "color and full versions")]
+    fn write_executables_matches_list_executables(color: bool, full: bool) {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 11), PathBuf::from("/bin/python3.11")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/pypy3.9")),
+        ];
+        let full_versions =
+            BTreeMap::from([(PathBuf::from("/bin/python3.11"), "3.11.4".to_string())]);
+        let full_versions = full.then_some(&full_versions);
+        let default = Some(Path::new("/bin/python3.11"));
+
+        let mut writer = RecordingWriter {
+            writes: Vec::new(),
+            limit: usize::MAX,
+        };
+        write_executables(
+            &mut writer,
+            &listing(
+                &executables,
+                ListOrder::VersionDescending,
+                full_versions,
+                RequestedVersion::Any,
+                default,
+                color,
+            ),
+        )
+        .unwrap();
+        let batch = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            full_versions,
+            RequestedVersion::Any,
+            default,
+            color,
+        ))
+        .unwrap();
+
+        // Streamed a line at a time ...
+        assert!(writer
+            .writes
+            .iter()
+            .all(|write| write.ends_with('\n') && write.matches('\n').count() == 1));
+        // ... to the same output.
+        assert_eq!(writer.writes.concat(), batch);
+    }
+
+    #[test]
+    fn test_write_executables_stops_on_error() {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 11), PathBuf::from("/bin/python3.11")),
+        ];
+        let mut writer = RecordingWriter {
+            writes: Vec::new(),
+            limit: 1,
+        };
+
+        let error = write_executables(
+            &mut writer,
+            &listing(
+                &executables,
+                ListOrder::VersionDescending,
+                None,
+                RequestedVersion::Any,
+                None,
+                false,
+            ),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(writer.writes, vec![" 3.11 │ /bin/python3.11 \n"]);
+    }
+
+    #[test]
+    fn test_list_sourced_executables_shadowed() {
+        let executables = vec![
+            (
+                ExactVersion::new(3, 8),
+                PathBuf::from("/bin/python3.8"),
+                crate::Source::Path,
+            ),
+            (
+                ExactVersion::new(3, 9),
+                PathBuf::from("/bin/python3.9"),
+                crate::Source::Path,
+            ),
+        ];
+        let shadowed = vec![
+            (
+                ExactVersion::new(3, 9),
+                PathBuf::from("/usr/bin/python3.9"),
+                crate::Source::Path,
+            ),
+            (
+                ExactVersion::new(3, 9),
+                PathBuf::from("/opt/python3.9"),
+                crate::Source::ExtraPaths,
+            ),
+        ];
+
+        let output = list_sourced_executables(None, &executables, &shadowed).unwrap();
+        let lines: Vec<&str> = output.lines().map(str::trim).collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("/bin/python3.9") && lines[0].ends_with("PATH"));
+        assert!(lines[1].contains("/usr/bin/python3.9") && lines[1].ends_with("PATH (shadowed)"));
+        assert!(lines[2].contains("/opt/python3.9"));
+        assert!(lines[2].ends_with("config extra-paths (shadowed)"));
+        assert!(lines[3].contains("/bin/python3.8"));
+    }
+
+    #[test_case(Some(ExactVersion::new(3, 11)) => "3.11".to_string() ; "known version")]
+    #[test_case(None => "unknown".to_string() ; "unknown version")]
+    fn list_sourced_executables_venv_version(version: Option<ExactVersion>) -> String {
+        let venv = (version, PathBuf::from("/venv/bin/python"));
+        let executables = vec![(
+            ExactVersion::new(3, 9),
+            PathBuf::from("/bin/python3.9"),
+            crate::Source::Path,
+        )];
+
+        let output = list_sourced_executables(Some(&venv), &executables, &[]).unwrap();
+        let venv_row = output.lines().next().unwrap();
+        assert!(venv_row.contains("/venv/bin/python"));
+        venv_row.split('│').next().unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn test_list_executables_color() {
+        let executables = vec![
+            (ExactVersion::new(3, 8), PathBuf::from("/bin/python3.8")),
+            (ExactVersion::new(3, 9), PathBuf::from("/bin/python3.9")),
+        ];
+        let default = Path::new("/bin/python3.8");
+
+        let plain = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            None,
+            RequestedVersion::Any,
+            Some(default),
+            false,
+        ))
+        .unwrap();
+        assert!(!plain.contains('\u{1b}'));
+        assert!(!plain.contains("Version"));
+        assert_eq!(
+            plain,
+            list_executables(&listing(
+                &executables,
+                ListOrder::VersionDescending,
+                None,
+                RequestedVersion::Any,
+                None,
+                false
+            ))
+            .unwrap()
+        );
+
+        let colored = list_executables(&listing(
+            &executables,
+            ListOrder::VersionDescending,
+            None,
+            RequestedVersion::Any,
+            Some(default),
+            true,
+        ))
+        .unwrap();
+        assert!(colored.contains("Version (default: *)"));
+        // Only the header and the default are styled.
+        let styled_lines: Vec<&str> = colored
+            .lines()
+            .filter(|line| line.contains('\u{1b}'))
+            .collect();
+        assert_eq!(styled_lines.len(), 2);
+        assert!(styled_lines[0].contains("Version"));
+        assert!(styled_lines[1].contains("/bin/python3.8"));
+    }
+
+    #[test_case("/usr/bin" => "'/usr/bin'" ; "plain")]
+    #[test_case("/path with/spaces" => "'/path with/spaces'" ; "spaces")]
+    #[test_case("/it's/here" => "'/it'\\''s/here'" ; "single quote")]
+    fn shell_quote_tests(text: &str) -> String {
+        shell_quote(text)
+    }
+
+    #[test_case("/usr/bin/python3.9" => "export PY_PYTHON=3.9\nPATH_add '/usr/bin'\n" ; "versioned executable")]
+    #[test_case("/path/to/venv/bin/python" => "PATH_add '/path/to/venv/bin'\n" ; "unversioned executable")]
+    fn direnv_block_tests(executable: &str) -> String {
+        direnv_block(&PathBuf::from(executable))
+    }
+
+    #[test_case("home = /usr/bin\nversion = 3.11.4\n" => Ok(ExactVersion::new(3, 11)) ; "venv")]
+    #[test_case("home = /usr/bin\nversion_info = 3.12.1.final.0\n" => Ok(ExactVersion::new(3, 12)) ; "version_info")]
+    #[test_case("version=3.9" => Ok(ExactVersion::new(3, 9)) ; "no whitespace")]
+    #[test_case("home = /usr/bin\n" => matches Err(crate::Error::InvalidPyvenvCfg(_)) ; "no version")]
+    #[test_case("version = three" => matches Err(crate::Error::InvalidPyvenvCfg(_)) ; "invalid version")]
+    fn venv_base_version_tests(contents: &str) -> crate::Result<ExactVersion> {
+        let venv_root = tempfile::tempdir().unwrap();
+        fs::write(venv_root.path().join(PYVENV_CFG), contents).unwrap();
+        venv_base_version(venv_root.path())
+    }
+
+    #[test]
+    fn venv_base_version_missing_pyvenv_cfg() {
+        let venv_root = tempfile::tempdir().unwrap();
+        assert_eq!(
+            venv_base_version(venv_root.path()),
+            Err(crate::Error::InvalidPyvenvCfg(
+                venv_root.path().join(PYVENV_CFG)
+            ))
+        );
+    }
+
     #[test]
     fn test_venv_executable_path() {
         let venv_root = "/path/to/venv";
         assert_eq!(
-            venv_executable_path(&venv_root),
+            venv_executable_path(venv_root),
             PathBuf::from("/path/to/venv/bin/python")
         );
     }
@@ -365,14 +3581,79 @@ mod tests {
     #[test_case("#! /usr/bin/python3.7" => Some(RequestedVersion::Exact(3, 7)) ; "typical 'python' with minor version")]
     #[test_case("#! python3.7" => Some(RequestedVersion::Exact(3, 7)) ; "bare 'python' with minor version")]
     #[test_case("#!/usr/bin/python" => Some(RequestedVersion::Any) ; "no space between shebang and path")]
+    #[test_case("#! /usr/bin/env python3 -S" => Some(RequestedVersion::MajorOnly(3)) ; "version followed by an argument")]
+    #[test_case("#! /usr/bin/python -S" => Some(RequestedVersion::Any) ; "no version followed by an argument")]
+    #[test_case("\u{feff}#! /usr/bin/env python3.7" => Some(RequestedVersion::Exact(3, 7)) ; "byte order mark")]
+    #[test_case("\u{feff}# /usr/bin/python3.7" => None ; "byte order mark without a shebang")]
+    #[test_case("#! /usr/bin/env python3.7\r\nimport sys\r\n" => Some(RequestedVersion::Exact(3, 7)) ; "CRLF line ending")]
+    #[test_case("\u{feff}#!/usr/bin/python3\r\n" => Some(RequestedVersion::MajorOnly(3)) ; "byte order mark and CRLF line ending")]
     fn parse_python_shebang_tests(shebang: &str) -> Option<RequestedVersion> {
-        parse_python_shebang(&mut shebang.as_bytes())
+        parse_python_shebang(&mut shebang.as_bytes()).map(|(version, _)| version)
+    }
+
+    #[test_case(" /usr/bin/env python3.7\n" => Some((RequestedVersion::Exact(3, 7), Vec::new())) ; "no arguments")]
+    #[test_case("/usr/bin/env python3 -S" => Some((RequestedVersion::MajorOnly(3), vec!["-S".to_string()])) ; "single argument")]
+    #[test_case("/usr/bin/python -S -v\n" => Some((RequestedVersion::Any, vec!["-S".to_string(), "-v".to_string()])) ; "multiple arguments without a version")]
+    #[test_case("/usr/bin/python3.7\t-E  -s" => Some((RequestedVersion::Exact(3, 7), vec!["-E".to_string(), "-s".to_string()])) ; "arguments separated by assorted whitespace")]
+    #[test_case("/bin/sh -e" => None ; "non-Python shebang")]
+    #[test_case("/opt/homebrew/bin/python3" => Some((RequestedVersion::MajorOnly(3), Vec::new())) ; "Homebrew on Apple Silicon")]
+    #[test_case("/usr/local/opt/python@3.11/bin/python3.11" => Some((RequestedVersion::Exact(3, 11), Vec::new())) ; "Homebrew keg")]
+    #[test_case("/home/user/.pyenv/versions/3.10.4/bin/python3.10 -u" => Some((RequestedVersion::Exact(3, 10), vec!["-u".to_string()])) ; "pyenv")]
+    #[test_case("/usr/local/bin/env python3" => Some((RequestedVersion::MajorOnly(3), Vec::new())) ; "env in another directory")]
+    #[test_case("/usr/bin/python3-config" => None ; "Python-related executable")]
+    #[test_case("/usr/bin/python3.9abc" => None ; "trailing garbage after the version")]
+    #[test_case("/usr/bin/env python3.x" => None ; "non-numeric minor version")]
+    #[test_case("/usr/bin/env" => None ; "env without an interpreter")]
+    #[test_case("/usr/bin/env -S python3 -X dev" => Some((RequestedVersion::MajorOnly(3), vec!["-X".to_string(), "dev".to_string()])) ; "env -S")]
+    #[test_case("/usr/bin/env PYTHONHASHSEED=0 python3.11" => Some((RequestedVersion::Exact(3, 11), Vec::new())) ; "env with an assignment")]
+    #[test_case("/usr/bin/env -S PYTHONHASHSEED=0 PYTHONUTF8=1 python -E" => Some((RequestedVersion::Any, vec!["-E".to_string()])) ; "env -S with assignments")]
+    #[test_case("/usr/bin/env -S bash -e" => None ; "env -S without Python")]
+    #[test_case("/usr/bin/env pythonw3.11" => Some((RequestedVersion::Exact(3, 11), Vec::new())) ; "env pythonw with a version")]
+    #[test_case("/usr/bin/pythonw" => Some((RequestedVersion::Any, Vec::new())) ; "pythonw")]
+    #[test_case("/usr/bin/pythonw3 -u" => Some((RequestedVersion::MajorOnly(3), vec!["-u".to_string()])) ; "pythonw with a major version and an argument")]
+    #[test_case("/usr/bin/pythonww3" => None ; "doubled w")]
+    #[test_case("" => None ; "empty")]
+    fn split_shebang_tests(line: &str) -> Option<(RequestedVersion, Vec<String>)> {
+        split_shebang(line)
     }
 
     #[test_case(&[0x23, 0x21, 0xc0, 0xaf] => None ; "invalid UTF-8")]
+    #[test_case(&[0xef, 0xbb] => None ; "truncated byte order mark")]
     fn parse_python_sheban_include_invalid_bytes_tests(
         mut shebang: &[u8],
     ) -> Option<RequestedVersion> {
-        parse_python_shebang(&mut shebang)
+        parse_python_shebang(&mut shebang).map(|(version, _)| version)
+    }
+
+    #[test_case(MAX_SHEBANG_LENGTH, true => Some(RequestedVersion::Exact(3, 7)) ; "line at the limit")]
+    #[test_case(MAX_SHEBANG_LENGTH + 1, true => None ; "line beyond the limit")]
+    #[test_case(MAX_SHEBANG_LENGTH, false => None ; "no newline within the limit")]
+    fn parse_python_shebang_long_line_tests(
+        line_length: usize,
+        newline: bool,
+    ) -> Option<RequestedVersion> {
+        // A first line (after `#!`) of `line_length` bytes, followed by 4 MiB.
+        let mut contents = b"#! /usr/bin/python3.7".to_vec();
+        contents.resize(2 + line_length, b' ');
+        if newline {
+            *contents.last_mut().unwrap() = b'\n';
+        }
+        contents.resize(contents.len() + (4 << 20), b'x');
+
+        let mut reader = contents.as_slice();
+        let version = parse_python_shebang(&mut reader).map(|(version, _)| version);
+        let read = contents.len() - reader.len();
+        assert!(
+            read <= UTF8_BOM.len() + 2 + MAX_SHEBANG_LENGTH + 1,
+            "{}",
+            read
+        );
+        version
+    }
+
+    #[test]
+    fn parse_python_shebang_unbounded_line() {
+        let mut reader = io::Read::chain(&b"#! /usr/bin/python3"[..], io::repeat(b'3'));
+        assert_eq!(parse_python_shebang(&mut reader), None);
     }
 }
@@ -0,0 +1,71 @@
+//! Shell completion scripts.
+//!
+//! The scripts call back into `py --list` so that the version flags offered
+//! reflect the interpreters which are currently installed.
+
+use std::{fmt, str::FromStr};
+
+/// A shell for which a completion script is available.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Shell {
+    type Err = crate::Error;
+
+    fn from_str(name: &str) -> crate::Result<Self> {
+        match name {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(crate::Error::UnsupportedShell(name.to_string())),
+        }
+    }
+}
+
+impl Shell {
+    /// Returns the completion script for the shell.
+    pub fn script(self) -> &'static str {
+        match self {
+            Self::Bash => include_str!("../completions/py.bash"),
+            Self::Zsh => include_str!("../completions/py.zsh"),
+            Self::Fish => include_str!("../completions/py.fish"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("bash" => Ok(Shell::Bash) ; "bash")]
+    #[test_case("zsh" => Ok(Shell::Zsh) ; "zsh")]
+    #[test_case("fish" => Ok(Shell::Fish) ; "fish")]
+    #[test_case("tcsh" => Err(crate::Error::UnsupportedShell("tcsh".to_string())) ; "unsupported shell")]
+    fn shell_from_str_tests(name: &str) -> crate::Result<Shell> {
+        Shell::from_str(name)
+    }
+
+    #[test_case(Shell::Bash => true ; "bash")]
+    #[test_case(Shell::Zsh => true ; "zsh")]
+    #[test_case(Shell::Fish => true ; "fish")]
+    fn script_uses_list_tests(shell: Shell) -> bool {
+        shell.script().contains("py --list")
+    }
+}
@@ -0,0 +1,314 @@
+//! Loading of the user's configuration file.
+//!
+//! The file lives at `$XDG_CONFIG_HOME/py/config.toml` (falling back to
+//! `~/.config/py/config.toml`). Unknown keys are ignored and a malformed file
+//! is treated as if it were empty.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use serde::Deserialize;
+
+use crate::RequestedVersion;
+
+/// The name of the directory holding the configuration file.
+static CONFIG_DIR_NAME: &str = "py";
+
+/// The name of the configuration file.
+static CONFIG_FILE_NAME: &str = "config.toml";
+
+/// The keys recognized in the configuration file.
+static KNOWN_KEYS: [&str; 4] = [
+    "default-version",
+    "extra-paths",
+    "priority-paths",
+    "case-insensitive",
+];
+
+/// The user's configuration.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// The version of Python to use when none is explicitly requested.
+    pub default_version: Option<String>,
+    /// Directories to search for Python executables after `PATH`.
+    #[serde(default)]
+    pub extra_paths: Vec<PathBuf>,
+    /// Directories, most preferred first, whose executables are used over
+    /// those of the same version elsewhere.
+    #[serde(default)]
+    pub priority_paths: Vec<PathBuf>,
+    /// Whether to match executables' names regardless of case, for
+    /// filesystems which are case-insensitive.
+    #[serde(default)]
+    pub case_insensitive: bool,
+}
+
+impl FromStr for Config {
+    type Err = toml::de::Error;
+
+    /// Parses the contents of a configuration file, expanding a leading `~`
+    /// or `$HOME` in its directories (see [`crate::expand_home`]).
+    fn from_str(contents: &str) -> std::result::Result<Self, Self::Err> {
+        let mut config: Self = toml::from_str(contents)?;
+        for directory in config
+            .extra_paths
+            .iter_mut()
+            .chain(config.priority_paths.iter_mut())
+        {
+            *directory = crate::expand_home(directory);
+        }
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// The configuration for this process, which is loaded (see
+    /// [`Config::load`]) the first time it's needed rather than every time.
+    ///
+    /// The file is only loaded again if its location changes (e.g. a different
+    /// `XDG_CONFIG_HOME`).
+    pub fn current() -> Arc<Self> {
+        static CURRENT: Mutex<Option<(Option<PathBuf>, Arc<Config>)>> = Mutex::new(None);
+        let path = config_path();
+        let mut current = CURRENT.lock().unwrap();
+        match &*current {
+            Some((loaded_path, config)) if *loaded_path == path => Arc::clone(config),
+            _ => {
+                let config = Arc::new(path.as_deref().map_or_else(Self::default, Self::load_from));
+                *current = Some((path, Arc::clone(&config)));
+                config
+            }
+        }
+    }
+
+    /// Loads the configuration file, returning the default configuration if
+    /// the file does not exist or cannot be parsed.
+    pub fn load() -> Self {
+        match config_path() {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Loads the configuration from the specified file.
+    pub fn load_from(path: &Path) -> Self {
+        log::debug!("Checking for a config file at {}", path.display());
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                log::debug!("Can't read {}: {}", path.display(), error);
+                return Self::default();
+            }
+        };
+
+        Self::from_str(&contents).unwrap_or_else(|error| {
+            log::debug!(
+                "Ignoring malformed config file {}: {}",
+                path.display(),
+                error
+            );
+            Self::default()
+        })
+    }
+
+    /// The [`RequestedVersion`] specified by `default-version`, if valid.
+    pub fn default_version(&self) -> Option<RequestedVersion> {
+        let version = self.default_version.as_ref()?;
+        match RequestedVersion::from_user_str(version) {
+            Ok(requested_version) => Some(requested_version),
+            Err(error) => {
+                log::debug!("Ignoring invalid default-version {:?}: {}", version, error);
+                None
+            }
+        }
+    }
+
+    /// Describes every problem with the configuration file at `path`; a
+    /// missing file has no problems.
+    pub fn problems_in(path: &Path) -> Vec<String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) if !path.exists() => return Vec::new(),
+            Err(error) => return vec![format!("Can't read {}: {}", path.display(), error)],
+        };
+
+        let mut problems = Vec::new();
+        if let Ok(toml::Value::Table(table)) = contents.parse::<toml::Value>() {
+            for key in table.keys() {
+                if !KNOWN_KEYS.contains(&key.as_str()) {
+                    problems.push(format!("Unknown key {:?} in {}", key, path.display()));
+                }
+            }
+        }
+
+        let config = match Self::from_str(&contents) {
+            Ok(config) => config,
+            Err(error) => {
+                problems.push(format!("Malformed {}: {}", path.display(), error));
+                return problems;
+            }
+        };
+
+        if let Some(version) = &config.default_version {
+            if let Err(error) = RequestedVersion::from_user_str(version) {
+                problems.push(format!("Invalid default-version {:?}: {}", version, error));
+            }
+        }
+        for (key, directories) in [
+            ("extra-paths", &config.extra_paths),
+            ("priority-paths", &config.priority_paths),
+        ] {
+            for directory in directories {
+                if !directory.is_dir() {
+                    problems.push(format!(
+                        "{} directory {} does not exist",
+                        key,
+                        directory.display()
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Returns the path to the configuration file.
+///
+/// `XDG_CONFIG_HOME` is used if set, otherwise `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_home.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+#[cfg(test)]
+#[allow(clippy::unused_unit)] // `test_case` generates a trailing `()` for tests without a return value.
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn from_str_all_keys() {
+        let config =
+            Config::from_str("default-version = \"3.11\"\nextra-paths = [\"/opt/python/bin\"]\npriority-paths = [\"/usr/bin\"]\ncase-insensitive = true")
+                .unwrap();
+        assert_eq!(config.default_version, Some("3.11".to_string()));
+        assert_eq!(config.extra_paths, vec![PathBuf::from("/opt/python/bin")]);
+        assert_eq!(config.priority_paths, vec![PathBuf::from("/usr/bin")]);
+        assert!(config.case_insensitive);
+    }
+
+    #[test]
+    fn from_str_empty() {
+        assert_eq!(Config::from_str("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn from_str_unknown_keys_ignored() {
+        let config = Config::from_str("default-version = \"3\"\nsome-key = 42").unwrap();
+        assert_eq!(config.default_version, Some("3".to_string()));
+    }
+
+    #[test_case("default-version = " ; "missing value")]
+    #[test_case("default-version = 3.11" ; "wrong type")]
+    #[test_case("extra-paths = \"/opt/python/bin\"" ; "not an array")]
+    #[test_case("priority-paths = \"/usr/bin\"" ; "priority-paths not an array")]
+    #[test_case("case-insensitive = \"yes\"" ; "case-insensitive not a boolean")]
+    fn from_str_malformed(contents: &str) {
+        assert!(Config::from_str(contents).is_err());
+    }
+
+    #[test_case(None => None ; "unset")]
+    #[test_case(Some("3.11") => Some(RequestedVersion::Exact(3, 11)) ; "major.minor")]
+    #[test_case(Some("3") => Some(RequestedVersion::MajorOnly(3)) ; "major only")]
+    #[test_case(Some("python3.11") => Some(RequestedVersion::Exact(3, 11)) ; "python prefix")]
+    #[test_case(Some("three") => None ; "invalid version")]
+    fn default_version_tests(default_version: Option<&str>) -> Option<RequestedVersion> {
+        Config {
+            default_version: default_version.map(|v| v.to_string()),
+            ..Config::default()
+        }
+        .default_version()
+    }
+
+    #[test]
+    fn load_from_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            Config::load_from(&dir.path().join(CONFIG_FILE_NAME)),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn problems_in_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Config::problems_in(&dir.path().join(CONFIG_FILE_NAME)).is_empty());
+    }
+
+    #[test]
+    fn problems_in_valid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            format!(
+                "default-version = \"3.11\"\nextra-paths = [{:?}]",
+                dir.path().to_str().unwrap()
+            ),
+        )
+        .unwrap();
+        assert!(Config::problems_in(&path).is_empty());
+    }
+
+    #[test]
+    fn problems_in_malformed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, "default-version = [").unwrap();
+        let problems = Config::problems_in(&path);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Malformed"));
+    }
+
+    #[test]
+    fn problems_in_invalid_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            "default-version = \"three\"\nextra-paths = [\"/path/to/nowhere\"]\npriority-paths = [\"/path/to/elsewhere\"]\nsome-key = 42",
+        )
+        .unwrap();
+        let problems = Config::problems_in(&path);
+        assert_eq!(problems.len(), 4);
+        assert!(problems[0].starts_with("Unknown key \"some-key\""));
+        assert!(problems[1].starts_with("Invalid default-version \"three\""));
+        assert_eq!(
+            problems[2],
+            "extra-paths directory /path/to/nowhere does not exist"
+        );
+        assert_eq!(
+            problems[3],
+            "priority-paths directory /path/to/elsewhere does not exist"
+        );
+    }
+
+    #[test]
+    fn load_from_malformed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, "default-version = [").unwrap();
+        assert_eq!(Config::load_from(&path), Config::default());
+    }
+}
@@ -0,0 +1,51 @@
+//! Reporting why a Python executable couldn't be executed.
+//!
+//! The launcher replaces itself with the interpreter via `execv`, which is
+//! the fastest way to run it and leaves nothing to manage afterwards, so a
+//! failure to do so is the only thing left to report.
+
+use nix::errno::Errno;
+
+/// Describes why an executable couldn't be executed and the exit code to
+/// report it with: the `errno`, or 1 if there isn't one.
+///
+/// The most common failures get a description of what is wrong with the
+/// executable instead of the system's terser one.
+pub fn exec_failure(error: nix::Error) -> (i32, String) {
+    match error.as_errno() {
+        Some(errno) => {
+            let reason = match errno {
+                Errno::ENOENT => "executable not found",
+                Errno::EACCES => "permission denied",
+                Errno::ENOEXEC => "not an executable format",
+                Errno::E2BIG => "argument list too long",
+                _ => errno.desc(),
+            };
+            (errno as i32, reason.to_string())
+        }
+        None => (1, error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::libc;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(Errno::ENOENT => (libc::ENOENT, "executable not found".to_string()) ; "ENOENT")]
+    #[test_case(Errno::EACCES => (libc::EACCES, "permission denied".to_string()) ; "EACCES")]
+    #[test_case(Errno::ENOEXEC => (libc::ENOEXEC, "not an executable format".to_string()) ; "ENOEXEC")]
+    #[test_case(Errno::E2BIG => (libc::E2BIG, "argument list too long".to_string()) ; "E2BIG")]
+    #[test_case(Errno::ELOOP => (libc::ELOOP, Errno::ELOOP.desc().to_string()) ; "other errno")]
+    fn exec_failure_tests(errno: Errno) -> (i32, String) {
+        exec_failure(nix::Error::Sys(errno))
+    }
+
+    #[test]
+    fn exec_failure_without_errno() {
+        let error = nix::Error::InvalidPath;
+        assert_eq!(exec_failure(error), (1, error.to_string()));
+    }
+}
@@ -1,13 +1,24 @@
+pub mod cache;
 pub mod cli;
+pub mod completions;
+pub mod config;
+pub mod exec;
 
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{hash_map::Entry, HashMap, HashSet},
     convert::From,
-    env, fmt,
+    env,
+    ffi::OsStr,
+    fmt,
     fmt::Display,
-    num::ParseIntError,
+    fs, io,
     path::{Path, PathBuf},
+    process::{Command, Stdio},
     str::FromStr,
+    sync::{Mutex, OnceLock},
+    thread,
+    time::{Duration, Instant},
 };
 
 /// [`std::result::Result`] type with [`Error`] as the error type.
@@ -16,11 +27,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Error enum for the entire crate.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
-    /// String passed to [`RequestedVersion::from_str`] or [`ExactVersion::from_str`]
-    /// has an expected digit component that cannot be parsed as an integer.
-    ParseVersionComponentError(ParseIntError),
-    /// [`ExactVersion::from_str`] is passed a string missing a `.`.
-    DotMissing,
+    /// A version (e.g. from a version spec or file name) could not be parsed.
+    ParseVersionError(ParseVersionError),
     /// [`ExactVersion::from_path`] is given a [`Path`] which lacks a file name.
     FileNameMissing,
     /// [`ExactVersion::from_path`] cannot convert a file name to a string.
@@ -30,28 +38,67 @@ pub enum Error {
     /// No Python executable could be found based on the [`RequestedVersion`].
     // cli::{list_executables, find_executable, help}
     NoExecutableFound(RequestedVersion),
+    /// No Python executable could be found based on the [`RequestedVersion`],
+    /// although executables of the listed versions were found.
+    // cli::find_executable
+    NoMatchingExecutable(RequestedVersion, Vec<ExactVersion>),
     /// Multiple CLI flags given when the first flag that is expected to be specified
     /// on its own.
     // cli::Action::from_main
     IllegalArgument(PathBuf, String),
+    /// A completion script was requested for an unsupported shell.
+    // cli::Action::from_main
+    UnsupportedShell(String),
+    /// A virtual environment's `pyvenv.cfg` is missing or lacks a valid
+    /// version.
+    // cli::Action::from_main
+    InvalidPyvenvCfg(PathBuf),
+    /// An explicitly specified interpreter is not an executable file.
+    // cli::Action::from_main
+    NotExecutable(PathBuf),
+    /// A script's shebang requests a version of Python 2, none of which are
+    /// installed.
+    // cli::Action::from_main
+    Python2Unavailable(PathBuf, RequestedVersion),
+    /// A version of Python 2 was explicitly requested, but none which satisfy
+    /// it are installed.
+    // cli::Action::from_main
+    Python2NotInstalled(RequestedVersion),
+    /// A version spec names an unrecognized [`Implementation`].
+    // parse_spec
+    UnknownImplementation(String),
+    /// A version flag follows arguments meant for the interpreter instead of
+    /// coming first.
+    // cli::Action::from_main
+    MisplacedVersionFlag(PathBuf, String),
+    /// A version flag includes a micro version (e.g. `-3.11.4`), which can't
+    /// be requested.
+    // cli::Action::from_main
+    MicroVersionFlag(PathBuf, String),
 }
 
 #[cfg(not(tarpaulin_include))]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::ParseVersionComponentError(int_error) => {
-                write!(f, "Error parsing a version component: {}", int_error)
+            Self::ParseVersionError(parse_error) => {
+                write!(f, "Error parsing a version: {}", parse_error)
             }
-            Self::DotMissing => write!(f, "'.' missing from the version"),
             Self::FileNameMissing => write!(f, "Path object lacks a file name"),
             Self::FileNameToStrError => write!(f, "Failed to convert file name to `str`"),
             Self::PathFileNameError => write!(f, "File name not of the format `pythonX.Y`"),
-            Self::NoExecutableFound(requested_version) => write!(
-                f,
-                "No executable found for {}",
-                requested_version.to_string()
-            ),
+            Self::NoExecutableFound(requested_version) => {
+                write!(f, "No executable found for {}", requested_version)
+            }
+            Self::NoMatchingExecutable(requested_version, available) => {
+                let available: Vec<String> = available.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "Requested {} not found. Available: {}",
+                    requested_version,
+                    available.join(", ")
+                )
+            }
             Self::IllegalArgument(launcher_path, flag) => {
                 write!(
                     f,
@@ -60,6 +107,47 @@ impl fmt::Display for Error {
                     launcher_path.to_string_lossy()
                 )
             }
+            Self::UnsupportedShell(shell) => {
+                write!(
+                    f,
+                    "Completions for `{}` are not supported; expected one of bash, zsh, or fish",
+                    shell
+                )
+            }
+            Self::InvalidPyvenvCfg(path) => {
+                write!(f, "Unable to read a Python version from {}", path.display())
+            }
+            Self::NotExecutable(path) => write!(f, "{} is not an executable file", path.display()),
+            Self::Python2Unavailable(script, requested_version) => write!(
+                f,
+                "{} requests {}, which isn't installed; set PY_SHEBANG_UPGRADE_PYTHON2 to run it with Python 3 instead",
+                script.display(),
+                requested_version
+            ),
+            Self::Python2NotInstalled(requested_version) => write!(
+                f,
+                "{} isn't installed; Python 2 reached its end of life in 2020, so consider Python 3 (e.g. `-3`) instead",
+                requested_version
+            ),
+            Self::UnknownImplementation(name) => write!(
+                f,
+                "Unknown Python implementation `{}`; expected one of cpython or pypy",
+                name
+            ),
+            Self::MisplacedVersionFlag(launcher_path, flag) => write!(
+                f,
+                "The `{}` flag must come before any arguments for Python (e.g. `{} {} -u script.py`)",
+                flag,
+                launcher_path.to_string_lossy(),
+                flag
+            ),
+            Self::MicroVersionFlag(launcher_path, flag) => write!(
+                f,
+                "The `{}` flag includes a micro version, but only a major or major.minor version may be requested (e.g. `{} {}`)",
+                flag,
+                launcher_path.to_string_lossy(),
+                flag.rsplit_once('.').map_or(flag.as_str(), |(flag, _)| flag)
+            ),
         }
     }
 }
@@ -68,13 +156,21 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::ParseVersionComponentError(int_error) => Some(int_error),
-            Self::DotMissing => None,
+            Self::ParseVersionError(parse_error) => Some(parse_error),
             Self::FileNameMissing => None,
             Self::FileNameToStrError => None,
             Self::PathFileNameError => None,
             Self::NoExecutableFound(_) => None,
+            Self::NoMatchingExecutable(_, _) => None,
             Self::IllegalArgument(_, _) => None,
+            Self::UnsupportedShell(_) => None,
+            Self::InvalidPyvenvCfg(_) => None,
+            Self::NotExecutable(_) => None,
+            Self::Python2Unavailable(_, _) => None,
+            Self::Python2NotInstalled(_) => None,
+            Self::UnknownImplementation(_) => None,
+            Self::MisplacedVersionFlag(_, _) => None,
+            Self::MicroVersionFlag(_, _) => None,
         }
     }
 }
@@ -84,20 +180,130 @@ impl Error {
     /// Returns the appropriate [exit code](`exitcode::ExitCode`) for the error.
     pub fn exit_code(&self) -> exitcode::ExitCode {
         match self {
-            Self::ParseVersionComponentError(_) => exitcode::USAGE,
-            Self::DotMissing => exitcode::USAGE,
+            Self::ParseVersionError(_) => exitcode::USAGE,
             Self::FileNameMissing => exitcode::USAGE,
             Self::FileNameToStrError => exitcode::SOFTWARE,
             Self::PathFileNameError => exitcode::SOFTWARE,
             Self::NoExecutableFound(_) => exitcode::USAGE,
+            Self::NoMatchingExecutable(_, _) => exitcode::USAGE,
             Self::IllegalArgument(_, _) => exitcode::USAGE,
+            Self::UnsupportedShell(_) => exitcode::USAGE,
+            Self::InvalidPyvenvCfg(_) => exitcode::USAGE,
+            Self::NotExecutable(_) => exitcode::NOINPUT,
+            Self::Python2Unavailable(_, _) => exitcode::UNAVAILABLE,
+            Self::Python2NotInstalled(_) => exitcode::UNAVAILABLE,
+            Self::UnknownImplementation(_) => exitcode::USAGE,
+            Self::MisplacedVersionFlag(_, _) => exitcode::USAGE,
+            Self::MicroVersionFlag(_, _) => exitcode::USAGE,
+        }
+    }
+
+    /// Whether the error is only that no interpreter satisfies the request,
+    /// which `--quiet` leaves to the exit status to report.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::NoExecutableFound(_)
+                | Self::NoMatchingExecutable(_, _)
+                | Self::Python2Unavailable(_, _)
+                | Self::Python2NotInstalled(_)
+        )
+    }
+}
+
+impl From<ParseVersionError> for Error {
+    fn from(parse_error: ParseVersionError) -> Self {
+        Self::ParseVersionError(parse_error)
+    }
+}
+
+/// Why [`RequestedVersion::from_str`] or [`ExactVersion::from_str`] rejected
+/// a version string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseVersionError {
+    /// A component is empty (e.g. the minor version of `3.`).
+    Empty,
+    /// A component contains something other than ASCII digits (e.g. `x` in
+    /// `3.x`).
+    NonNumeric(String),
+    /// A component is too large to be a version number.
+    TooLarge(String),
+    /// There are more components than `major.minor` (e.g. `3.11.4`).
+    TooManyComponents,
+    /// An [`ExactVersion`] lacks the `.` separating its minor version.
+    DotMissing,
+}
+
+#[cfg(not(tarpaulin_include))]
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "a version component is empty"),
+            Self::NonNumeric(component) => {
+                write!(f, "the version component {:?} is not a number", component)
+            }
+            Self::TooLarge(component) => {
+                write!(f, "the version component {} is too large", component)
+            }
+            Self::TooManyComponents => {
+                write!(f, "too many version components; expected at most `X.Y`")
+            }
+            Self::DotMissing => write!(f, "'.' missing from the version"),
         }
     }
 }
 
+impl std::error::Error for ParseVersionError {}
+
 /// The integral part of a version specifier (e.g. the `X` or `Y` of `X.Y`).
 type ComponentSize = u16;
 
+/// How well an installed [`ExactVersion`] satisfies a [`RequestedVersion`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum VersionMatch {
+    /// The installed version does not satisfy the request.
+    NotAtAll,
+    /// The installed version is one of potentially many which satisfy the
+    /// request (i.e. [`RequestedVersion::Any`], [`RequestedVersion::MajorOnly`],
+    /// or [`RequestedVersion::AtLeast`]).
+    Loosely,
+    /// The installed version is the one requested by
+    /// [`RequestedVersion::Exact`].
+    Exactly,
+}
+
+/// Which of several executables which loosely satisfy a [`RequestedVersion`]
+/// to choose.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Selection {
+    /// The newest version (the default).
+    Newest,
+    /// The oldest version (e.g. to test against a minimum supported version).
+    Oldest,
+}
+
+/// Which executable to use when more than one directory provides the same
+/// [`ExactVersion`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TieBreak {
+    /// The one in the directory searched first (the default).
+    FirstInPath,
+    /// One in a system directory (e.g. `/usr/bin`), falling back to the one in
+    /// the directory searched first.
+    PreferSystem,
+}
+
+impl TieBreak {
+    /// Whether `later`, found after `earlier` for the same version, should be
+    /// used instead of it.
+    fn prefers_later(self, earlier: &Path, later: &Path) -> bool {
+        match self {
+            Self::FirstInPath => false,
+            Self::PreferSystem => !is_system_executable(earlier) && is_system_executable(later),
+        }
+    }
+}
+
 /// The version of Python being searched for.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum RequestedVersion {
@@ -107,40 +313,134 @@ pub enum RequestedVersion {
     MajorOnly(ComponentSize),
     /// The specific `major.minor` version (e.g. `3.9`).
     Exact(ComponentSize, ComponentSize),
+    /// The `major.minor` version or any newer one (e.g. `3.9+`).
+    AtLeast(ComponentSize, ComponentSize),
 }
 
 impl Display for RequestedVersion {
     /// Format to a readable name of the Python version requested, e.g. `Python 3.9`.
+    ///
+    /// The alternate form (`{:#}`) is the version as it would be requested,
+    /// e.g. `3.9`, with `*` standing in for [`RequestedVersion::Any`].
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return match self {
+                Self::Any => write!(f, "*"),
+                Self::MajorOnly(major) => write!(f, "{}", major),
+                Self::Exact(major, minor) => write!(f, "{}.{}", major, minor),
+                Self::AtLeast(major, minor) => write!(f, "{}.{}+", major, minor),
+            };
+        }
+
         let repr = match self {
             Self::Any => "Python".to_string(),
             Self::MajorOnly(major) => format!("Python {}", major),
             Self::Exact(major, minor) => format!("Python {}.{}", major, minor),
+            Self::AtLeast(major, minor) => format!("Python {}.{}+", major, minor),
         };
         write!(f, "{}", repr)
     }
 }
 
+/// Architecture suffixes accepted (and ignored) after a requested version.
+static ARCHITECTURE_SUFFIXES: [&str; 2] = ["-32", "-64"];
+
 impl FromStr for RequestedVersion {
-    type Err = Error;
+    type Err = ParseVersionError;
 
     // XXX Require `python` as a prefix?
-    fn from_str(version_string: &str) -> Result<Self> {
+    fn from_str(version_string: &str) -> std::result::Result<Self, ParseVersionError> {
+        // The Windows launcher accepts an architecture suffix (e.g. `3.12-64`);
+        // there is only one architecture to choose from here.
+        let version_string = match ARCHITECTURE_SUFFIXES
+            .iter()
+            .find_map(|suffix| version_string.strip_suffix(suffix))
+        {
+            Some(version) if !version.is_empty() => {
+                log::debug!("Ignoring the architecture of {:?}", version_string);
+                version
+            }
+            _ => version_string,
+        };
         if version_string.is_empty() {
             Ok(Self::Any)
+        } else if let Some(minimum) = version_string.strip_suffix('+') {
+            let minimum_version = ExactVersion::from_str(minimum)?;
+            Ok(Self::AtLeast(minimum_version.major, minimum_version.minor))
         } else if version_string.contains('.') {
             let exact_version = ExactVersion::from_str(version_string)?;
             Ok(Self::Exact(exact_version.major, exact_version.minor))
         } else {
-            match version_string.parse::<ComponentSize>() {
-                Ok(number) => Ok(Self::MajorOnly(number)),
-                Err(parse_error) => Err(Error::ParseVersionComponentError(parse_error)),
-            }
+            parse_component(version_string).map(Self::MajorOnly)
         }
     }
 }
 
+/// Versions are ordered by major and then minor version, with
+/// [`RequestedVersion::MajorOnly`] sorting below any [`RequestedVersion::Exact`]
+/// of the same major version, [`RequestedVersion::AtLeast`] sorting just above
+/// the [`RequestedVersion::Exact`] of the same version, and
+/// [`RequestedVersion::Any`] sorting below everything else.
+impl Ord for RequestedVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for RequestedVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl RequestedVersion {
+    /// A key which sorts in the order documented on the [`Ord`] implementation;
+    /// `None` sorts before `Some` and `false` before `true`.
+    fn sort_key(self) -> Option<(ComponentSize, Option<ComponentSize>, bool)> {
+        match self {
+            Self::Any => None,
+            Self::MajorOnly(major) => Some((major, None, false)),
+            Self::Exact(major, minor) => Some((major, Some(minor), false)),
+            Self::AtLeast(major, minor) => Some((major, Some(minor), true)),
+        }
+    }
+
+    /// Parses a version specified by a user-controlled source such as an
+    /// environment variable or the config file.
+    ///
+    /// Surrounding whitespace and case are ignored, as is a leading `cpython`,
+    /// `python`, or `v` (e.g. `Python3.11` and `v3.11` are both `3.11`).
+    pub fn from_user_str(version_string: &str) -> std::result::Result<Self, ParseVersionError> {
+        let normalized = version_string.trim().to_ascii_lowercase();
+        let mut version = normalized.as_str();
+        for prefix in ["cpython", "python", "v"].iter() {
+            if let Some(remainder) = version.strip_prefix(prefix) {
+                version = remainder;
+                break;
+            }
+        }
+        Self::from_str(version)
+    }
+
+    /// Determines how well `installed` satisfies this request: [`Self::Any`]
+    /// matches everything loosely, [`Self::MajorOnly`] loosely matches the same
+    /// major version, [`Self::AtLeast`] loosely matches the same or any newer
+    /// version, and [`Self::Exact`] exactly matches the same major and minor
+    /// version.
+    pub fn matches(&self, installed: &ExactVersion) -> VersionMatch {
+        match *self {
+            Self::Any => VersionMatch::Loosely,
+            Self::MajorOnly(major) if installed.major == major => VersionMatch::Loosely,
+            Self::Exact(major, minor) if installed.major == major && installed.minor == minor => {
+                VersionMatch::Exactly
+            }
+            Self::AtLeast(major, minor) if *installed >= ExactVersion::new(major, minor) => {
+                VersionMatch::Loosely
+            }
+            _ => VersionMatch::NotAtAll,
+        }
+    }
+
     /// Returns the string representing the environment variable for the requested version.
     pub fn env_var(self) -> Option<String> {
         match self {
@@ -149,6 +449,30 @@ impl RequestedVersion {
             _ => None,
         }
     }
+
+    /// The version the user wants when none is explicitly requested (and
+    /// neither a virtual environment nor a script's shebang applies): that of
+    /// a `.python-version` file, then a `pyproject.toml`'s `tool.py.version`,
+    /// then `PY_PYTHON` (or `PY_PYTHON{major}` if only a major version
+    /// results), then the config file's `default-version`, and otherwise
+    /// [`RequestedVersion::Any`].
+    pub fn from_env_default() -> Self {
+        Self::env_defaults_traced(&mut Vec::new())[0]
+    }
+
+    /// Every version [`RequestedVersion::from_env_default`] could be, most
+    /// preferred first (as `PY_PYTHON` may list several), which is what the
+    /// launcher searches for; each source checked is recorded in `trace`.
+    pub(crate) fn env_defaults_traced(trace: &mut Vec<cli::ResolutionStep>) -> Vec<Self> {
+        let requested_version = match cli::project_version(trace) {
+            Some((path, file_version)) => {
+                log::info!("Using {:#} from {}", file_version, path.display());
+                file_version
+            }
+            None => Self::Any,
+        };
+        cli::effective_requests(requested_version, trace)
+    }
 }
 
 /// Specifies the `major.minor` version of a Python executable.
@@ -172,30 +496,120 @@ impl Display for ExactVersion {
 }
 
 impl FromStr for ExactVersion {
+    type Err = ParseVersionError;
+
+    fn from_str(version_string: &str) -> std::result::Result<Self, ParseVersionError> {
+        let components: Vec<&str> = version_string.split('.').collect();
+        match components.as_slice() {
+            [major, minor] => Ok(Self {
+                major: parse_component(major)?,
+                minor: parse_component(minor)?,
+            }),
+            [_] => Err(ParseVersionError::DotMissing),
+            _ => Err(ParseVersionError::TooManyComponents),
+        }
+    }
+}
+
+/// Parses a single version component, which must be made up of only ASCII
+/// digits (so e.g. `""`, `"+3"`, and `".6"` are errors); leading zeros are
+/// ignored (e.g. `"03"` is `3`).
+fn parse_component(component: &str) -> std::result::Result<ComponentSize, ParseVersionError> {
+    if component.is_empty() {
+        Err(ParseVersionError::Empty)
+    } else if !component.bytes().all(|byte| byte.is_ascii_digit()) {
+        Err(ParseVersionError::NonNumeric(component.to_string()))
+    } else {
+        component
+            .parse()
+            .map_err(|_| ParseVersionError::TooLarge(component.to_string()))
+    }
+}
+
+/// A Python implementation whose executables can be discovered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Implementation {
+    /// CPython, whose executables are named `pythonX.Y`.
+    CPython,
+    /// PyPy, whose executables are named `pypyX.Y`.
+    PyPy,
+}
+
+impl Display for Implementation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::CPython => "CPython",
+            Self::PyPy => "PyPy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Implementation {
     type Err = Error;
 
-    fn from_str(version_string: &str) -> Result<Self> {
-        match version_string.find('.') {
-            Some(dot_index) => {
-                let major_str = &version_string[..dot_index];
-                let major = match major_str.parse::<ComponentSize>() {
-                    Ok(number) => number,
-                    Err(parse_error) => return Err(Error::ParseVersionComponentError(parse_error)),
-                };
-                let minor_str = &version_string[dot_index + 1..];
-
-                match minor_str.parse::<ComponentSize>() {
-                    Ok(minor) => Ok(Self { major, minor }),
-                    Err(parse_error) => Err(Error::ParseVersionComponentError(parse_error)),
-                }
-            }
-            None => Err(Error::DotMissing),
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cpython" => Ok(Self::CPython),
+            "pypy" => Ok(Self::PyPy),
+            _ => Err(Error::UnknownImplementation(name.to_string())),
         }
     }
 }
 
-fn acceptable_file_name(file_name: &str) -> bool {
-    file_name.len() >= "python3.0".len() && file_name.starts_with("python")
+/// Parses a version spec of the form `impl@X.Y` (e.g. `pypy@3.9`) into the
+/// [`Implementation`] and [`RequestedVersion`] it names; without an `impl@`
+/// prefix (e.g. `3.11`), CPython is assumed.
+pub fn parse_spec(spec: &str) -> Result<(Implementation, RequestedVersion)> {
+    match spec.split_once('@') {
+        Some((implementation, version)) => Ok((
+            Implementation::from_str(implementation)?,
+            RequestedVersion::from_str(version)?,
+        )),
+        None => Ok((Implementation::CPython, RequestedVersion::from_str(spec)?)),
+    }
+}
+
+impl Implementation {
+    /// The prefix of the file name of the implementation's executables.
+    pub fn executable_prefix(self) -> &'static str {
+        match self {
+            Self::CPython => "python",
+            Self::PyPy => "pypy",
+        }
+    }
+}
+
+/// Where a directory searched for executables came from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Source {
+    /// The `--prepend-path` command-line flag.
+    PrependPath,
+    /// The `PATH` environment variable.
+    Path,
+    /// The `--append-path` command-line flag.
+    AppendPath,
+    /// The `PYLAUNCHER_PATH` environment variable.
+    LauncherPath,
+    /// The config file's `extra-paths`.
+    ExtraPaths,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::PrependPath => "--prepend-path",
+            Self::Path => "PATH",
+            Self::AppendPath => "--append-path",
+            Self::LauncherPath => "PYLAUNCHER_PATH",
+            Self::ExtraPaths => "config extra-paths",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn acceptable_file_name(file_name: &str, prefix: &str) -> bool {
+    file_name.len() >= prefix.len() + "3.0".len() && file_name.starts_with(prefix)
 }
 
 impl ExactVersion {
@@ -206,131 +620,1249 @@ impl ExactVersion {
 
     /// Constructs a [`ExactVersion`] from a `pythonX.Y` file path.
     pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_implementation_path(path, Implementation::CPython)
+    }
+
+    /// Constructs a [`ExactVersion`] from the file path of an executable of the
+    /// specified [`Implementation`] (e.g. `pypyX.Y` for [`Implementation::PyPy`]).
+    ///
+    /// A trailing `.exe` is ignored so that Windows installs on drives mounted
+    /// under WSL (e.g. `python3.11.exe`) are recognized, as is a micro version
+    /// which some custom installs include (e.g. `python3.11.4` is `3.11`).
+    pub fn from_implementation_path(path: &Path, implementation: Implementation) -> Result<Self> {
+        Self::from_discovered_path(path, implementation, false)
+    }
+
+    /// Like [`ExactVersion::from_implementation_path`], but with `ignore_case`
+    /// the file name may be in any case (e.g. `Python3.11`), as on a
+    /// case-insensitive filesystem.
+    fn from_discovered_path(
+        path: &Path,
+        implementation: Implementation,
+        ignore_case: bool,
+    ) -> Result<Self> {
+        let prefix = implementation.executable_prefix();
         path.file_name()
             .ok_or(Error::FileNameMissing)
             .and_then(|raw_file_name| match raw_file_name.to_str() {
-                Some(file_name) if acceptable_file_name(file_name) => {
-                    Self::from_str(&file_name["python".len()..])
-                }
-                Some(_) => Err(Error::PathFileNameError),
+                Some(file_name) if ignore_case => Ok(file_name.to_ascii_lowercase()),
+                Some(file_name) => Ok(file_name.to_string()),
                 None => Err(Error::FileNameToStrError),
             })
+            .and_then(|file_name| {
+                let file_name = file_name.strip_suffix(".exe").unwrap_or(&file_name);
+                if acceptable_file_name(file_name, prefix) {
+                    let version = &file_name[prefix.len()..];
+                    let version = match version.rsplit_once('.') {
+                        Some((major_minor, micro))
+                            if major_minor.contains('.') && parse_component(micro).is_ok() =>
+                        {
+                            major_minor
+                        }
+                        _ => version,
+                    };
+                    Ok(Self::from_str(version)?)
+                } else {
+                    Err(Error::PathFileNameError)
+                }
+            })
     }
 
     // XXX from_shebang()?
 
     /// Tests whether this [`ExactVersion`] satisfies the [`RequestedVersion`].
     pub fn supports(&self, requested: RequestedVersion) -> bool {
-        match requested {
-            RequestedVersion::Any => true,
-            RequestedVersion::MajorOnly(major_version) => self.major == major_version,
-            RequestedVersion::Exact(major_version, minor_version) => {
-                self.major == major_version && self.minor == minor_version
-            }
+        requested.matches(self) != VersionMatch::NotAtAll
+    }
+}
+
+/// A discovered Python executable along with what its file name says about
+/// it.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Interpreter {
+    pub implementation: Implementation,
+    pub exact_version: ExactVersion,
+    pub path: PathBuf,
+    /// The full version (e.g. `3.11.4`) the interpreter reports via
+    /// `--version`, which is `None` until [`Interpreter::probe`] finds it.
+    pub full_version: Option<String>,
+}
+
+impl Interpreter {
+    /// Constructs an [`Interpreter`] from the path to an executable named
+    /// like one of `implementation`'s (see
+    /// [`ExactVersion::from_implementation_path`]).
+    pub fn from_path(path: &Path, implementation: Implementation) -> Result<Self> {
+        Ok(Self {
+            implementation,
+            exact_version: ExactVersion::from_implementation_path(path, implementation)?,
+            path: path.to_path_buf(),
+            full_version: None,
+        })
+    }
+
+    /// Determines how well this interpreter satisfies `requested` (see
+    /// [`RequestedVersion::matches`]).
+    pub fn matches(&self, requested: RequestedVersion) -> VersionMatch {
+        requested.matches(&self.exact_version)
+    }
+
+    /// Fills in [`Interpreter::full_version`] by running the interpreter with
+    /// `--version`, unless it already is, and returns it.
+    ///
+    /// The interpreter is only run the first time this is called for its path
+    /// (see [`reported_versions`]).
+    pub fn probe(&mut self) -> Option<&str> {
+        if self.full_version.is_none() {
+            self.full_version = reported_versions(Some(self.path.as_path())).remove(&self.path);
         }
+        self.full_version.as_deref()
     }
 }
 
-fn env_path() -> Vec<PathBuf> {
+fn env_path(env_var: &str) -> Vec<PathBuf> {
     // Would love to have a return type of `impl Iterator<Item = PathBuf>
     // and return just SplitPaths and iter::empty(), but Rust
     // complains about differing return types.
-    match env::var_os("PATH") {
-        Some(path_val) => env::split_paths(&path_val).collect(),
+    match env::var_os(env_var) {
+        Some(path_val) => split_path(&path_val, env::current_dir().ok().as_deref()),
         None => Vec::new(),
     }
 }
 
+/// Splits a `PATH`-style value into its directories.
+///
+/// As with POSIX shells, an empty entry (e.g. from a leading, trailing, or
+/// doubled `:`) is the current directory, `current_dir`; such entries are
+/// dropped if the current directory is unknown.
+fn split_path(path_val: &OsStr, current_dir: Option<&Path>) -> Vec<PathBuf> {
+    env::split_paths(path_val)
+        .filter_map(|entry| {
+            if entry.as_os_str().is_empty() {
+                current_dir.map(Path::to_path_buf)
+            } else {
+                Some(entry)
+            }
+        })
+        .collect()
+}
+
 fn flatten_directories(
     directories: impl IntoIterator<Item = PathBuf>,
 ) -> impl Iterator<Item = PathBuf> {
     directories
         .into_iter()
-        .filter_map(|p| p.read_dir().ok()) // Filter to Ok(ReadDir).
+        .inspect(|p| log::debug!("Searching {}", p.display()))
+        // A directory which can't be read (e.g. one which doesn't exist, or a
+        // stray file) has nothing to offer, but the rest are still searched.
+        .filter_map(|p| match p.read_dir() {
+            Ok(entries) => Some(entries),
+            Err(error) => {
+                log::debug!("Skipping {}: {}", p.display(), error);
+                None
+            }
+        })
         .flatten() // Flatten out `for DirEntry in ReadDir`.
         .filter_map(|e| e.ok()) // Filter to Ok(DirEntry).
+        .filter(|e| !is_dangling_symlink(e))
         .map(|e| e.path()) // Get the PathBuf from the DirEntry.
 }
 
+/// Whether `entry` is a symlink whose target doesn't exist (e.g. left behind
+/// by an uninstalled interpreter), which would fail to execute.
+fn is_dangling_symlink(entry: &fs::DirEntry) -> bool {
+    let dangling = entry
+        .file_type()
+        .is_ok_and(|file_type| file_type.is_symlink())
+        && !entry.path().exists();
+    if dangling {
+        log::debug!("Skipping broken symlink {}", entry.path().display());
+    }
+    dangling
+}
+
 fn all_executables_in_paths(
     paths: impl IntoIterator<Item = PathBuf>,
+    implementation: Implementation,
+    tie_break: TieBreak,
+    priority_paths: &[PathBuf],
+    ignore_case: bool,
 ) -> HashMap<ExactVersion, PathBuf> {
     let mut executables = HashMap::new();
-    paths.into_iter().for_each(|path| {
-        ExactVersion::from_path(&path).map_or((), |version| {
-            executables.entry(version).or_insert(path);
+    discovered_executables(paths, implementation, ignore_case).for_each(|(version, path)| {
+        match executables.entry(version) {
+            Entry::Vacant(entry) => {
+                entry.insert(path);
+            }
+            Entry::Occupied(mut entry) => {
+                if prefers_later(tie_break, priority_paths, entry.get(), &path)
+                    || prefers_name(entry.get(), &path)
+                {
+                    log::debug!(
+                        "Preferring {} over {}",
+                        path.display(),
+                        entry.get().display()
+                    );
+                    entry.insert(path);
+                }
+            }
+        }
+    });
+
+    log::debug!(
+        "Found {} executables: {:?}",
+        implementation,
+        executables.values()
+    );
+    executables
+}
+
+/// The executables of the specified [`Implementation`] among `paths` along with
+/// their versions, with any pyenv shim replaced by the interpreter it runs (see
+/// [`resolve_pyenv_shim`]) so every search compares the same paths.
+///
+/// An executable named for only its major version (e.g. `python3`) is run to
+/// find its minor version; see [`unversioned_exact_version`].
+fn discovered_executables(
+    paths: impl IntoIterator<Item = PathBuf>,
+    implementation: Implementation,
+    ignore_case: bool,
+) -> impl Iterator<Item = (ExactVersion, PathBuf)> {
+    paths.into_iter().filter_map(move |path| {
+        let version = ExactVersion::from_discovered_path(&path, implementation, ignore_case)
+            .ok()
+            .or_else(|| unversioned_exact_version(&path, implementation, ignore_case))?;
+        Some((version, resolve_pyenv_shim(&path, version).unwrap_or(path)))
+    })
+}
+
+/// The version of an executable of the specified [`Implementation`] named for
+/// only its major version (e.g. `python3`), as reported via `--version`, if it
+/// reports that major version.
+fn unversioned_exact_version(
+    path: &Path,
+    implementation: Implementation,
+    ignore_case: bool,
+) -> Option<ExactVersion> {
+    let major = unversioned_major(path, implementation, ignore_case)?;
+    reported_exact_version(path).filter(|version| version.major == major)
+}
+
+/// The major version which the name of an executable of the specified
+/// [`Implementation`] is for, if that's all it names (e.g. `3` for `python3`).
+fn unversioned_major(
+    path: &Path,
+    implementation: Implementation,
+    ignore_case: bool,
+) -> Option<ComponentSize> {
+    let file_name = path.file_name()?.to_str()?;
+    let file_name = if ignore_case {
+        file_name.to_ascii_lowercase()
+    } else {
+        file_name.to_string()
+    };
+    let file_name = file_name.strip_suffix(".exe").unwrap_or(&file_name);
+    parse_component(file_name.strip_prefix(implementation.executable_prefix())?).ok()
+}
+
+/// Whether `later`, found after `earlier` for the same version, should be used
+/// instead of it: the one in the directory listed first in `priority_paths`
+/// (the config file's `priority-paths`) wins, with `tie_break` deciding when
+/// neither directory is listed or both are the same one.
+fn prefers_later(
+    tie_break: TieBreak,
+    priority_paths: &[PathBuf],
+    earlier: &Path,
+    later: &Path,
+) -> bool {
+    let priority = |path: &Path| {
+        path.parent()
+            .and_then(|directory| priority_paths.iter().position(|path| path == directory))
+    };
+    match (priority(earlier), priority(later)) {
+        (Some(earlier_priority), Some(later_priority)) if earlier_priority != later_priority => {
+            later_priority < earlier_priority
+        }
+        (None, Some(_)) => true,
+        (Some(_), None) => false,
+        _ => tie_break.prefers_later(earlier, later),
+    }
+}
+
+/// Whether `later` should be used instead of `earlier`, another name for the
+/// same version in the same directory, because its name includes the minor
+/// version (e.g. `python3.11` over `python3`) or is otherwise shorter (e.g.
+/// `python3.11` over `python3.11.4`), which keeps the choice from depending on
+/// the order the directory is read in.
+fn prefers_name(earlier: &Path, later: &Path) -> bool {
+    let names_minor = |path: &Path| {
+        path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy();
+            name.strip_suffix(".exe").unwrap_or(&name).contains('.')
+        })
+    };
+    earlier.parent() == later.parent()
+        && match (names_minor(earlier), names_minor(later)) {
+            (false, true) => true,
+            (true, false) => false,
+            _ => later.as_os_str().len() < earlier.as_os_str().len(),
+        }
+}
+
+/// Whether executables' names are matched regardless of case (e.g.
+/// `Python3.11`), for interpreters on case-insensitive filesystems such as
+/// Windows drives mounted under WSL, based on the
+/// `PYLAUNCHER_CASE_INSENSITIVE` environment variable or the config file's
+/// `case-insensitive`.
+fn ignore_case(config: &config::Config) -> bool {
+    log::debug!("Checking for PYLAUNCHER_CASE_INSENSITIVE environment variable");
+    env::var_os("PYLAUNCHER_CASE_INSENSITIVE").is_some_and(|value| !value.is_empty())
+        || config.case_insensitive
+}
+
+/// Directories holding the interpreters installed by the operating system.
+static SYSTEM_DIRECTORIES: [&str; 2] = ["/usr/bin", "/bin"];
+
+/// Whether `path` is in one of the [`SYSTEM_DIRECTORIES`].
+fn is_system_executable(path: &Path) -> bool {
+    path.parent().is_some_and(|directory| {
+        SYSTEM_DIRECTORIES
+            .iter()
+            .any(|system| directory == Path::new(system))
+    })
+}
+
+/// The [`TieBreak`] specified by the `PYLAUNCHER_PREFER` environment variable
+/// (defaulting to [`TieBreak::FirstInPath`]).
+fn tie_break() -> TieBreak {
+    log::debug!("Checking for PYLAUNCHER_PREFER environment variable");
+    match env::var("PYLAUNCHER_PREFER") {
+        Ok(value) if value == "system" => TieBreak::PreferSystem,
+        Ok(value) if value.is_empty() || value == "path" => TieBreak::FirstInPath,
+        Ok(value) => {
+            log::debug!("Ignoring unrecognized PYLAUNCHER_PREFER value {:?}", value);
+            TieBreak::FirstInPath
+        }
+        Err(_) => TieBreak::FirstInPath,
+    }
+}
+
+/// Whether `path` is a [pyenv](https://github.com/pyenv/pyenv) shim: a shell
+/// script in a `shims` directory which runs whichever interpreter pyenv
+/// selects.
+pub fn is_pyenv_shim(path: &Path) -> bool {
+    if path.parent().and_then(Path::file_name) != Some(OsStr::new("shims")) {
+        return false;
+    }
+
+    match fs::read(path) {
+        Ok(contents) => contents.windows(5).any(|window| window == b"pyenv"),
+        Err(error) => {
+            log::debug!("Can't read {}: {}", path.display(), error);
+            false
+        }
+    }
+}
+
+/// Resolves a pyenv shim for `version` to the interpreter of the same name
+/// which pyenv installed, i.e. `$PYENV_ROOT/versions/*/bin/{name}`, preferring
+/// the newest micro version. Installs whose directory names another version
+/// are skipped, which matters for a shim named for only its major version
+/// (e.g. `python3`), as every CPython 3 install provides it.
+///
+/// Returns `None` if `path` is not a shim or nothing installed matches it.
+pub fn resolve_pyenv_shim(path: &Path, version: ExactVersion) -> Option<PathBuf> {
+    if !is_pyenv_shim(path) {
+        return None;
+    }
+
+    let file_name = path.file_name()?;
+    let versions_dir = path.parent()?.parent()?.join("versions");
+    log::debug!(
+        "Resolving pyenv shim {} via {}",
+        path.display(),
+        versions_dir.display()
+    );
+    let resolved = fs::read_dir(versions_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|version_dir| version_dir.join("bin").join(file_name).is_file())
+        .filter(|version_dir| {
+            let key = version_dir
+                .file_name()
+                .map(|name| pyenv_version_key(&name.to_string_lossy()))
+                .unwrap_or_default();
+            // Names which don't start with a version (e.g. `pypy3.10-7.3.17`)
+            // can't be told apart.
+            key.len() < 2 || key[..2] == [u32::from(version.major), u32::from(version.minor)]
+        })
+        .max_by_key(|version_dir| {
+            version_dir
+                .file_name()
+                .map(|name| pyenv_version_key(&name.to_string_lossy()))
+        })?
+        .join("bin")
+        .join(file_name);
+    log::debug!("{} resolves to {}", path.display(), resolved.display());
+    Some(resolved)
+}
+
+/// The leading numeric components of a pyenv version directory name (e.g.
+/// `[3, 11, 4]` for `3.11.4`), for comparing versions numerically.
+fn pyenv_version_key(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map_while(|component| component.parse().ok())
+        .collect()
+}
+
+/// Finds all possible CPython executables, newest first.
+pub fn all_executables() -> Vec<Interpreter> {
+    interpreters_for(&[Implementation::CPython])
+}
+
+/// Finds all possible CPython executables in `directories` (in the order
+/// they are searched) instead of those from [`path_entries`].
+pub fn all_executables_from(
+    directories: impl IntoIterator<Item = PathBuf>,
+) -> HashMap<ExactVersion, PathBuf> {
+    all_executables_in_directories(
+        directories,
+        Implementation::CPython,
+        &config::Config::current(),
+    )
+}
+
+/// Finds all possible executables for the specified [`Implementation`] in the
+/// directories of [`path_entries`].
+pub fn all_executables_for(
+    implementation: Implementation,
+    directories: &SearchDirectories,
+) -> HashMap<ExactVersion, PathBuf> {
+    let config = config::Config::current();
+    all_executables_in_directories(
+        path_entries_in(&config, directories)
+            .into_iter()
+            .map(|(directory, _)| directory),
+        implementation,
+        &config,
+    )
+}
+
+/// The executables of the specified [`Implementation`] in the directories of
+/// [`path_entries`] which are named for only their major version (e.g.
+/// `python3`) but don't report any version via `--version`, so they're never
+/// chosen; see [`all_executables_for`] for those which do.
+pub fn unknown_version_executables_for(
+    implementation: Implementation,
+    directories: &SearchDirectories,
+) -> Vec<PathBuf> {
+    let config = config::Config::current();
+    let ignore_case = ignore_case(&config);
+    let mut executables: Vec<PathBuf> = flatten_directories(
+        path_entries_in(&config, directories)
+            .into_iter()
+            .map(|(directory, _)| directory),
+    )
+    .filter(|path| {
+        unversioned_major(path, implementation, ignore_case).is_some()
+            && reported_versions(Some(path.as_path())).is_empty()
+    })
+    .collect();
+    executables.dedup();
+    executables
+}
+
+/// Finds all possible executables of every [`Implementation`], newest first
+/// (with CPython ahead of PyPy for the same version).
+pub fn all_interpreters() -> Vec<Interpreter> {
+    interpreters_for(&[Implementation::CPython, Implementation::PyPy])
+}
+
+/// Finds all possible executables of the `implementations`, newest first
+/// (with CPython ahead of PyPy for the same version).
+fn interpreters_for(implementations: &[Implementation]) -> Vec<Interpreter> {
+    let mut interpreters: Vec<Interpreter> = implementations
+        .iter()
+        .flat_map(|&implementation| {
+            all_executables_for(implementation, &SearchDirectories::default())
+                .into_iter()
+                .map(move |(exact_version, path)| Interpreter {
+                    implementation,
+                    exact_version,
+                    path,
+                    full_version: None,
+                })
         })
+        .collect();
+    interpreters.sort_by(|a, b| {
+        b.exact_version
+            .cmp(&a.exact_version)
+            .then(a.implementation.cmp(&b.implementation))
     });
+    interpreters
+}
+
+fn all_executables_in_directories(
+    directories: impl IntoIterator<Item = PathBuf>,
+    implementation: Implementation,
+    config: &config::Config,
+) -> HashMap<ExactVersion, PathBuf> {
+    all_executables_in_paths(
+        flatten_directories(directories),
+        implementation,
+        tie_break(),
+        &config.priority_paths,
+        ignore_case(config),
+    )
+}
+
+/// Like [`all_executables_for`], but also records the [`Source`] of the
+/// directory each executable was found in.
+pub fn all_sourced_executables_for(
+    implementation: Implementation,
+    directories: &SearchDirectories,
+) -> HashMap<ExactVersion, (PathBuf, Source)> {
+    let mut executables = HashMap::new();
+    let tie_break = tie_break();
+    let config = config::Config::current();
+    let priority_paths = &config.priority_paths;
+    let ignore_case = ignore_case(&config);
+    // Searching one directory at a time keeps track of where each executable
+    // came from while earlier directories still take precedence.
+    for (directory, source) in path_entries_in(&config, directories) {
+        let found = all_executables_in_paths(
+            flatten_directories(Some(directory)),
+            implementation,
+            tie_break,
+            priority_paths,
+            ignore_case,
+        );
+        for (version, path) in found {
+            match executables.entry(version) {
+                Entry::Vacant(entry) => {
+                    entry.insert((path, source));
+                }
+                Entry::Occupied(mut entry) => {
+                    if prefers_later(tie_break, priority_paths, &entry.get().0, &path) {
+                        entry.insert((path, source));
+                    }
+                }
+            }
+        }
+    }
+    executables
+}
 
-    log::debug!("Found executables: {:?}", executables.values());
+/// Every executable of the specified [`Implementation`] along with the
+/// [`Source`] of its directory, including those shadowed by one of the same
+/// version found earlier, in the order their directories are searched (and by
+/// file name within a directory).
+pub fn all_sourced_executables_in_search_order(
+    implementation: Implementation,
+    directories: &SearchDirectories,
+) -> Vec<(ExactVersion, PathBuf, Source)> {
+    let mut executables = Vec::new();
+    let config = config::Config::current();
+    let ignore_case = ignore_case(&config);
+    for (directory, source) in path_entries_in(&config, directories) {
+        let mut found: Vec<(ExactVersion, PathBuf)> = discovered_executables(
+            flatten_directories(Some(directory)),
+            implementation,
+            ignore_case,
+        )
+        .collect();
+        found.sort_by(|(_, a), (_, b)| a.file_name().cmp(&b.file_name()));
+        executables.extend(
+            found
+                .into_iter()
+                .map(|(version, path)| (version, path, source)),
+        );
+    }
+    executables
+}
+
+/// Every executable of the specified [`Implementation`] which satisfies
+/// `requested`, including those shadowed by an earlier directory, in the order
+/// their directories are searched (and from newest to oldest within a
+/// directory).
+pub fn all_matching_executables_for(
+    implementation: Implementation,
+    requested: RequestedVersion,
+    directories: &SearchDirectories,
+) -> Vec<PathBuf> {
+    let mut executables = Vec::new();
+    let config = config::Config::current();
+    let ignore_case = ignore_case(&config);
+    for (directory, _) in path_entries_in(&config, directories) {
+        let mut found: Vec<(ExactVersion, PathBuf)> = discovered_executables(
+            flatten_directories(Some(directory)),
+            implementation,
+            ignore_case,
+        )
+        .filter(|(version, _)| version.supports(requested))
+        .collect();
+        found.sort_by(|(a, _), (b, _)| b.cmp(a));
+        executables.extend(found.into_iter().map(|(_, path)| path));
+    }
     executables
 }
 
-/// Finds all possible Python executables.
-pub fn all_executables() -> HashMap<ExactVersion, PathBuf> {
-    log::info!("Checking PATH environment variable");
-    let path_entries = env_path();
-    log::debug!("PATH: {:?}", path_entries);
-    let paths = flatten_directories(path_entries);
-    all_executables_in_paths(paths)
+/// The spellings of the home directory expanded by [`expand_home`].
+static HOME_PREFIXES: [&str; 3] = ["~", "$HOME", "${HOME}"];
+
+/// Replaces a leading `~`, `$HOME`, or `${HOME}` in `path` with the `HOME`
+/// environment variable, as a shell would; see [`expand_home_in`].
+pub fn expand_home(path: &Path) -> PathBuf {
+    expand_home_in(path, env::var_os("HOME").as_deref())
+}
+
+/// Replaces a leading `~`, `$HOME`, or `${HOME}` component of `path` with
+/// `home`. The path is returned unchanged if it has no such component (e.g.
+/// `~user/bin` or `$HOMEDIR/bin`) or `home` is unset or empty.
+fn expand_home_in(path: &Path, home: Option<&OsStr>) -> PathBuf {
+    let home = match home {
+        Some(home) if !home.is_empty() => home,
+        _ => return path.to_path_buf(),
+    };
+    let mut components = path.components();
+    match components.next() {
+        Some(first)
+            if HOME_PREFIXES
+                .iter()
+                .any(|prefix| first.as_os_str() == *prefix) =>
+        {
+            let mut expanded = PathBuf::from(home);
+            // Joining an empty path would add a trailing separator.
+            if components.clone().next().is_some() {
+                expanded.push(components.as_path());
+            }
+            log::debug!("Expanded {} to {}", path.display(), expanded.display());
+            expanded
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Directories to search before (`prepend`) and after (`append`) `PATH`, as
+/// given by the `--prepend-path` and `--append-path` flags; the default is
+/// none.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchDirectories {
+    prepend: Vec<PathBuf>,
+    append: Vec<PathBuf>,
+}
+
+impl SearchDirectories {
+    /// Expands a leading `~` or `$HOME` in each directory (see
+    /// [`expand_home`]), skipping any which don't exist.
+    pub fn new(prepend: &[PathBuf], append: &[PathBuf]) -> Self {
+        let existing = |directories: &[PathBuf]| -> Vec<PathBuf> {
+            directories
+                .iter()
+                .map(|directory| expand_home(directory))
+                .filter(|directory| {
+                    let is_dir = directory.is_dir();
+                    if !is_dir {
+                        log::debug!("Skipping nonexistent directory {}", directory.display());
+                    }
+                    is_dir
+                })
+                .collect()
+        };
+        Self {
+            prepend: existing(prepend),
+            append: existing(append),
+        }
+    }
+}
+
+/// The directories searched for executables along with where they came from,
+/// in order: those `directories` prepends, `PATH`, those `directories`
+/// appends, `PYLAUNCHER_PATH`, then the config file's `extra-paths`.
+pub fn path_entries(directories: &SearchDirectories) -> Vec<(PathBuf, Source)> {
+    path_entries_in(&config::Config::current(), directories)
+}
+
+/// Like [`path_entries`], with the `extra-paths` of `config`.
+fn path_entries_in(
+    config: &config::Config,
+    directories: &SearchDirectories,
+) -> Vec<(PathBuf, Source)> {
+    let SearchDirectories { prepend, append } = directories;
+    if !prepend.is_empty() {
+        log::debug!("--prepend-path: {:?}", prepend);
+    }
+    let mut path_entries: Vec<_> = prepend
+        .iter()
+        .map(|directory| (directory.clone(), Source::PrependPath))
+        .collect();
+    log::debug!("Checking PATH environment variable");
+    // Unlike an empty `PATH`, which is the current directory, an unset one
+    // has no directories at all.
+    match env::var_os("PATH") {
+        Some(path) => path_entries.extend(path_entries_from(&path)),
+        None => log::debug!("PATH is unset"),
+    }
+    if !append.is_empty() {
+        log::debug!("--append-path: {:?}", append);
+        path_entries.extend(
+            append
+                .iter()
+                .map(|directory| (directory.clone(), Source::AppendPath)),
+        );
+    }
+    log::debug!("Checking PYLAUNCHER_PATH environment variable");
+    let launcher_paths = env_path("PYLAUNCHER_PATH");
+    if !launcher_paths.is_empty() {
+        log::debug!("PYLAUNCHER_PATH: {:?}", launcher_paths);
+        path_entries.extend(
+            launcher_paths
+                .into_iter()
+                .map(|directory| (directory, Source::LauncherPath)),
+        );
+    }
+    if !config.extra_paths.is_empty() {
+        log::debug!("Extra paths from config: {:?}", config.extra_paths);
+        path_entries.extend(
+            config
+                .extra_paths
+                .iter()
+                .map(|directory| (directory.clone(), Source::ExtraPaths)),
+        );
+    }
+    dedup_directories(path_entries)
+}
+
+/// The directories in `path_var`, a value like that of the `PATH` environment
+/// variable, in the order they are searched for executables.
+///
+/// An empty entry is the current directory and only the first of several
+/// entries for the same directory is kept.
+pub fn path_entries_from(path_var: &OsStr) -> Vec<(PathBuf, Source)> {
+    let path = split_path(path_var, env::current_dir().ok().as_deref());
+    log::debug!("PATH: {:?}", path);
+    dedup_directories(
+        path.into_iter()
+            .map(|directory| (directory, Source::Path))
+            .collect(),
+    )
+}
+
+/// Removes directories which are the same as an earlier one (once symlinks and
+/// the like are resolved), so the first occurrence keeps its precedence.
+fn dedup_directories<T>(directories: Vec<(PathBuf, T)>) -> Vec<(PathBuf, T)> {
+    let mut seen = HashSet::new();
+    directories
+        .into_iter()
+        .filter(|(directory, _)| {
+            let canonical = directory
+                .canonicalize()
+                .unwrap_or_else(|_| directory.clone());
+            let unseen = seen.insert(canonical);
+            if !unseen {
+                log::debug!("Skipping duplicate directory {}", directory.display());
+            }
+            unseen
+        })
+        .collect()
 }
 
 fn find_executable_in_hashmap(
     requested: RequestedVersion,
     found_executables: &HashMap<ExactVersion, PathBuf>,
+    selection: Selection,
+) -> Option<PathBuf> {
+    let mut matching: Vec<(&ExactVersion, &PathBuf)> = found_executables
+        .iter()
+        .filter(|pair| requested.matches(pair.0) != VersionMatch::NotAtAll)
+        .collect();
+    // Most preferred first.
+    match selection {
+        Selection::Newest => matching.sort_unstable_by(|a, b| b.cmp(a)),
+        Selection::Oldest => matching.sort_unstable(),
+    }
+    let preferred = matching.first()?.1;
+    if requested.matches(matching[0].0) == VersionMatch::Exactly || prereleases_allowed() {
+        return Some(preferred.clone());
+    }
+
+    // Prefer a final release when choosing among versions, but fall back to a
+    // pre-release if that's all there is. Only executables positively
+    // identified as pre-releases are passed over; one whose version can't be
+    // determined is treated as a final release. Probing means running each
+    // executable, so only as many are probed as it takes to reach one.
+    let mut prerelease = None;
+    for (_, path) in matching {
+        match release_level(path) {
+            ReleaseLevel::Final | ReleaseLevel::Unknown => return Some(path.clone()),
+            ReleaseLevel::Prerelease if prerelease.is_none() => prerelease = Some(path),
+            ReleaseLevel::Prerelease => {}
+        }
+    }
+    prerelease.cloned()
+}
+
+/// Whether pre-release executables may be chosen as the highest version,
+/// based on the `PYLAUNCHER_ALLOW_PRERELEASES` environment variable.
+fn prereleases_allowed() -> bool {
+    log::debug!("Checking for PYLAUNCHER_ALLOW_PRERELEASES environment variable");
+    env::var_os("PYLAUNCHER_ALLOW_PRERELEASES").is_some_and(|value| !value.is_empty())
+}
+
+/// How long an executable is given to report its version before it's
+/// considered hung.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The kind of release an executable is, according to its `--version` output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ReleaseLevel {
+    Final,
+    /// An alpha, beta, or release candidate.
+    Prerelease,
+    /// The executable ran but its version couldn't be determined (e.g. it hung
+    /// or printed something unexpected).
+    Unknown,
+}
+
+/// Determines the [`ReleaseLevel`] of the executable by running it.
+///
+/// An executable which can't be run at all is assumed to be a final release so
+/// that it's left for execution to report the problem.
+fn release_level(executable: &Path) -> ReleaseLevel {
+    log::debug!("Checking if {} is a pre-release", executable.display());
+    match probe_version_output(executable, PROBE_TIMEOUT) {
+        Ok(Some(output)) => release_level_from_output(&output),
+        Ok(None) => {
+            log::debug!(
+                "{} did not report its version within {:?}",
+                executable.display(),
+                PROBE_TIMEOUT
+            );
+            ReleaseLevel::Unknown
+        }
+        Err(error) => {
+            log::debug!("Can't run {}: {}", executable.display(), error);
+            ReleaseLevel::Final
+        }
+    }
+}
+
+/// Runs the executable with `--version`, returning its stdout and stderr
+/// combined (Python 2 writes its version to stderr), or `None` if it didn't
+/// exit within `timeout`.
+fn probe_version_output(executable: &Path, timeout: Duration) -> io::Result<Option<String>> {
+    let mut child = Command::new(executable)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let deadline = Instant::now() + timeout;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let output = child.wait_with_output()?;
+    let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
+    combined.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(Some(combined))
+}
+
+/// The full version (e.g. `3.11.4`) reported via `--version` by each of the
+/// `executables` which reports one, keyed by path.
+///
+/// The executables are probed in parallel as each one has to be run, and the
+/// results are cached for each executable.
+pub fn reported_versions<'a>(
+    executables: impl IntoIterator<Item = &'a Path>,
+) -> HashMap<PathBuf, String> {
+    static REPORTED_VERSIONS: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+    let cache = REPORTED_VERSIONS.get_or_init(Default::default);
+
+    let executables: HashSet<&Path> = executables.into_iter().collect();
+    thread::scope(|scope| {
+        for executable in executables.iter() {
+            if cache.lock().unwrap().contains_key(*executable) {
+                continue;
+            }
+            scope.spawn(move || {
+                log::debug!("Checking the full version of {}", executable.display());
+                let version = probe_version_output(executable, PROBE_TIMEOUT)
+                    .ok()
+                    .flatten()
+                    .and_then(|output| parse_version_output(&output).map(String::from));
+                log::debug!("{} reports version {:?}", executable.display(), version);
+                cache
+                    .lock()
+                    .unwrap()
+                    .insert(executable.to_path_buf(), version);
+            });
+        }
+    });
+
+    let cache = cache.lock().unwrap();
+    executables
+        .into_iter()
+        .filter_map(|executable| {
+            let version = cache.get(executable)?.clone()?;
+            Some((executable.to_path_buf(), version))
+        })
+        .collect()
+}
+
+/// Finds the version in `--version` output (e.g. `3.14.0b2` in
+/// `Python 3.14.0b2`).
+fn parse_version_output(version_output: &str) -> Option<&str> {
+    let mut words = version_output.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "Python" {
+            return words
+                .next()
+                .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()));
+        }
+    }
+    None
+}
+
+/// The major and minor version of a full version (e.g. `3.11` of `3.11.4`).
+fn exact_version_from_full(full_version: &str) -> Option<ExactVersion> {
+    let mut components = full_version.splitn(3, '.');
+    let major = parse_component(components.next()?).ok()?;
+    let minor = parse_component(components.next()?).ok()?;
+    Some(ExactVersion::new(major, minor))
+}
+
+/// The major and minor version which `executable` reports via `--version`,
+/// for an executable whose name doesn't say (e.g. a virtual environment's
+/// `python`); see [`reported_versions`].
+pub fn reported_exact_version(executable: &Path) -> Option<ExactVersion> {
+    exact_version_from_full(&reported_versions(Some(executable)).remove(executable)?)
+}
+
+/// Whether an unversioned executable (e.g. `python3`) should be preferred over
+/// the newest `pythonX.Y` when only a major version is requested, based on the
+/// `PYLAUNCHER_PREFER_UNVERSIONED` environment variable.
+fn unversioned_preferred() -> bool {
+    log::debug!("Checking for PYLAUNCHER_PREFER_UNVERSIONED environment variable");
+    env::var_os("PYLAUNCHER_PREFER_UNVERSIONED").is_some_and(|value| !value.is_empty())
+}
+
+/// Finds the first unversioned executable for `major` (e.g. `python3`, which
+/// a distribution typically points at its chosen default) whose `--version`
+/// reports that major version.
+fn unversioned_executable(
+    implementation: Implementation,
+    major: ComponentSize,
+    directories: &SearchDirectories,
+) -> Option<PathBuf> {
+    let file_name = format!("{}{}", implementation.executable_prefix(), major);
+    path_entries(directories)
+        .into_iter()
+        .find_map(|(directory, _)| {
+            let executable = directory.join(&file_name);
+            if !executable.is_file() {
+                return None;
+            }
+            reported_exact_version(&executable)
+                .filter(|version| version.major == major)
+                .map(|version| {
+                    log::info!("Using {} as Python {}", executable.display(), version);
+                    executable
+                })
+        })
+}
+
+/// The unversioned executable to use for `requested` when
+/// [`unversioned_preferred`], which only applies when only a major version is
+/// requested and the newest executable would otherwise be chosen.
+fn preferred_unversioned_executable(
+    implementation: Implementation,
+    requested: RequestedVersion,
+    selection: Selection,
+    directories: &SearchDirectories,
+) -> Option<PathBuf> {
+    match requested {
+        RequestedVersion::MajorOnly(major)
+            if selection == Selection::Newest && unversioned_preferred() =>
+        {
+            unversioned_executable(implementation, major, directories)
+        }
+        _ => None,
+    }
+}
+
+/// The unversioned executable to use for `requested` when no `pythonX.Y`
+/// satisfies it, as some systems only provide e.g. `python3`; like
+/// [`preferred_unversioned_executable`], this only applies when only a major
+/// version is requested.
+fn fallback_unversioned_executable(
+    implementation: Implementation,
+    requested: RequestedVersion,
+    directories: &SearchDirectories,
 ) -> Option<PathBuf> {
-    let mut iter = found_executables.iter();
     match requested {
-        RequestedVersion::Any => iter.max(),
-        RequestedVersion::MajorOnly(_) => iter.filter(|pair| pair.0.supports(requested)).max(),
-        RequestedVersion::Exact(_, _) => iter.find(|pair| pair.0.supports(requested)),
+        RequestedVersion::MajorOnly(major) => {
+            log::debug!(
+                "No {}{}.Y found; checking for {}{}",
+                implementation.executable_prefix(),
+                major,
+                implementation.executable_prefix(),
+                major
+            );
+            unversioned_executable(implementation, major, directories)
+        }
+        _ => None,
+    }
+}
+
+/// The [`ReleaseLevel`] reported by `--version` output.
+fn release_level_from_output(version_output: &str) -> ReleaseLevel {
+    match parse_version_output(version_output) {
+        Some(version) => {
+            log::debug!("Found version: {}", version);
+            if version.contains(|c: char| c.is_ascii_alphabetic()) {
+                ReleaseLevel::Prerelease
+            } else {
+                ReleaseLevel::Final
+            }
+        }
+        None => {
+            log::debug!("No version found in {:?}", version_output);
+            ReleaseLevel::Unknown
+        }
     }
-    .map(|pair| pair.1.clone())
 }
 
-/// Attempts to find an executable that satisfies a specified [`RequestedVersion`].
+/// Attempts to find a CPython executable that satisfies a specified [`RequestedVersion`].
 pub fn find_executable(requested: RequestedVersion) -> Option<PathBuf> {
-    let found_executables = all_executables();
-    find_executable_in_hashmap(requested, &found_executables)
+    find_executable_for(
+        Implementation::CPython,
+        requested,
+        &SearchDirectories::default(),
+    )
+}
+
+/// Attempts to find an executable of the specified [`Implementation`] that
+/// satisfies a specified [`RequestedVersion`], also searching `directories`.
+pub fn find_executable_for(
+    implementation: Implementation,
+    requested: RequestedVersion,
+    directories: &SearchDirectories,
+) -> Option<PathBuf> {
+    preferred_unversioned_executable(implementation, requested, Selection::Newest, directories)
+        .or_else(|| {
+            let found_executables = all_executables_for(implementation, directories);
+            find_executable_in_hashmap(requested, &found_executables, Selection::Newest)
+        })
+        .or_else(|| fallback_unversioned_executable(implementation, requested, directories))
+}
+
+/// The base delay between discovery attempts; multiplied by the attempt number.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+fn find_executable_retrying(
+    requested: RequestedVersion,
+    selection: Selection,
+    retries: u32,
+    backoff: Duration,
+    mut scan: impl FnMut() -> HashMap<ExactVersion, PathBuf>,
+) -> Option<PathBuf> {
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            log::debug!("Retrying discovery (attempt {} of {})", attempt, retries);
+            thread::sleep(backoff * attempt);
+        }
+        let found_executables = scan();
+        if let Some(executable) =
+            find_executable_in_hashmap(requested, &found_executables, selection)
+        {
+            return Some(executable);
+        }
+    }
+
+    None
+}
+
+/// Like [`find_executable_for`], but re-scans up to `retries` more times (with
+/// a short, increasing delay) if no executable is found.
+///
+/// Useful when interpreters may still be in the middle of being installed.
+pub fn find_executable_with_retries(
+    implementation: Implementation,
+    requested: RequestedVersion,
+    selection: Selection,
+    retries: u32,
+    directories: &SearchDirectories,
+) -> Option<PathBuf> {
+    preferred_unversioned_executable(implementation, requested, selection, directories)
+        .or_else(|| {
+            find_executable_retrying(requested, selection, retries, RETRY_BACKOFF, || {
+                all_executables_for(implementation, directories)
+            })
+        })
+        .or_else(|| fallback_unversioned_executable(implementation, requested, directories))
 }
 
 #[cfg(test)]
+#[allow(clippy::unused_unit)] // `test_case` generates a trailing `()` for tests without a return value.
 mod tests {
     use super::*;
 
-    use std::cmp::Ordering;
-
     use test_case::test_case;
 
+    #[test_case("~" => "/home/user" ; "tilde")]
+    #[test_case("~/pys/3.12/bin" => "/home/user/pys/3.12/bin" ; "tilde directory")]
+    #[test_case("$HOME/bin" => "/home/user/bin" ; "HOME")]
+    #[test_case("${HOME}/bin" => "/home/user/bin" ; "HOME in braces")]
+    #[test_case("/opt/~/bin" => "/opt/~/bin" ; "tilde later in the path")]
+    #[test_case("~user/bin" => "~user/bin" ; "another user's home")]
+    #[test_case("$HOMEDIR/bin" => "$HOMEDIR/bin" ; "another variable")]
+    #[test_case("bin" => "bin" ; "relative path")]
+    fn expand_home_in_tests(path: &str) -> String {
+        expand_home_in(Path::new(path), Some(OsStr::new("/home/user")))
+            .display()
+            .to_string()
+    }
+
+    #[test_case(None ; "unset")]
+    #[test_case(Some("") ; "empty")]
+    fn expand_home_in_without_home_tests(home: Option<&str>) {
+        assert_eq!(
+            expand_home_in(Path::new("~/bin"), home.map(OsStr::new)),
+            PathBuf::from("~/bin")
+        );
+    }
+
     #[test_case(RequestedVersion::Any => "Python" ; "Any")]
     #[test_case(RequestedVersion::MajorOnly(3) => "Python 3" ; "Major")]
     #[test_case(RequestedVersion::Exact(3, 8) => "Python 3.8" ; "Exact/major.minor")]
+    #[test_case(RequestedVersion::AtLeast(3, 8) => "Python 3.8+" ; "AtLeast/major.minor+")]
     fn requestedversion_to_string_tests(requested_version: RequestedVersion) -> String {
         requested_version.to_string()
     }
 
-    #[test_case(".3" => matches Err(Error::ParseVersionComponentError(_)) ; "missing major version is an error")]
-    #[test_case("3." => matches Err(Error::ParseVersionComponentError(_)) ; "missing minor version is an error")]
-    #[test_case("h" => matches Err(Error::ParseVersionComponentError(_)) ; "non-number, non-emptry string is an error")]
-    #[test_case("3.b" => matches Err(Error::ParseVersionComponentError(_)) ; "major.minor where minor is a non-number is an error")]
-    #[test_case("a.7" => matches Err(Error::ParseVersionComponentError(_)) ; "major.minor where major is a non-number is an error")]
+    #[test_case(RequestedVersion::Any => "*" ; "Any")]
+    #[test_case(RequestedVersion::MajorOnly(3) => "3" ; "Major")]
+    #[test_case(RequestedVersion::Exact(3, 8) => "3.8" ; "Exact/major.minor")]
+    #[test_case(RequestedVersion::AtLeast(3, 8) => "3.8+" ; "AtLeast/major.minor+")]
+    fn requestedversion_alternate_format_tests(requested_version: RequestedVersion) -> String {
+        format!("{:#}", requested_version)
+    }
+
+    #[test_case(".3" => Err(ParseVersionError::Empty) ; "missing major version is an error")]
+    #[test_case("3." => Err(ParseVersionError::Empty) ; "missing minor version is an error")]
+    #[test_case("h" => Err(ParseVersionError::NonNumeric("h".to_string())) ; "non-number, non-emptry string is an error")]
+    #[test_case("3.b" => Err(ParseVersionError::NonNumeric("b".to_string())) ; "major.minor where minor is a non-number is an error")]
+    #[test_case("a.7" => Err(ParseVersionError::NonNumeric("a".to_string())) ; "major.minor where major is a non-number is an error")]
     #[test_case("" => Ok(RequestedVersion::Any) ; "empty string is Any")]
     #[test_case("3" => Ok(RequestedVersion::MajorOnly(3)) ; "major-only version")]
     #[test_case("3.8" => Ok(RequestedVersion::Exact(3, 8)) ; "major.minor")]
     #[test_case("42.13" => Ok(RequestedVersion::Exact(42, 13)) ; "double digit version components")]
-    #[test_case("3.6.5" => matches Err(Error::ParseVersionComponentError(_)) ; "specifying a micro version is an error")]
-    fn requestedversion_from_str_tests(version_str: &str) -> Result<RequestedVersion> {
+    #[test_case("3.6.5" => Err(ParseVersionError::TooManyComponents) ; "specifying a micro version is an error")]
+    #[test_case("3.9abc" => Err(ParseVersionError::NonNumeric("9abc".to_string())) ; "trailing letters after minor version is an error")]
+    #[test_case("3abc" => Err(ParseVersionError::NonNumeric("3abc".to_string())) ; "trailing letters after major-only version is an error")]
+    #[test_case("3.x" => Err(ParseVersionError::NonNumeric("x".to_string())) ; "wildcard minor version is an error")]
+    #[test_case("3.9 " => Err(ParseVersionError::NonNumeric("9 ".to_string())) ; "trailing whitespace is an error")]
+    #[test_case("3.9-dev" => Err(ParseVersionError::NonNumeric("9-dev".to_string())) ; "trailing suffix is an error")]
+    #[test_case("3.9+abc" => Err(ParseVersionError::NonNumeric("9+abc".to_string())) ; "garbage after plus is an error")]
+    #[test_case("03" => Ok(RequestedVersion::MajorOnly(3)) ; "leading zero in major-only version is ignored")]
+    #[test_case("03.006" => Ok(RequestedVersion::Exact(3, 6)) ; "leading zeros are ignored")]
+    #[test_case("3..6" => Err(ParseVersionError::TooManyComponents) ; "consecutive dots are an error")]
+    #[test_case("3.12-64" => Ok(RequestedVersion::Exact(3, 12)) ; "64-bit architecture suffix is ignored")]
+    #[test_case("3-32" => Ok(RequestedVersion::MajorOnly(3)) ; "32-bit architecture suffix is ignored")]
+    #[test_case("3.8+-64" => Ok(RequestedVersion::AtLeast(3, 8)) ; "architecture suffix after plus is ignored")]
+    #[test_case("-64" => Err(ParseVersionError::NonNumeric("-64".to_string())) ; "architecture suffix alone is an error")]
+    #[test_case("3.12-16" => Err(ParseVersionError::NonNumeric("12-16".to_string())) ; "unknown architecture suffix is an error")]
+    #[test_case("3.12-64-64" => Err(ParseVersionError::NonNumeric("12-64".to_string())) ; "only one architecture suffix is ignored")]
+    #[test_case("." => Err(ParseVersionError::Empty) ; "dot alone is an error")]
+    #[test_case("+3" => Err(ParseVersionError::NonNumeric("+3".to_string())) ; "sign on major-only version is an error")]
+    #[test_case("+3.6" => Err(ParseVersionError::NonNumeric("+3".to_string())) ; "sign on major version is an error")]
+    #[test_case("3.+6" => Err(ParseVersionError::NonNumeric("+6".to_string())) ; "sign on minor version is an error")]
+    #[test_case("3.-6" => Err(ParseVersionError::NonNumeric("-6".to_string())) ; "negative minor version is an error")]
+    #[test_case(" 3.6" => Err(ParseVersionError::NonNumeric(" 3".to_string())) ; "whitespace is an error")]
+    #[test_case("65536" => Err(ParseVersionError::TooLarge("65536".to_string())) ; "overflowing component is an error")]
+    #[test_case("3.8+" => Ok(RequestedVersion::AtLeast(3, 8)) ; "major.minor+")]
+    #[test_case("3.8++" => Err(ParseVersionError::NonNumeric("8+".to_string())) ; "doubled plus is an error")]
+    #[test_case("3+" => Err(ParseVersionError::DotMissing) ; "major-only minimum is an error")]
+    #[test_case("+" => Err(ParseVersionError::DotMissing) ; "plus alone is an error")]
+    fn requestedversion_from_str_tests(
+        version_str: &str,
+    ) -> std::result::Result<RequestedVersion, ParseVersionError> {
         RequestedVersion::from_str(version_str)
     }
 
+    #[test_case("3.11" => Ok(RequestedVersion::Exact(3, 11)) ; "major.minor")]
+    #[test_case("v3.11" => Ok(RequestedVersion::Exact(3, 11)) ; "v prefix")]
+    #[test_case("python3.11" => Ok(RequestedVersion::Exact(3, 11)) ; "python prefix")]
+    #[test_case("cpython3.11" => Ok(RequestedVersion::Exact(3, 11)) ; "cpython prefix")]
+    #[test_case(" Python3 " => Ok(RequestedVersion::MajorOnly(3)) ; "case and whitespace ignored")]
+    #[test_case("python" => Ok(RequestedVersion::Any) ; "prefix alone is Any")]
+    #[test_case("vpython3.11" => Err(ParseVersionError::NonNumeric("python3".to_string())) ; "only one prefix stripped")]
+    #[test_case("pypy3.11" => Err(ParseVersionError::NonNumeric("pypy3".to_string())) ; "other prefixes are an error")]
+    fn requestedversion_from_user_str_tests(
+        version_str: &str,
+    ) -> std::result::Result<RequestedVersion, ParseVersionError> {
+        RequestedVersion::from_user_str(version_str)
+    }
+
     #[test_case(RequestedVersion::Any => Some("PY_PYTHON".to_string()) ; "Any is PY_PYTHON")]
     #[test_case(RequestedVersion::MajorOnly(3) => Some("PY_PYTHON3".to_string()) ; "major-only is PY_PYTHON{major}")]
     #[test_case(RequestedVersion::MajorOnly(42) => Some("PY_PYTHON42".to_string()) ; "double-digit major component")]
     #[test_case(RequestedVersion::Exact(42, 13) => None ; "exact/major.minor has no environment variable")]
+    #[test_case(RequestedVersion::AtLeast(3, 8) => None ; "minimum version has no environment variable")]
     fn requstedversion_env_var_tests(requested_version: RequestedVersion) -> Option<String> {
         requested_version.env_var()
     }
 
+    #[test]
+    fn requestedversion_comparisons() {
+        let any = RequestedVersion::Any;
+        let py2 = RequestedVersion::MajorOnly(2);
+        let py2_7 = RequestedVersion::Exact(2, 7);
+        let py3 = RequestedVersion::MajorOnly(3);
+        let py3_0 = RequestedVersion::Exact(3, 0);
+        let py3_9 = RequestedVersion::Exact(3, 9);
+        let py3_10 = RequestedVersion::Exact(3, 10);
+        let py3_9_plus = RequestedVersion::AtLeast(3, 9);
+
+        // ==
+        assert_eq!(any.cmp(&any), Ordering::Equal);
+        assert_eq!(py3.cmp(&py3), Ordering::Equal);
+        assert_eq!(py3_9.cmp(&py3_9), Ordering::Equal);
+        // Any sorts lowest.
+        assert_eq!(any.cmp(&py2), Ordering::Less);
+        assert_eq!(any.cmp(&py2_7), Ordering::Less);
+        assert_eq!(py3_10.cmp(&any), Ordering::Greater);
+        // Major-only sorts below the same major version's exact versions.
+        assert_eq!(py3.cmp(&py3_0), Ordering::Less);
+        assert_eq!(py3_9.cmp(&py3), Ordering::Greater);
+        // Major-only sorts above older major versions' exact versions.
+        assert_eq!(py2_7.cmp(&py3), Ordering::Less);
+        assert_eq!(py3.cmp(&py2_7), Ordering::Greater);
+        assert_eq!(py2.cmp(&py3), Ordering::Less);
+        // Exact versions compare numerically.
+        assert_eq!(py3_9.cmp(&py3_10), Ordering::Less);
+        assert_eq!(py2_7.cmp(&py3_0), Ordering::Less);
+        // Minimum versions sort just above the same exact version.
+        assert_eq!(py3_9.cmp(&py3_9_plus), Ordering::Less);
+        assert_eq!(py3_9_plus.cmp(&py3_10), Ordering::Less);
+
+        let mut versions = vec![py3_10, py3, py3_9_plus, any, py2_7, py3_9, py2];
+        versions.sort();
+        assert_eq!(
+            versions,
+            vec![any, py2, py2_7, py3, py3_9, py3_9_plus, py3_10]
+        );
+    }
+
     #[test]
     fn test_requestedversion_from_exactversion() {
         assert_eq!(
@@ -342,6 +1874,28 @@ mod tests {
         );
     }
 
+    #[test_case("/python2.7" ; "single digits")]
+    #[test_case("/python3.10" ; "double-digit minor version")]
+    fn exactversion_requestedversion_interop(path: &str) {
+        let found = ExactVersion::from_path(&PathBuf::from(path)).unwrap();
+        let requested = RequestedVersion::from(found);
+
+        // A discovered version can be requested exactly ...
+        assert_eq!(requested.matches(&found), VersionMatch::Exactly);
+        assert!(found.supports(requested));
+        // ... renders the same as a request for it ...
+        assert_eq!(format!("{:#}", requested), found.to_string());
+        assert_eq!(
+            RequestedVersion::from_str(&found.to_string()),
+            Ok(requested)
+        );
+        // ... and sorts consistently with it.
+        let older = ExactVersion::new(found.major, found.minor - 1);
+        assert!(older < found);
+        assert!(RequestedVersion::from(older) < requested);
+        assert_eq!(requested.matches(&older), VersionMatch::NotAtAll);
+    }
+
     #[test] // For some reason, having Ordering breaks test-case 1.0.0.
     fn exactversion_comparisons() {
         let py2_7 = ExactVersion { major: 2, minor: 7 };
@@ -372,26 +1926,109 @@ mod tests {
         ExactVersion { major, minor }.to_string()
     }
 
-    #[test_case("" => Err(Error::DotMissing) ; "empty string is an error")]
-    #[test_case("3" => Err(Error::DotMissing) ; "major-only version is an error")]
-    #[test_case(".7" => matches Err(Error::ParseVersionComponentError(_)) ; "missing major version is an error")]
-    #[test_case("3." => matches Err(Error::ParseVersionComponentError(_)) ; "missing minor version is an error")]
-    #[test_case("3.Y" => matches Err(Error::ParseVersionComponentError(_)) ; "non-digit minor version is an error")]
-    #[test_case("X.7" => matches Err(Error::ParseVersionComponentError(_)) ; "non-digit major version is an error")]
+    #[test_case("" => Err(ParseVersionError::DotMissing) ; "empty string is an error")]
+    #[test_case("3" => Err(ParseVersionError::DotMissing) ; "major-only version is an error")]
+    #[test_case(".7" => Err(ParseVersionError::Empty) ; "missing major version is an error")]
+    #[test_case("3." => Err(ParseVersionError::Empty) ; "missing minor version is an error")]
+    #[test_case("3.Y" => Err(ParseVersionError::NonNumeric("Y".to_string())) ; "non-digit minor version is an error")]
+    #[test_case("X.7" => Err(ParseVersionError::NonNumeric("X".to_string())) ; "non-digit major version is an error")]
+    #[test_case("3.11.4" => Err(ParseVersionError::TooManyComponents) ; "micro version is an error")]
+    #[test_case("3.70000" => Err(ParseVersionError::TooLarge("70000".to_string())) ; "overflowing minor version is an error")]
     #[test_case("42.13" => Ok(ExactVersion {major: 42, minor: 13 }) ; "double digit version components")]
-    fn exactversion_from_str_tests(version_str: &str) -> Result<ExactVersion> {
+    fn exactversion_from_str_tests(
+        version_str: &str,
+    ) -> std::result::Result<ExactVersion, ParseVersionError> {
         ExactVersion::from_str(version_str)
     }
 
     #[test_case("/" => Err(Error::FileNameMissing) ; "path missing a file name is an error")]
     #[test_case("/notpython" => Err(Error::PathFileNameError) ; "path not ending with 'python' is an error")]
     #[test_case("/python3" => Err(Error::PathFileNameError) ; "filename lacking a minor component is an error")]
-    #[test_case("/pythonX.Y" => matches Err(Error::ParseVersionComponentError(_)) ; "filename with non-digit version is an error")]
+    #[test_case("/pythonX.Y" => Err(Error::ParseVersionError(ParseVersionError::NonNumeric("X".to_string()))) ; "filename with non-digit version is an error")]
     #[test_case("/python42.13" => Ok(ExactVersion { major: 42, minor: 13 }) ; "double digit version components")]
+    #[test_case("/mnt/c/Python311/python3.11.exe" => Ok(ExactVersion { major: 3, minor: 11 }) ; "Windows executable under WSL")]
+    #[test_case("/mnt/c/Python311/python.exe" => Err(Error::PathFileNameError) ; "Windows executable without a version is an error")]
+    #[test_case("/mnt/c/Windows/foo.exe" => Err(Error::PathFileNameError) ; "unrelated Windows executable is an error")]
+    #[test_case("/python3.11.exe.bak" => Err(Error::ParseVersionError(ParseVersionError::TooManyComponents)) ; "only a trailing .exe is ignored")]
+    #[test_case("/opt/python/bin/python3.11.4" => Ok(ExactVersion { major: 3, minor: 11 }) ; "micro version is ignored")]
+    #[test_case("/mnt/c/Python311/python3.11.4.exe" => Ok(ExactVersion { major: 3, minor: 11 }) ; "micro version and .exe are ignored")]
+    #[test_case("/python3.11.4.1" => Err(Error::ParseVersionError(ParseVersionError::TooManyComponents)) ; "only one extra component is ignored")]
+    #[test_case("/python3.11.x" => Err(Error::ParseVersionError(ParseVersionError::TooManyComponents)) ; "non-digit micro version is an error")]
+    #[test_case("/python3.11." => Err(Error::ParseVersionError(ParseVersionError::TooManyComponents)) ; "empty micro version is an error")]
+    #[test_case("/usr/bin/python3.11" => Ok(ExactVersion { major: 3, minor: 11 }) ; "interpreter next to its tools")]
+    #[test_case("/usr/bin/python3.11-config" => Err(Error::ParseVersionError(ParseVersionError::NonNumeric("11-config".to_string()))) ; "versioned python-config is an error")]
+    #[test_case("/usr/bin/python3-config" => Err(Error::ParseVersionError(ParseVersionError::DotMissing)) ; "major-only python-config is an error")]
+    #[test_case("/usr/bin/python-config" => Err(Error::ParseVersionError(ParseVersionError::DotMissing)) ; "unversioned python-config is an error")]
+    #[test_case("/usr/bin/python3.11-dbg-config" => Err(Error::ParseVersionError(ParseVersionError::NonNumeric("11-dbg-config".to_string()))) ; "debug python-config is an error")]
+    #[test_case("/usr/bin/python3.7m" => Err(Error::ParseVersionError(ParseVersionError::NonNumeric("7m".to_string()))) ; "ABI-suffixed name is an error")]
+    #[test_case("/usr/bin/python3.11-gdb.py" => Err(Error::ParseVersionError(ParseVersionError::TooManyComponents)) ; "gdb helper script is an error")]
     fn exactversion_from_path_tests(path: &str) -> Result<ExactVersion> {
         ExactVersion::from_path(&PathBuf::from(path))
     }
 
+    #[test_case("/pypy3.9", Implementation::PyPy => Ok(ExactVersion { major: 3, minor: 9 }) ; "PyPy")]
+    #[test_case("/python3.9", Implementation::PyPy => Err(Error::PathFileNameError) ; "CPython file name for PyPy is an error")]
+    #[test_case("/pypy3.9", Implementation::CPython => Err(Error::PathFileNameError) ; "PyPy file name for CPython is an error")]
+    #[test_case("/pypy3", Implementation::PyPy => Err(Error::PathFileNameError) ; "PyPy file name lacking a minor component is an error")]
+    fn exactversion_from_implementation_path_tests(
+        path: &str,
+        implementation: Implementation,
+    ) -> Result<ExactVersion> {
+        ExactVersion::from_implementation_path(&PathBuf::from(path), implementation)
+    }
+
+    #[test_case("/mnt/c/bin/Python3.11", false => Err(Error::PathFileNameError) ; "capitalized is an error by default")]
+    #[test_case("/mnt/c/bin/Python3.11", true => Ok(ExactVersion::new(3, 11)) ; "capitalized when ignoring case")]
+    #[test_case("/mnt/c/Python311/PYTHON3.11.EXE", true => Ok(ExactVersion::new(3, 11)) ; "upper-case Windows executable when ignoring case")]
+    #[test_case("/usr/bin/python3.11", true => Ok(ExactVersion::new(3, 11)) ; "lower-case when ignoring case")]
+    fn exactversion_from_discovered_path_tests(
+        path: &str,
+        ignore_case: bool,
+    ) -> Result<ExactVersion> {
+        ExactVersion::from_discovered_path(
+            &PathBuf::from(path),
+            Implementation::CPython,
+            ignore_case,
+        )
+    }
+
+    #[test_case("/usr/bin/python3.11", Implementation::CPython => Ok(Interpreter { implementation: Implementation::CPython, exact_version: ExactVersion::new(3, 11), path: PathBuf::from("/usr/bin/python3.11"), full_version: None }) ; "CPython")]
+    #[test_case("/opt/pypy/bin/pypy3.9", Implementation::PyPy => Ok(Interpreter { implementation: Implementation::PyPy, exact_version: ExactVersion::new(3, 9), path: PathBuf::from("/opt/pypy/bin/pypy3.9"), full_version: None }) ; "PyPy")]
+    #[test_case("/usr/bin/pypy3.9", Implementation::CPython => Err(Error::PathFileNameError) ; "wrong implementation")]
+    #[test_case("/usr/bin/python3", Implementation::CPython => Err(Error::PathFileNameError) ; "no minor version")]
+    fn interpreter_from_path_tests(
+        path: &str,
+        implementation: Implementation,
+    ) -> Result<Interpreter> {
+        Interpreter::from_path(Path::new(path), implementation)
+    }
+
+    #[test_case(RequestedVersion::Any => VersionMatch::Loosely ; "Any")]
+    #[test_case(RequestedVersion::MajorOnly(3) => VersionMatch::Loosely ; "same major version")]
+    #[test_case(RequestedVersion::MajorOnly(2) => VersionMatch::NotAtAll ; "different major version")]
+    #[test_case(RequestedVersion::Exact(3, 11) => VersionMatch::Exactly ; "same version")]
+    #[test_case(RequestedVersion::Exact(3, 12) => VersionMatch::NotAtAll ; "different version")]
+    #[test_case(RequestedVersion::AtLeast(3, 10) => VersionMatch::Loosely ; "at least an older version")]
+    #[test_case(RequestedVersion::AtLeast(3, 12) => VersionMatch::NotAtAll ; "at least a newer version")]
+    fn interpreter_matches_tests(requested: RequestedVersion) -> VersionMatch {
+        Interpreter::from_path(Path::new("/usr/bin/pypy3.11"), Implementation::PyPy)
+            .unwrap()
+            .matches(requested)
+    }
+
+    #[test_case("cpython@3.11" => Ok((Implementation::CPython, RequestedVersion::Exact(3, 11))) ; "CPython")]
+    #[test_case("pypy@3.9" => Ok((Implementation::PyPy, RequestedVersion::Exact(3, 9))) ; "PyPy")]
+    #[test_case("3.11" => Ok((Implementation::CPython, RequestedVersion::Exact(3, 11))) ; "bare version defaults to CPython")]
+    #[test_case("PyPy@3" => Ok((Implementation::PyPy, RequestedVersion::MajorOnly(3))) ; "implementation is case-insensitive")]
+    #[test_case("pypy@" => Ok((Implementation::PyPy, RequestedVersion::Any)) ; "implementation without a version")]
+    #[test_case("cpython@3.8+" => Ok((Implementation::CPython, RequestedVersion::AtLeast(3, 8))) ; "minimum version")]
+    #[test_case("jython@2.7" => Err(Error::UnknownImplementation("jython".to_string())) ; "unknown implementation")]
+    #[test_case("pypy@3.x" => Err(Error::ParseVersionError(ParseVersionError::NonNumeric("x".to_string()))) ; "invalid version")]
+    #[test_case("@3.11" => Err(Error::UnknownImplementation(String::new())) ; "empty implementation")]
+    fn parse_spec_tests(spec: &str) -> Result<(Implementation, RequestedVersion)> {
+        parse_spec(spec)
+    }
+
     #[test]
     fn exactversion_from_path_invalid_utf8() {
         // From https://doc.rust-lang.org/std/ffi/struct.OsStr.html#examples-2.
@@ -420,6 +2057,126 @@ mod tests {
         example.supports(requested_version)
     }
 
+    #[test_case(RequestedVersion::Any, 2, 7 => VersionMatch::Loosely ; "Any, older major")]
+    #[test_case(RequestedVersion::Any, 3, 6 => VersionMatch::Loosely ; "Any, same version")]
+    #[test_case(RequestedVersion::Any, 4, 0 => VersionMatch::Loosely ; "Any, newer major")]
+    #[test_case(RequestedVersion::MajorOnly(3), 2, 7 => VersionMatch::NotAtAll ; "major-only, older major")]
+    #[test_case(RequestedVersion::MajorOnly(3), 3, 5 => VersionMatch::Loosely ; "major-only, older minor")]
+    #[test_case(RequestedVersion::MajorOnly(3), 3, 6 => VersionMatch::Loosely ; "major-only, same major")]
+    #[test_case(RequestedVersion::MajorOnly(3), 3, 7 => VersionMatch::Loosely ; "major-only, newer minor")]
+    #[test_case(RequestedVersion::MajorOnly(3), 4, 0 => VersionMatch::NotAtAll ; "major-only, newer major")]
+    #[test_case(RequestedVersion::Exact(3, 6), 2, 6 => VersionMatch::NotAtAll ; "exact, older major")]
+    #[test_case(RequestedVersion::Exact(3, 6), 3, 5 => VersionMatch::NotAtAll ; "exact, older minor")]
+    #[test_case(RequestedVersion::Exact(3, 6), 3, 6 => VersionMatch::Exactly ; "exact, same version")]
+    #[test_case(RequestedVersion::Exact(3, 6), 3, 7 => VersionMatch::NotAtAll ; "exact, newer minor")]
+    #[test_case(RequestedVersion::Exact(3, 6), 4, 6 => VersionMatch::NotAtAll ; "exact, newer major")]
+    #[test_case(RequestedVersion::AtLeast(3, 6), 2, 7 => VersionMatch::NotAtAll ; "minimum, older major")]
+    #[test_case(RequestedVersion::AtLeast(3, 6), 3, 5 => VersionMatch::NotAtAll ; "minimum, older minor")]
+    #[test_case(RequestedVersion::AtLeast(3, 6), 3, 6 => VersionMatch::Loosely ; "minimum, same version")]
+    #[test_case(RequestedVersion::AtLeast(3, 6), 3, 10 => VersionMatch::Loosely ; "minimum, newer minor")]
+    #[test_case(RequestedVersion::AtLeast(3, 6), 4, 0 => VersionMatch::Loosely ; "minimum, newer major")]
+    fn requestedversion_matches_tests(
+        requested_version: RequestedVersion,
+        major: ComponentSize,
+        minor: ComponentSize,
+    ) -> VersionMatch {
+        requested_version.matches(&ExactVersion::new(major, minor))
+    }
+
+    #[test_case("3.11.4" => vec![3, 11, 4] ; "final release")]
+    #[test_case("3.9.18" => vec![3, 9, 18] ; "double-digit micro version")]
+    #[test_case("3.13-dev" => vec![3] ; "development version")]
+    #[test_case("pypy3.10-7.3.15" => Vec::<u32>::new() ; "non-CPython")]
+    fn pyenv_version_key_tests(version: &str) -> Vec<u32> {
+        pyenv_version_key(version)
+    }
+
+    #[test_case("Python 3.14.0\n" => ReleaseLevel::Final ; "final release")]
+    #[test_case("Python 3.14.0a1\n" => ReleaseLevel::Prerelease ; "alpha")]
+    #[test_case("Python 3.14.0b2\n" => ReleaseLevel::Prerelease ; "beta")]
+    #[test_case("Python 3.14.0rc1\n" => ReleaseLevel::Prerelease ; "release candidate")]
+    #[test_case("Python 3.14.0+\n" => ReleaseLevel::Final ; "development build of a final release")]
+    #[test_case("Python 3.9.16 (7f4d8e5f0f3a, Jan 09 2023)\n[PyPy 7.3.11]\n" => ReleaseLevel::Final ; "PyPy")]
+    #[test_case("Python\n" => ReleaseLevel::Unknown ; "no version")]
+    #[test_case(">>> " => ReleaseLevel::Unknown ; "unexpected output")]
+    #[test_case("" => ReleaseLevel::Unknown ; "no output")]
+    fn release_level_from_output_tests(version_output: &str) -> ReleaseLevel {
+        release_level_from_output(version_output)
+    }
+
+    #[test_case("Python 3.11.4\n" => Some(ExactVersion::new(3, 11)) ; "final release")]
+    #[test_case("Python 3.14.0rc1\n" => Some(ExactVersion::new(3, 14)) ; "pre-release")]
+    #[test_case("Python 3.12\n" => Some(ExactVersion::new(3, 12)) ; "no micro version")]
+    #[test_case("Python 3\n" => None ; "major version only")]
+    #[test_case("Python 3.x\n" => None ; "non-numeric minor version")]
+    #[test_case("not a version" => None ; "unexpected output")]
+    fn exact_version_from_output_tests(version_output: &str) -> Option<ExactVersion> {
+        parse_version_output(version_output).and_then(exact_version_from_full)
+    }
+
+    #[test_case("/usr/bin:/bin", Some("/cwd") => vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")] ; "no empty entries")]
+    #[test_case(":/usr/bin", Some("/cwd") => vec![PathBuf::from("/cwd"), PathBuf::from("/usr/bin")] ; "leading colon")]
+    #[test_case("/usr/bin:", Some("/cwd") => vec![PathBuf::from("/usr/bin"), PathBuf::from("/cwd")] ; "trailing colon")]
+    #[test_case("/usr/bin::/bin", Some("/cwd") => vec![PathBuf::from("/usr/bin"), PathBuf::from("/cwd"), PathBuf::from("/bin")] ; "double colon")]
+    #[test_case(":/usr/bin", None => vec![PathBuf::from("/usr/bin")] ; "unknown current directory")]
+    fn split_path_tests(path_val: &str, current_dir: Option<&str>) -> Vec<PathBuf> {
+        split_path(OsStr::new(path_val), current_dir.map(Path::new))
+    }
+
+    #[test]
+    fn dedup_directories_keeps_first_occurrence() {
+        let dir1 = tempfile::tempdir().unwrap();
+        let dir2 = tempfile::tempdir().unwrap();
+        let link = dir2.path().join("link");
+        std::os::unix::fs::symlink(dir1.path(), &link).unwrap();
+        let missing = PathBuf::from("/path/to/nowhere");
+
+        assert_eq!(
+            dedup_directories(vec![
+                (dir1.path().to_path_buf(), Source::Path),
+                (dir2.path().to_path_buf(), Source::Path),
+                (missing.clone(), Source::Path),
+                (dir1.path().join("."), Source::LauncherPath),
+                (link, Source::LauncherPath),
+                (dir2.path().to_path_buf(), Source::ExtraPaths),
+                (missing.clone(), Source::ExtraPaths),
+            ]),
+            vec![
+                (dir1.path().to_path_buf(), Source::Path),
+                (dir2.path().to_path_buf(), Source::Path),
+                (missing, Source::Path)
+            ]
+        );
+    }
+
+    #[test]
+    fn path_entries_from_empty_and_duplicate_entries() {
+        let dir1 = tempfile::tempdir().unwrap();
+        let dir2 = tempfile::tempdir().unwrap();
+        let current_dir = env::current_dir().unwrap();
+        let path_var = env::join_paths([
+            dir1.path(),
+            Path::new(""),
+            dir2.path(),
+            dir1.path(),
+            current_dir.as_path(),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            path_entries_from(&path_var),
+            vec![
+                (dir1.path().to_path_buf(), Source::Path),
+                (current_dir.clone(), Source::Path),
+                (dir2.path().to_path_buf(), Source::Path),
+            ]
+        );
+        assert_eq!(
+            path_entries_from(OsStr::new("")),
+            vec![(current_dir, Source::Path)]
+        );
+    }
+
     #[test_case(2, 7, "/dir1/python2.7" ; "first directory")]
     #[test_case(3, 6, "/dir1/python3.6" ; "matches in multiple directories")]
     #[test_case(3, 7, "/dir2/python3.7" ; "last directory")]
@@ -435,7 +2192,13 @@ mod tests {
             python37_path,
         ];
 
-        let executables = all_executables_in_paths(files.into_iter());
+        let executables = all_executables_in_paths(
+            files,
+            Implementation::CPython,
+            TieBreak::FirstInPath,
+            &[],
+            false,
+        );
         assert_eq!(executables.len(), 3);
 
         let version = ExactVersion { major, minor };
@@ -443,6 +2206,86 @@ mod tests {
         assert_eq!(executables.get(&version), Some(&PathBuf::from(path)));
     }
 
+    #[test_case(TieBreak::FirstInPath => "/home/user/.local/bin/python3.9" ; "first in PATH")]
+    #[test_case(TieBreak::PreferSystem => "/usr/bin/python3.9" ; "prefer system")]
+    fn all_executables_in_paths_tie_break_tests(tie_break: TieBreak) -> String {
+        let files = vec![
+            PathBuf::from("/home/user/.local/bin/python3.9"),
+            PathBuf::from("/usr/bin/python3.9"),
+            PathBuf::from("/bin/python3.9"),
+            PathBuf::from("/usr/bin/python3.8"),
+        ];
+
+        let executables =
+            all_executables_in_paths(files, Implementation::CPython, tie_break, &[], false);
+        assert_eq!(executables.len(), 2);
+        executables[&ExactVersion::new(3, 9)].display().to_string()
+    }
+
+    #[test_case("/usr/bin/python3.9", "/home/user/.local/bin/python3.9" => false ; "system first")]
+    #[test_case("/opt/python/bin/python3.9", "/home/user/.local/bin/python3.9" => false ; "neither in a system directory")]
+    #[test_case("/home/user/.local/bin/python3.9", "/bin/python3.9" => true ; "system later")]
+    #[test_case("/usr/bin/python3.9", "/bin/python3.9" => false ; "both in system directories")]
+    fn tie_break_prefer_system_tests(earlier: &str, later: &str) -> bool {
+        TieBreak::PreferSystem.prefers_later(Path::new(earlier), Path::new(later))
+    }
+
+    #[test_case("/usr/bin/python3.9", "/opt/python/bin/python3.9", TieBreak::FirstInPath => true ; "later listed first")]
+    #[test_case("/opt/python/bin/python3.9", "/usr/bin/python3.9", TieBreak::FirstInPath => false ; "earlier listed first")]
+    #[test_case("/home/user/.local/bin/python3.9", "/usr/bin/python3.9", TieBreak::FirstInPath => true ; "only later listed")]
+    #[test_case("/usr/bin/python3.9", "/home/user/.local/bin/python3.9", TieBreak::FirstInPath => false ; "only earlier listed")]
+    #[test_case("/opt/python/bin/python3.9", "/bin/python3.9", TieBreak::PreferSystem => false ; "priority beats preferring system")]
+    #[test_case("/home/user/.local/bin/python3.9", "/bin/python3.9", TieBreak::PreferSystem => true ; "neither listed falls back to the tie break")]
+    #[test_case("/home/user/.local/bin/python3.9", "/tmp/python3.9", TieBreak::FirstInPath => false ; "neither listed in PATH order")]
+    fn prefers_later_priority_paths_tests(earlier: &str, later: &str, tie_break: TieBreak) -> bool {
+        let priority_paths = [PathBuf::from("/opt/python/bin"), PathBuf::from("/usr/bin/")];
+        prefers_later(
+            tie_break,
+            &priority_paths,
+            Path::new(earlier),
+            Path::new(later),
+        )
+    }
+
+    #[test]
+    fn all_executables_in_paths_by_implementation() {
+        let files = vec![
+            PathBuf::from("/dir1/python3.9"),
+            PathBuf::from("/dir1/pypy3.9"),
+            PathBuf::from("/dir2/pypy3.8"),
+        ];
+
+        let cpython = all_executables_in_paths(
+            files.clone(),
+            Implementation::CPython,
+            TieBreak::FirstInPath,
+            &[],
+            false,
+        );
+        assert_eq!(cpython.len(), 1);
+        assert_eq!(
+            cpython.get(&ExactVersion::new(3, 9)),
+            Some(&PathBuf::from("/dir1/python3.9"))
+        );
+
+        let pypy = all_executables_in_paths(
+            files,
+            Implementation::PyPy,
+            TieBreak::FirstInPath,
+            &[],
+            false,
+        );
+        assert_eq!(pypy.len(), 2);
+        assert_eq!(
+            pypy.get(&ExactVersion::new(3, 9)),
+            Some(&PathBuf::from("/dir1/pypy3.9"))
+        );
+        assert_eq!(
+            pypy.get(&ExactVersion::new(3, 8)),
+            Some(&PathBuf::from("/dir2/pypy3.8"))
+        );
+    }
+
     #[test_case(RequestedVersion::Any => Some(PathBuf::from("/python3.7")) ; "Any version chooses newest version")]
     #[test_case(RequestedVersion::MajorOnly(42) => None ; "major-only version newer than any options")]
     #[test_case(RequestedVersion::MajorOnly(3) => Some(PathBuf::from("/python3.7")) ; "matching major version chooses newest minor version")]
@@ -451,7 +2294,7 @@ mod tests {
     fn find_executable_in_hashmap_tests(requested_version: RequestedVersion) -> Option<PathBuf> {
         let mut executables = HashMap::new();
         assert_eq!(
-            find_executable_in_hashmap(RequestedVersion::Any, &executables),
+            find_executable_in_hashmap(RequestedVersion::Any, &executables, Selection::Newest),
             None
         );
 
@@ -461,6 +2304,111 @@ mod tests {
         let python37_path = PathBuf::from("/python3.7");
         executables.insert(ExactVersion { major: 3, minor: 7 }, python37_path);
 
-        find_executable_in_hashmap(requested_version, &executables)
+        find_executable_in_hashmap(requested_version, &executables, Selection::Newest)
+    }
+
+    #[test]
+    fn find_executable_in_hashmap_ignores_insertion_order() {
+        let minors = [7, 12, 9, 10, 8];
+        for rotation in 0..minors.len() {
+            let mut executables = HashMap::new();
+            for &minor in minors[rotation..].iter().chain(&minors[..rotation]) {
+                executables.insert(
+                    ExactVersion::new(3, minor),
+                    PathBuf::from(format!("/python3.{}", minor)),
+                );
+            }
+
+            assert_eq!(
+                find_executable_in_hashmap(RequestedVersion::Any, &executables, Selection::Newest),
+                Some(PathBuf::from("/python3.12"))
+            );
+        }
+    }
+
+    #[test_case(RequestedVersion::Any => Some(PathBuf::from("/python3.8")) ; "Any version chooses oldest version")]
+    #[test_case(RequestedVersion::MajorOnly(3) => Some(PathBuf::from("/python3.8")) ; "matching major version chooses oldest minor version")]
+    #[test_case(RequestedVersion::AtLeast(3, 9) => Some(PathBuf::from("/python3.10")) ; "minimum version chooses oldest satisfying version")]
+    #[test_case(RequestedVersion::AtLeast(3, 12) => None ; "minimum version newer than any options")]
+    #[test_case(RequestedVersion::Exact(3, 11) => Some(PathBuf::from("/python3.11")) ; "exact version match")]
+    fn find_executable_in_hashmap_oldest_tests(
+        requested_version: RequestedVersion,
+    ) -> Option<PathBuf> {
+        let mut executables = HashMap::new();
+        for minor in [8, 10, 11] {
+            executables.insert(
+                ExactVersion::new(3, minor),
+                PathBuf::from(format!("/python3.{}", minor)),
+            );
+        }
+
+        find_executable_in_hashmap(requested_version, &executables, Selection::Oldest)
+    }
+
+    #[test]
+    fn find_executable_retrying_succeeds_on_later_attempt() {
+        let mut attempts = 0;
+        let scanner = || {
+            attempts += 1;
+            let mut executables = HashMap::new();
+            if attempts > 1 {
+                executables.insert(ExactVersion::new(3, 9), PathBuf::from("/python3.9"));
+            }
+            executables
+        };
+
+        assert_eq!(
+            find_executable_retrying(
+                RequestedVersion::Any,
+                Selection::Newest,
+                2,
+                Duration::ZERO,
+                scanner
+            ),
+            Some(PathBuf::from("/python3.9"))
+        );
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn find_executable_retrying_gives_up() {
+        let mut attempts = 0;
+        let scanner = || {
+            attempts += 1;
+            HashMap::new()
+        };
+
+        assert_eq!(
+            find_executable_retrying(
+                RequestedVersion::Any,
+                Selection::Newest,
+                2,
+                Duration::ZERO,
+                scanner
+            ),
+            None
+        );
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn find_executable_retrying_defaults_to_single_scan() {
+        let mut attempts = 0;
+        let scanner = || {
+            attempts += 1;
+            HashMap::new()
+        };
+
+        assert_eq!(
+            find_executable_retrying(
+                RequestedVersion::Any,
+                Selection::Newest,
+                0,
+                Duration::ZERO,
+                scanner
+            ),
+            None
+        );
+        assert_eq!(attempts, 1);
     }
 }
@@ -1,13 +1,20 @@
 // https://docs.python.org/3.8/using/windows.html#python-launcher-for-windows
 // https://github.com/python/cpython/blob/master/PC/launcher.c
 
-use std::{env, ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+use std::{
+    env,
+    ffi::CString,
+    io::{self, Write},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
 
-use nix::unistd;
+use nix::{errno::Errno, sys::signal::Signal, unistd};
 
-use python_launcher::cli;
+use python_launcher::{cli, exec};
 
 #[cfg(not(tarpaulin_include))]
+#[allow(deprecated)] // `human_panic::setup_panic!` uses `std::panic::PanicInfo`.
 fn main() {
     human_panic::setup_panic!(Metadata {
         name: env!("CARGO_PKG_DESCRIPTION").into(),
@@ -16,49 +23,98 @@ fn main() {
         homepage: env!("CARGO_PKG_REPOSITORY").into(),
     });
 
-    let log_level = if env::var_os("PYLAUNCH_DEBUG").is_some() {
-        3
-    } else {
-        0
-    };
+    let argv = env::args().collect::<Vec<String>>();
+    let log_level = cli::log_verbosity(env::var_os("PYLAUNCH_DEBUG").as_deref());
+    let quiet = cli::quiet_requested(&argv);
     /*
     - `error!` is for errors
-    - `info!` is to communicate what the launcher is doing/checking
-    - `debug!` is communicating about specific values
+    - `info!` is to communicate what the launcher chose and why
+    - `debug!` is communicating about each check made and specific values
     */
     stderrlog::new()
         .module(module_path!())
         .module("python_launcher")
         .show_level(false)
-        .verbosity(log_level) // [error, warn, info, debug, trace]
+        .verbosity(log_level)
         .init()
         .unwrap();
 
-    match cli::Action::from_main(&env::args().collect::<Vec<String>>()) {
+    match cli::Action::from_main(&argv) {
         Ok(action) => match action {
             cli::Action::Help(message, executable) => {
-                print!("{}", message);
+                print_output(&message);
                 run(&executable, &["--help".to_string()])
-                    .map_err(|message| log_exit(nix::errno::errno(), message))
-                    .unwrap()
+                    .unwrap_or_else(|error| exec_failed(&executable, error))
+            }
+            cli::Action::List(output) => print_output(&output),
+            cli::Action::ListTable(table) => write_output(|stdout| table.write(stdout)),
+            cli::Action::Precedence(output) => print_output(&output),
+            cli::Action::Complete(script) => print_output(&script),
+            cli::Action::Version(version) => print_output(&format!("{}\n", version)),
+            cli::Action::CheckConfig(problems) => {
+                if problems.is_empty() {
+                    print_output("No problems found\n");
+                } else {
+                    problems
+                        .iter()
+                        .for_each(|problem| print_output(&format!("{}\n", problem)));
+                    std::process::exit(exitcode::CONFIG);
+                }
             }
-            cli::Action::List(output) => print!("{}", output),
+            cli::Action::Info(report) => print_output(&format!("{}\n", report)),
+            cli::Action::ShowCommand(command) => print_output(&format!("{}\n", command)),
             cli::Action::Execute {
                 executable, args, ..
-            } => run(&executable, &args)
-                .map_err(|message| log_exit(nix::errno::errno(), message))
-                .unwrap(),
+            } => run(&executable, &args).unwrap_or_else(|error| exec_failed(&executable, error)),
         },
+        Err(message) if cli::json_requested(&argv) => {
+            print_output(&format!("{}\n", cli::json_error(&message, &argv)));
+            std::process::exit(message.exit_code());
+        }
+        Err(message) if quiet && message.is_not_found() => std::process::exit(message.exit_code()),
         Err(message) => log_exit(message.exit_code(), message),
     }
 }
 
+/// Writes `output` to stdout.
+///
+/// If whatever is reading stdout has gone away (e.g. `py --list | head -1`),
+/// the launcher exits quietly with the status a shell reports for a process
+/// killed by `SIGPIPE`, instead of panicking.
+#[cfg(not(tarpaulin_include))]
+fn print_output(output: &str) {
+    write_output(|stdout| stdout.write_all(output.as_bytes()))
+}
+
+/// Like [`print_output`], but with `write` doing the writing to a locked
+/// stdout, e.g. as the output is produced.
+#[cfg(not(tarpaulin_include))]
+fn write_output(write: impl FnOnce(&mut io::StdoutLock<'static>) -> io::Result<()>) {
+    let mut stdout = io::stdout().lock();
+    match write(&mut stdout).and_then(|_| stdout.flush()) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+            std::process::exit(128 + Signal::SIGPIPE as i32)
+        }
+        Err(error) => log_exit(exitcode::IOERR, error),
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
 fn log_exit(return_code: i32, message: impl std::error::Error) {
     log::error!("{}", message);
     std::process::exit(return_code);
 }
 
+/// Logs why `executable` couldn't be executed and exits with the
+/// corresponding `errno`.
+#[cfg(not(tarpaulin_include))]
+fn exec_failed(executable: &Path, error: nix::Error) {
+    let (return_code, reason) = exec::exec_failure(error);
+    log::error!("failed to execute {}: {}", executable.display(), reason);
+    std::process::exit(return_code);
+}
+
 #[cfg(not(tarpaulin_include))]
 fn run(executable: &Path, args: &[String]) -> nix::Result<()> {
     if executable.is_file() {
@@ -67,9 +123,40 @@ fn run(executable: &Path, args: &[String]) -> nix::Result<()> {
         log::error!("{}: No such file", executable.display());
         std::process::exit(1);
     }
-    let executable_as_cstring = CString::new(executable.as_os_str().as_bytes()).unwrap();
+    let executable_as_cstring = to_cstring(executable.as_os_str().as_bytes(), "path");
     let mut argv = vec![executable_as_cstring.clone()];
-    argv.extend(args.iter().map(|arg| CString::new(arg.as_str()).unwrap()));
+    argv.extend(
+        args.iter()
+            .map(|arg| to_cstring(arg.as_bytes(), "argument")),
+    );
 
-    unistd::execv(&executable_as_cstring, &argv).map(|_| ())
+    match unistd::execv(&executable_as_cstring, &argv) {
+        // Like a shell, run a file without a recognized executable format
+        // (e.g. a wrapper script lacking a shebang) as a shell script.
+        Err(error) if error.as_errno() == Some(Errno::ENOEXEC) => {
+            log::info!("Executing {} with {}", executable.display(), SHELL);
+            let shell = to_cstring(SHELL.as_bytes(), "path");
+            argv.insert(0, shell.clone());
+            unistd::execv(&shell, &argv).map(|_| ())
+        }
+        result => result.map(|_| ()),
+    }
+}
+
+/// The shell used to run an executable which `execv()` can't.
+static SHELL: &str = "/bin/sh";
+
+/// Converts `bytes` for passing to `execv()`, exiting with an error if they
+/// contain a NUL byte (e.g. from a malformed shebang line).
+#[cfg(not(tarpaulin_include))]
+fn to_cstring(bytes: &[u8], kind: &str) -> CString {
+    CString::new(bytes).unwrap_or_else(|error| {
+        log::error!(
+            "{} {:?} contains a NUL byte at position {}",
+            kind,
+            String::from_utf8_lossy(bytes),
+            error.nul_position()
+        );
+        std::process::exit(exitcode::DATAERR);
+    })
 }
@@ -4,14 +4,17 @@ use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serial_test_derive::serial;
+use tempfile::TempDir;
 
 use python_launcher::cli;
 use python_launcher::cli::Action;
 use python_launcher::Error;
+use python_launcher::ExactVersion;
 use python_launcher::RequestedVersion;
+use python_launcher::SearchDirectories;
 
 use common::{EnvState, EnvVarState};
 
@@ -27,6 +30,9 @@ fn from_main_help() {
                 assert!(message.contains(launcher_path));
                 assert_eq!(env_state.python37, python_path);
                 assert!(message.contains(python_path.to_str().unwrap()));
+                // Installed interpreters are listed.
+                assert!(message.contains(env_state.python27.to_str().unwrap()));
+                assert!(message.contains("2.7"));
             }
             _ => panic!("{:?} flag did not return Action::Help", flag),
         }
@@ -53,12 +59,557 @@ fn from_main_list() {
     let env_state = EnvState::new();
 
     match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
-        Ok(Action::List(output)) => {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
             assert!(output.contains(env_state.python27.to_str().unwrap()));
             assert!(output.contains(env_state.python36.to_str().unwrap()));
             assert!(output.contains(env_state.python37.to_str().unwrap()));
         }
-        _ => panic!("'--list' did not return Action::List"),
+        _ => panic!("'--list' did not return Action::ListTable"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_fake_path() {
+    let fake_path = common::FakePath::new();
+    let python39 = fake_path.python(3, 9);
+    let python311 = fake_path.python(3, 11);
+    let pypy310 = fake_path.executable("pypy3.10", "Python 3.10.14");
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            let paths: Vec<&str> = output
+                .lines()
+                .map(|line| line.split('│').nth(1).unwrap().trim())
+                .collect();
+            assert_eq!(
+                paths,
+                vec![
+                    python311.to_str().unwrap(),
+                    pypy310.to_str().unwrap(),
+                    python39.to_str().unwrap()
+                ]
+            );
+        }
+        _ => panic!("'--list' did not return Action::ListTable"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_grouped() {
+    let mut env_state = EnvState::new();
+    let list = || match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--list".to_string(),
+        "--grouped".to_string(),
+    ]) {
+        Ok(Action::List(output)) => output
+            .lines()
+            .map(|line| {
+                line.split('│')
+                    .map(|cell| cell.trim().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+        result => panic!("'--list --grouped' returned {:?}", result),
+    };
+    let row = |version: &str, path: &std::path::Path, marker: &str| {
+        vec![
+            version.to_string(),
+            path.to_str().unwrap().to_string(),
+            marker.to_string(),
+        ]
+    };
+
+    assert_eq!(
+        list(),
+        vec![
+            vec!["Python 3".to_string()],
+            row("3.7", &env_state.python37, "py -3"),
+            row("3.6", &env_state.python36, ""),
+            vec!["Python 2".to_string()],
+            row("2.7", &env_state.python27, "py -2"),
+        ]
+    );
+
+    env_state.env_vars.change("PY_PYTHON3", Some("3.6"));
+    assert_eq!(
+        list(),
+        vec![
+            vec!["Python 3".to_string()],
+            row("3.7", &env_state.python37, ""),
+            row("3.6", &env_state.python36, "py -3"),
+            vec!["Python 2".to_string()],
+            row("2.7", &env_state.python27, "py -2"),
+        ]
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_list_ignores_activated_virtual_env() {
+    let mut env_state = EnvState::new();
+    let list = || match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => table.to_string(),
+        result => panic!("'--list' returned {:?}", result),
+    };
+    let without_venv = list();
+
+    let venv_root = TempDir::new().unwrap();
+    fs::create_dir(venv_root.path().join("bin")).unwrap();
+    let venv_python = common::touch_file(venv_root.path().join("bin").join("python"));
+    fs::write(
+        venv_root.path().join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.6.15\n",
+    )
+    .unwrap();
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+
+    let with_venv = list();
+    assert_eq!(with_venv, without_venv);
+    assert!(!with_venv.contains(venv_python.to_str().unwrap()));
+    for python in [
+        &env_state.python27,
+        &env_state.python36,
+        &env_state.python37,
+    ] {
+        assert!(with_venv.contains(python.to_str().unwrap()));
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_sort() {
+    let env_state = EnvState::new();
+    let list = |order: &str| match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--list".to_string(),
+        "--sort".to_string(),
+        order.to_string(),
+    ]) {
+        Ok(Action::ListTable(table)) => table
+            .to_string()
+            .lines()
+            .map(|line| PathBuf::from(line.split('│').nth(1).unwrap().trim()))
+            .collect::<Vec<_>>(),
+        result => panic!("'--sort {}' returned {:?}", order, result),
+    };
+    let python27 = &env_state.python27;
+    let python36 = &env_state.python36;
+    let python37 = &env_state.python37;
+
+    assert_eq!(
+        list("version-desc"),
+        vec![python37.clone(), python36.clone(), python27.clone()]
+    );
+    assert_eq!(
+        list("version-asc"),
+        vec![python27.clone(), python36.clone(), python37.clone()]
+    );
+    // `python2.7` and `python3.6` share a directory.
+    let by_path = if python36.parent() < python37.parent() {
+        vec![python36.clone(), python27.clone(), python37.clone()]
+    } else {
+        vec![python37.clone(), python36.clone(), python27.clone()]
+    };
+    assert_eq!(list("path"), by_path);
+
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--list".to_string(),
+            "--sort".to_string(),
+            "size".to_string(),
+        ]),
+        Err(Error::IllegalArgument(
+            PathBuf::from("/path/to/py"),
+            "--list".to_string()
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_list_full() {
+    let _env_state = EnvState::new();
+    let fake_path = common::FakePath::new();
+    let python39 = fake_path.executable("python3.9", "Python 3.9.18");
+    let python311 = fake_path.executable("python3.11", "Python 3.11.4");
+    let pypy310 = fake_path.executable("pypy3.10", "Python 3.10.14 (7.3.17, Aug 28 2024)");
+    let broken38 = fake_path.executable("python3.8", "Segmentation fault");
+
+    let rows = |argv: &[&str]| {
+        let mut full_argv = vec!["/path/to/py".to_string(), "--list".to_string()];
+        full_argv.extend(argv.iter().map(ToString::to_string));
+        match Action::from_main(&full_argv) {
+            Ok(Action::ListTable(table)) => table
+                .to_string()
+                .lines()
+                .map(|line| {
+                    line.split('│')
+                        .map(|column| column.trim().to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+            result => panic!("{:?} returned {:?}", argv, result),
+        }
+    };
+    let row = |version: &str, path: &PathBuf, full_version: &str| {
+        vec![
+            version.to_string(),
+            path.display().to_string(),
+            full_version.to_string(),
+        ]
+    };
+
+    assert_eq!(
+        rows(&["--full"]),
+        vec![
+            row("3.11", &python311, "3.11.4"),
+            row("3.10", &pypy310, "3.10.14"),
+            row("3.9", &python39, "3.9.18"),
+            row("3.8", &broken38, "?"),
+        ]
+    );
+    // Composes with the other modifiers.
+    assert_eq!(
+        rows(&["--sort", "version-asc", "--full", "--impl", "cpython"]),
+        vec![
+            row("3.8", &broken38, "?"),
+            row("3.9", &python39, "3.9.18"),
+            row("3.11", &python311, "3.11.4"),
+        ]
+    );
+    // Without `--full`, nothing is run.
+    assert!(rows(&[]).iter().all(|columns| columns.len() == 2));
+
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--list".to_string(),
+            "--full".to_string(),
+            "--full".to_string(),
+        ]),
+        Err(Error::IllegalArgument(
+            PathBuf::from("/path/to/py"),
+            "--list".to_string()
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_list_impl() {
+    let fake_path = common::FakePath::new();
+    let python39 = fake_path.python(3, 9);
+    let python311 = fake_path.python(3, 11);
+    let pypy39 = fake_path.executable("pypy3.9", "Python 3.9.18");
+    let pypy310 = fake_path.executable("pypy3.10", "Python 3.10.14");
+
+    let listed_paths = |argv: &[&str]| {
+        let mut full_argv = vec!["/path/to/py".to_string()];
+        full_argv.extend(argv.iter().map(ToString::to_string));
+        match Action::from_main(&full_argv) {
+            Ok(Action::ListTable(table)) => table
+                .to_string()
+                .lines()
+                .map(|line| PathBuf::from(line.split('│').nth(1).unwrap().trim()))
+                .collect::<Vec<_>>(),
+            result => panic!("{:?} returned {:?}", argv, result),
+        }
+    };
+
+    assert_eq!(
+        listed_paths(&["--list", "--impl", "pypy"]),
+        vec![pypy310.clone(), pypy39.clone()]
+    );
+    assert_eq!(
+        listed_paths(&["--list", "--impl", "CPython"]),
+        vec![python311.clone(), python39.clone()]
+    );
+    assert_eq!(
+        listed_paths(&["--list"]),
+        vec![python311, pypy310, python39, pypy39.clone()]
+    );
+    assert_eq!(
+        listed_paths(&["-3.9", "--list", "--impl", "pypy"]),
+        vec![pypy39]
+    );
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "-3.11".to_string(),
+            "--list".to_string(),
+            "--impl".to_string(),
+            "pypy".to_string(),
+        ]),
+        Err(Error::NoExecutableFound(RequestedVersion::Exact(3, 11)))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_list_by_version() {
+    let fake_path = common::FakePath::new();
+    let python27 = fake_path.python(2, 7);
+    let python39 = fake_path.python(3, 9);
+    let python310 = fake_path.python(3, 10);
+    let python311 = fake_path.python(3, 11);
+    let list = |flags: &[&str]| {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(flags.iter().map(ToString::to_string));
+        Action::from_main(&argv)
+    };
+    let paths = |output: &str| -> Vec<PathBuf> {
+        output
+            .lines()
+            .map(|line| PathBuf::from(line.split('│').nth(1).unwrap().trim()))
+            .collect()
+    };
+
+    match list(&["-3", "--list"]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert_eq!(paths(&output), vec![python311, python310, python39.clone()])
+        }
+        _ => panic!("'-3 --list' did not return Action::ListTable"),
+    }
+    match list(&["-3.9", "--list"]) {
+        Ok(Action::ListTable(table)) => assert_eq!(paths(&table.to_string()), vec![python39]),
+        _ => panic!("'-3.9 --list' did not return Action::ListTable"),
+    }
+    match list(&["--list"]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert_eq!(paths(&output).len(), 4);
+            assert_eq!(paths(&output)[3], python27);
+        }
+        _ => panic!("'--list' did not return Action::ListTable"),
+    }
+
+    assert_eq!(
+        list(&["-4", "--list"]),
+        Err(Error::NoExecutableFound(RequestedVersion::MajorOnly(4)))
+    );
+    assert!(matches!(
+        list(&["-3", "--list", "--direnv"]),
+        Err(Error::IllegalArgument(..))
+    ));
+}
+
+#[test]
+#[serial]
+fn from_main_list_verbose() {
+    let mut env_state = EnvState::new();
+    let launcher_dir = TempDir::new().unwrap();
+    let python312 = common::touch_file(launcher_dir.path().join("python3.12"));
+    env_state.env_vars.change(
+        "PYLAUNCHER_PATH",
+        Some(launcher_dir.path().to_str().unwrap()),
+    );
+    let shadowed_python36 = env_state.python37.parent().unwrap().join("python3.6");
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list-verbose".to_string()]) {
+        Ok(Action::List(output)) => {
+            let lines: Vec<&str> = output.lines().map(str::trim).collect();
+            assert_eq!(lines.len(), 5);
+            assert!(lines[0].contains(python312.to_str().unwrap()));
+            assert!(lines[0].ends_with("PYLAUNCHER_PATH"));
+            assert!(lines[1].contains(env_state.python37.to_str().unwrap()));
+            assert!(lines[1].ends_with("PATH"));
+            assert!(!lines[1].ends_with("PYLAUNCHER_PATH"));
+            // The Python 3.6 which is chosen comes before the one it shadows.
+            assert!(lines[2].contains(env_state.python36.to_str().unwrap()));
+            assert!(lines[2].ends_with("PATH"));
+            assert!(lines[3].contains(shadowed_python36.to_str().unwrap()));
+            assert!(lines[3].ends_with("PATH (shadowed)"));
+            assert!(lines[4].contains(env_state.python27.to_str().unwrap()));
+        }
+        _ => panic!("'--list-verbose' did not return Action::List"),
+    }
+
+    assert!(matches!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--list-verbose".to_string(),
+            "--direnv".to_string()
+        ]),
+        Err(Error::IllegalArgument(..))
+    ));
+}
+
+#[test]
+#[serial]
+fn from_main_list_verbose_activated_virtual_env() {
+    let mut env_state = EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    fs::write(
+        venv_root.path().join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.11.4\n",
+    )
+    .unwrap();
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", Some(venv_root.path().to_str().unwrap()));
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list-verbose".to_string()]) {
+        Ok(Action::List(output)) => {
+            let lines: Vec<&str> = output.lines().map(str::trim).collect();
+            assert_eq!(lines.len(), 5);
+            // The virtual environment comes first, ahead of newer versions.
+            assert!(lines[0].starts_with("3.11"));
+            assert!(lines[0].contains(
+                venv_root
+                    .path()
+                    .join("bin")
+                    .join("python")
+                    .to_str()
+                    .unwrap()
+            ));
+            assert!(lines[0].ends_with("activated virtual environment (VIRTUAL_ENV)"));
+            assert!(lines[1].contains(env_state.python37.to_str().unwrap()));
+        }
+        _ => panic!("'--list-verbose' did not return Action::List"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_verbose_unversioned_virtual_env() {
+    let mut env_state = EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    // No version in `pyvenv.cfg`, so only running `python` can tell.
+    fs::write(venv_root.path().join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    let venv_row =
+        || match Action::from_main(&["/path/to/py".to_string(), "--list-verbose".to_string()]) {
+            Ok(Action::List(output)) => output
+                .lines()
+                .next()
+                .unwrap()
+                .split('│')
+                .map(|cell| cell.trim().to_string())
+                .collect::<Vec<_>>(),
+            result => panic!("'--list-verbose' returned {:?}", result),
+        };
+
+    let probed = common::fake_python(bin_dir.join("python"), "Python 3.12.1");
+    assert_eq!(
+        venv_row()[..2],
+        ["3.12".to_string(), probed.to_str().unwrap().to_string()]
+    );
+
+    // A second virtual environment, as each executable is only probed once.
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    let silent = common::fake_executable(bin_dir.join("python"), "exit 1");
+    assert_eq!(
+        venv_row()[..2],
+        ["unknown".to_string(), silent.to_str().unwrap().to_string()]
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_two_digit_minor() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python39 = common::touch_file(dir.path().join("python3.9"));
+    let python310 = common::touch_file(dir.path().join("python3.10"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python310),
+        _ => panic!("No executable found for Python 3.10"),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            // Newest first, so 3.10 is listed above 3.9.
+            assert!(
+                output.find(python310.to_str().unwrap()).unwrap()
+                    < output.find(python39.to_str().unwrap()).unwrap()
+            );
+        }
+        _ => panic!("'--list' did not return Action::ListTable"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_minimum_version() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python38 = common::touch_file(dir.path().join("python3.8"));
+    common::touch_file(dir.path().join("python3.10"));
+    let python311 = common::touch_file(dir.path().join("python3.11"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+    let args = ["/path/to/py".to_string(), "-3.8+".to_string()];
+
+    match Action::from_main(&args) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python311),
+        _ => panic!("No executable found for Python 3.8+"),
+    }
+
+    env_state.env_vars.change("PYLAUNCHER_SELECT", Some("min"));
+    match Action::from_main(&args) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python38),
+        _ => panic!("No executable found for Python 3.8+"),
+    }
+
+    // Versions older than the minimum are never chosen.
+    let args = ["/path/to/py".to_string(), "-3.9+".to_string()];
+    match Action::from_main(&args) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, dir.path().join("python3.10"))
+        }
+        _ => panic!("No executable found for Python 3.9+"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_unresolved_pyenv_shim() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let pyenv_root = TempDir::new().unwrap();
+    let shim = common::pyenv_shim(pyenv_root.path(), "python3.11", "Python 3.11.4");
+    env_state
+        .env_vars
+        .change("PATH", Some(shim.parent().unwrap().to_str().unwrap()));
+
+    // The shim is a script with its own shebang, so it can be executed.
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, shim),
+        _ => panic!("No executable found for Python 3.11"),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert!(output.contains(&format!("{} (pyenv shim)", shim.display())))
+        }
+        _ => panic!("'--list' did not return Action::ListTable"),
     }
 }
 
@@ -68,7 +619,7 @@ fn from_main_by_flag() {
     let _working_dir = common::CurrentDir::new();
     let env_state = common::EnvState::new();
     let launcher_location = "/path/to/py".to_string();
-    let no_argv = Action::from_main(&[launcher_location.clone()]);
+    let no_argv = Action::from_main(std::slice::from_ref(&launcher_location));
 
     match no_argv {
         Ok(Action::Execute {
@@ -82,6 +633,13 @@ fn from_main_by_flag() {
         }
         Ok(Action::Help(_, _)) => panic!("Got back help"),
         Ok(Action::List(_)) => panic!("Got back a list of executables"),
+        Ok(Action::ListTable(_)) => panic!("Got back a table of executables"),
+        Ok(Action::Precedence(_)) => panic!("Got back the precedence"),
+        Ok(Action::Complete(_)) => panic!("Got back a completion script"),
+        Ok(Action::CheckConfig(_)) => panic!("Got back configuration problems"),
+        Ok(Action::Info(_)) => panic!("Got back the info report"),
+        Ok(Action::Version(_)) => panic!("Got back the launcher version"),
+        Ok(Action::ShowCommand(_)) => panic!("Got back the command line"),
         Err(error) => panic!("No executable found in default case: {:?}", error),
     }
 
@@ -134,7 +692,7 @@ fn from_main_by_flag() {
 fn from_main_activated_virtual_env() {
     let venv_path = "/path/to/venv";
     let mut env_state = common::EnvState::new();
-    env_state.env_vars.change("VIRTUAL_ENV", Some(&venv_path));
+    env_state.env_vars.change("VIRTUAL_ENV", Some(venv_path));
 
     match Action::from_main(&["/path/to/py".to_string()]) {
         Ok(Action::Execute { executable, .. }) => {
@@ -155,6 +713,161 @@ fn from_main_activated_virtual_env() {
     }
 }
 
+#[test]
+#[serial]
+fn from_main_activated_virtual_env_shebang() {
+    let mut env_state = common::EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let venv_python = common::touch_file(bin_dir.join("python"));
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    let script_path = venv_root.path().join("script.py");
+    let script = script_path.to_str().unwrap().to_string();
+    let run = |shebang: &str| {
+        fs::write(&script_path, shebang).unwrap();
+        match Action::from_main(&["/path/to/py".to_string(), script.clone()]) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => (executable, args),
+            result => panic!("{:?} returned {:?}", shebang, result),
+        }
+    };
+
+    // Without a recorded version, the environment is always used.
+    assert_eq!(
+        run("#! /usr/bin/env python3.6 -u\n"),
+        (venv_python.clone(), vec!["-u".to_string(), script.clone()])
+    );
+
+    fs::write(venv_root.path().join("pyvenv.cfg"), "version = 3.7.4\n").unwrap();
+    for shebang in [
+        "#! /usr/bin/env python -u\n",
+        "#! /usr/bin/python3 -u\n",
+        "#! /usr/bin/env python3.7 -u\n",
+    ] {
+        assert_eq!(
+            run(shebang),
+            (venv_python.clone(), vec!["-u".to_string(), script.clone()]),
+            "{:?}",
+            shebang
+        );
+    }
+    assert_eq!(
+        run("print('no shebang')\n"),
+        (venv_python.clone(), vec![script.clone()])
+    );
+    // A version the environment doesn't provide is searched for instead.
+    assert_eq!(
+        run("#! /usr/bin/env python3.6 -u\n"),
+        (
+            env_state.python36.clone(),
+            vec!["-u".to_string(), script.clone()]
+        )
+    );
+    assert_eq!(
+        run("#! /usr/bin/python2\n"),
+        (env_state.python27.clone(), vec![script.clone()])
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_activated_conda_env() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    let conda_prefix = TempDir::new().unwrap();
+    env_state
+        .env_vars
+        .change("CONDA_PREFIX", conda_prefix.path().to_str());
+
+    // Without an interpreter the Conda environment is skipped.
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found with an empty `CONDA_PREFIX`"),
+    }
+
+    fs::create_dir(conda_prefix.path().join("bin")).unwrap();
+    let conda_python = common::touch_file(conda_prefix.path().join("bin").join("python"));
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, conda_python),
+        _ => panic!("No executable found in `CONDA_PREFIX` case"),
+    }
+
+    // CONDA_PREFIX gets ignored if any specific version is requested.
+    match Action::from_main(&["/path/to/py".to_string(), "-3".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found in `CONDA_PREFIX` case"),
+    }
+
+    // An activated virtual environment takes precedence.
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", Some("/path/to/venv"));
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, PathBuf::from("/path/to/venv/bin/python"))
+        }
+        _ => panic!("No executable found in `VIRTUAL_ENV` case"),
+    }
+
+    env_state.env_vars.change("CONDA_PREFIX", None);
+    env_state.env_vars.change("VIRTUAL_ENV", None);
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found once `CONDA_PREFIX` is unset"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_activated_virtual_env_python3_only() {
+    let mut env_state = common::EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let python3 = common::touch_file(bin_dir.join("python3"));
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python3),
+        _ => panic!("No executable found in `VIRTUAL_ENV` with only `bin/python3`"),
+    }
+
+    // `bin/python` is preferred when it exists.
+    let python = common::touch_file(bin_dir.join("python"));
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python),
+        _ => panic!("No executable found in `VIRTUAL_ENV` with `bin/python`"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_activated_virtual_env_windows_layout() {
+    let mut env_state = common::EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    let scripts_dir = venv_root.path().join("Scripts");
+    fs::create_dir(&scripts_dir).unwrap();
+    common::touch_file(scripts_dir.join("python.exe"));
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+
+    // The (missing) Unix executable is still chosen so its absence is reported.
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, venv_root.path().join("bin").join("python"))
+        }
+        _ => panic!("No executable found in Windows-style `VIRTUAL_ENV`"),
+    }
+}
+
 #[test]
 #[serial]
 fn from_main_default_cwd_venv_path() {
@@ -261,7 +974,7 @@ fn from_main_env_var() {
     env_state.env_vars.change("PY_PYTHON", Some("3.6"));
     let launcher_location = "/path/to/py".to_string();
 
-    match Action::from_main(&[launcher_location.clone()]) {
+    match Action::from_main(std::slice::from_ref(&launcher_location)) {
         Ok(Action::Execute {
             launcher_path,
             executable,
@@ -292,10 +1005,1900 @@ fn from_main_env_var() {
 
 #[test]
 #[serial]
-fn from_main_no_executable_found() {
+fn from_main_env_var_normalized() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    env_state.env_vars.change("PY_PYTHON", Some("Python3.6"));
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python36),
+        _ => panic!("No executable found in normalized PY_PYTHON case"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_env_var_preference_list() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    // Python 3.12 isn't installed, so the next preference is used.
+    env_state.env_vars.change("PY_PYTHON", Some("3.12,3.6,3.7"));
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python36),
+        _ => panic!("No executable found in PY_PYTHON list case"),
+    }
+
+    env_state
+        .env_vars
+        .change("PY_PYTHON3", Some("3.12,bogus,3.6"));
+
+    match Action::from_main(&["/path/to/py".to_string(), "-3".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python36),
+        _ => panic!("No executable found in PY_PYTHON3 list case"),
+    }
+
+    env_state.env_vars.change("PY_PYTHON", Some("3.12,3.11"));
+
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string()])
+            .unwrap_err()
+            .to_string(),
+        Action::from_main(&["/path/to/py".to_string(), "-3.12".to_string()])
+            .unwrap_err()
+            .to_string()
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_no_matching_executable_message() {
     let _env_state = common::EnvState::new();
+    let error = Action::from_main(&["/path/to/py".to_string(), "-3.12".to_string()]).unwrap_err();
     assert_eq!(
-        Action::from_main(&["/path/to/py".to_string(), "-42.13".to_string()]),
-        Err(Error::NoExecutableFound(RequestedVersion::Exact(42, 13)))
+        error.to_string(),
+        "Requested Python 3.12 not found. Available: 2.7, 3.6, 3.7"
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_nothing_installed() {
+    let _env_state = EnvVarState::empty();
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-3.12".to_string()]),
+        Err(Error::NoExecutableFound(RequestedVersion::Exact(3, 12)))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_no_executable_found() {
+    let _env_state = common::EnvState::new();
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-42.13".to_string()]),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(42, 13),
+            vec![
+                ExactVersion::new(2, 7),
+                ExactVersion::new(3, 6),
+                ExactVersion::new(3, 7)
+            ]
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_config_default_version() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    env_state.write_config("default-version = \"3.6\"");
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, env_state.python36);
+        }
+        _ => panic!("No executable found in config file case"),
+    }
+
+    // An explicit version flag takes precedence over the config file.
+    match Action::from_main(&["/path/to/py".to_string(), "-2".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, env_state.python27);
+        }
+        _ => panic!("No executable found in config file case"),
+    }
+
+    // PY_PYTHON takes precedence over the config file.
+    env_state.env_vars.change("PY_PYTHON", Some("2.7"));
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, env_state.python27);
+        }
+        _ => panic!("No executable found in config file case"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_malformed_config() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    env_state.write_config("default-version = [");
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, env_state.python37);
+        }
+        _ => panic!("No executable found with a malformed config file"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_precedence() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+
+    match Action::from_main(&["/path/to/py".to_string(), "--precedence".to_string()]) {
+        Ok(Action::Precedence(output)) => {
+            assert!(output.contains("[ ] PYLAUNCHER_PYTHON"));
+            assert!(output.contains("[ ] Activated virtual environment"));
+            assert!(output.contains("[ ] Activated Conda environment"));
+            assert!(output.contains("[ ] `.venv` directory"));
+            assert!(output.contains("[ ] `.python-version` file"));
+            assert!(output.contains("[ ] PY_PYTHON"));
+            assert!(output.contains("[ ] `default-version`"));
+            assert!(output.ends_with(&format!(
+                "[x] Python executable on PATH: {}\n",
+                env_state.python37.display()
+            )));
+            // No script is involved, so its shebang isn't checked.
+            assert!(!output.contains("Shebang"));
+        }
+        _ => panic!("'--precedence' did not return Action::Precedence"),
+    }
+
+    env_state.env_vars.change("PY_PYTHON", Some("3.6"));
+    env_state.write_config("default-version = \"2.7\"");
+
+    match Action::from_main(&["/path/to/py".to_string(), "--precedence".to_string()]) {
+        Ok(Action::Precedence(output)) => {
+            assert!(output.contains("[x] PY_PYTHON environment variable: Python 3.6"));
+            // PY_PYTHON replaces the default, so the config file isn't checked.
+            assert!(!output.contains("`default-version`"));
+            assert!(output.ends_with(&format!(
+                "[x] Python 3.6 executable on PATH: {}\n",
+                env_state.python36.display()
+            )));
+        }
+        _ => panic!("'--precedence' did not return Action::Precedence"),
+    }
+
+    env_state.env_vars.change("PY_PYTHON", None);
+
+    match Action::from_main(&["/path/to/py".to_string(), "--precedence".to_string()]) {
+        Ok(Action::Precedence(output)) => {
+            assert!(output.contains("[x] `default-version` in the config file: Python 2.7"));
+            assert!(output.ends_with(&format!(
+                "[x] Python 2.7 executable on PATH: {}\n",
+                env_state.python27.display()
+            )));
+        }
+        _ => panic!("'--precedence' did not return Action::Precedence"),
+    }
+
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", Some("/path/to/venv"));
+
+    match Action::from_main(&["/path/to/py".to_string(), "--precedence".to_string()]) {
+        Ok(Action::Precedence(output)) => {
+            // The activated virtual environment decides, so nothing after it
+            // is checked.
+            assert!(output.ends_with(
+                "[x] Activated virtual environment (VIRTUAL_ENV): /path/to/venv/bin/python\n"
+            ));
+            assert!(!output.contains("Conda"));
+            assert!(!output.contains("PATH:"));
+        }
+        _ => panic!("'--precedence' did not return Action::Precedence"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_pypy() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python39 = common::touch_file(dir.path().join("python3.9"));
+    let pypy39 = common::touch_file(dir.path().join("pypy3.9"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    match Action::from_main(&["/path/to/py".to_string(), "-3.9".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python39),
+        _ => panic!("No executable found in `-3.9` case"),
+    }
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--pypy".to_string(),
+        "-3.9".to_string(),
+        "-c".to_string(),
+        "pass".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, pypy39);
+            assert_eq!(args, ["-c".to_string(), "pass".to_string()]);
+        }
+        _ => panic!("No executable found in `--pypy -3.9` case"),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--pypy".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, pypy39),
+        _ => panic!("No executable found in `--pypy` case"),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert!(output.contains(python39.to_str().unwrap()));
+            assert!(output.contains(pypy39.to_str().unwrap()));
+        }
+        _ => panic!("'--list' did not return Action::ListTable"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_shebang_fallback() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = common::EnvState::new();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shebang.py");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "#! /usr/bin/env python3.4").unwrap();
+    let argv = [
+        "/path/to/py".to_string(),
+        file_path.to_str().unwrap().to_string(),
+    ];
+
+    // By default, the shebang's version must be found.
+    assert_eq!(
+        Action::from_main(&argv),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 4),
+            vec![
+                ExactVersion::new(2, 7),
+                ExactVersion::new(3, 6),
+                ExactVersion::new(3, 7)
+            ]
+        ))
+    );
+
+    env_state
+        .env_vars
+        .change("PY_SHEBANG_FALLBACK", Some("default"));
+    match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => {
+            assert_eq!(executable, env_state.python37);
+        }
+        _ => panic!("No executable found in shebang fallback case"),
+    }
+
+    // An explicitly requested version never falls back.
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-3.4".to_string()]),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 4),
+            vec![
+                ExactVersion::new(2, 7),
+                ExactVersion::new(3, 6),
+                ExactVersion::new(3, 7)
+            ]
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_shebang_python2_unavailable() {
+    let mut fake_path = common::FakePath::new();
+    fake_path.python(3, 6);
+    let python311 = fake_path.python(3, 11);
+    let script = fake_path.dir.path().join("legacy.py");
+    fs::write(&script, "#!/usr/bin/python2\nprint 'hello'\n").unwrap();
+    let exact_script = fake_path.dir.path().join("legacy27.py");
+    fs::write(&exact_script, "#!/usr/bin/env python2.7 -u\n").unwrap();
+    let argv = |script: &Path| ["/path/to/py".to_string(), script.display().to_string()];
+
+    assert_eq!(
+        Action::from_main(&argv(&script)),
+        Err(Error::Python2Unavailable(
+            script.clone(),
+            RequestedVersion::MajorOnly(2)
+        ))
+    );
+    assert_eq!(
+        Action::from_main(&argv(&exact_script)),
+        Err(Error::Python2Unavailable(
+            exact_script.clone(),
+            RequestedVersion::Exact(2, 7)
+        ))
+    );
+
+    fake_path
+        .env_vars
+        .change("PY_SHEBANG_UPGRADE_PYTHON2", Some("1"));
+    match Action::from_main(&argv(&script)) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python311),
+        _ => panic!("Python 2 shebang was not upgraded to Python 3"),
+    }
+    match Action::from_main(&argv(&exact_script)) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, python311);
+            assert_eq!(args[0], "-u");
+        }
+        _ => panic!("Python 2.7 shebang was not upgraded to Python 3"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_python_version_file() {
+    let working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let project = working_dir.dir.path().join("project");
+    let nested = project.join("src");
+    fs::create_dir_all(&nested).unwrap();
+    env::set_current_dir(&nested).unwrap();
+    let version_file = project.join(".python-version");
+    let argv = ["/path/to/py".to_string()];
+
+    // File absent.
+    match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found without a .python-version file"),
+    }
+
+    // File found in a parent directory, taking precedence over PY_PYTHON.
+    fs::write(&version_file, "3.6.15\n").unwrap();
+    env_state.env_vars.change("PY_PYTHON", Some("3.7"));
+    match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python36),
+        _ => panic!("No executable found for the .python-version file"),
+    }
+    // An explicit version flag still wins.
+    match Action::from_main(&["/path/to/py".to_string(), "-2".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python27),
+        _ => panic!("No executable found for -2"),
+    }
+    env_state.env_vars.change("PY_PYTHON", None);
+
+    // Malformed contents are ignored.
+    fs::write(&version_file, "system\n").unwrap();
+    match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found with a malformed .python-version file"),
+    }
+
+    // The search stops at the home directory.
+    fs::write(&version_file, "3.6\n").unwrap();
+    env_state.env_vars.change("HOME", nested.to_str());
+    match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python37),
+        _ => panic!("No executable found when .python-version is above the home directory"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_pyproject_version() {
+    let working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let project = working_dir.dir.path().join("project");
+    let nested = project.join("packages").join("sub");
+    fs::create_dir_all(&nested).unwrap();
+    env::set_current_dir(&nested).unwrap();
+    let pyproject = project.join("pyproject.toml");
+    let argv = ["/path/to/py".to_string()];
+    let chosen = || match Action::from_main(&argv) {
+        Ok(Action::Execute { executable, .. }) => executable,
+        result => panic!("No executable found: {:?}", result),
+    };
+
+    // Missing key.
+    fs::write(
+        &pyproject,
+        "[project]\nname = \"spam\"\nrequires-python = \">=3.6\"\n",
+    )
+    .unwrap();
+    assert_eq!(chosen(), env_state.python37);
+
+    // Found in a parent directory, past a nested `pyproject.toml` without the
+    // key, and taking precedence over PY_PYTHON.
+    fs::write(
+        &pyproject,
+        "[project]\nname = \"spam\"\n\n[tool.py]\nversion = \"3.6\"\n",
+    )
+    .unwrap();
+    fs::write(nested.join("pyproject.toml"), "[project]\nname = \"sub\"\n").unwrap();
+    env_state.env_vars.change("PY_PYTHON", Some("3.7"));
+    assert_eq!(chosen(), env_state.python36);
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::Exact(3, 6)
+    );
+
+    // A `.python-version` file takes precedence.
+    fs::write(nested.join(".python-version"), "3.7\n").unwrap();
+    assert_eq!(chosen(), env_state.python37);
+    fs::remove_file(nested.join(".python-version")).unwrap();
+    env_state.env_vars.change("PY_PYTHON", None);
+
+    // An explicit version flag still wins.
+    match Action::from_main(&["/path/to/py".to_string(), "-2".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python27),
+        _ => panic!("No executable found for -2"),
+    }
+
+    let (_, trace) = cli::resolve_with_trace(RequestedVersion::Any, &SearchDirectories::default());
+    assert!(trace.contains(&cli::ResolutionStep::PyprojectVersion(Some((
+        pyproject.clone(),
+        RequestedVersion::Exact(3, 6)
+    )))));
+
+    // The search stops at the home directory.
+    env_state.env_vars.change("HOME", nested.to_str());
+    assert_eq!(chosen(), env_state.python37);
+}
+
+#[test]
+#[serial]
+fn from_main_stdin_dash() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = common::EnvState::new();
+
+    match Action::from_main(&["/path/to/py".to_string(), "-".to_string()]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python37);
+            assert_eq!(args, ["-".to_string()]);
+        }
+        _ => panic!("No executable found in `-` case"),
+    }
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "-3.6".to_string(),
+        "-".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python36);
+            assert_eq!(args, ["-".to_string()]);
+        }
+        _ => panic!("No executable found in `-3.6 -` case"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_shebang_args() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = common::EnvState::new();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file_path = temp_dir.path().join("shebang.py");
+    let mut file = File::create(&file_path).unwrap();
+    writeln!(file, "#!/usr/bin/env python3 -S -E").unwrap();
+    let script = file_path.to_str().unwrap().to_string();
+
+    match Action::from_main(&["/path/to/py".to_string(), script.clone(), "-v".to_string()]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python37);
+            assert_eq!(
+                args,
+                [
+                    "-S".to_string(),
+                    "-E".to_string(),
+                    script.clone(),
+                    "-v".to_string()
+                ]
+            );
+        }
+        _ => panic!("No executable found in shebang arguments case"),
+    }
+
+    // No shebang arguments means the arguments are passed through as-is.
+    let plain_path = temp_dir.path().join("plain.py");
+    let mut plain_file = File::create(&plain_path).unwrap();
+    writeln!(plain_file, "#!/usr/bin/env python3").unwrap();
+    let plain_script = plain_path.to_str().unwrap().to_string();
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        plain_script.clone(),
+        "-v".to_string(),
+    ]) {
+        Ok(Action::Execute { args, .. }) => {
+            assert_eq!(args, [plain_script, "-v".to_string()]);
+        }
+        _ => panic!("No executable found in shebang without arguments case"),
+    }
+
+    // An explicit version means the shebang isn't used.
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "-3.6".to_string(),
+        script.clone(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python36);
+            assert_eq!(args, [script]);
+        }
+        _ => panic!("No executable found in explicit version with shebang arguments case"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_direnv() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = common::EnvState::new();
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--list".to_string(),
+        "--direnv".to_string(),
+    ]) {
+        Ok(Action::List(output)) => {
+            assert_eq!(
+                output,
+                format!(
+                    "export PY_PYTHON=3.7\nPATH_add '{}'\n",
+                    env_state.python37.parent().unwrap().display()
+                )
+            );
+        }
+        _ => panic!("'--list --direnv' did not return Action::List"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_check_config() {
+    let mut env_state = EnvState::new();
+
+    match Action::from_main(&["/path/to/py".to_string(), "--check-config".to_string()]) {
+        Ok(Action::CheckConfig(problems)) => assert!(problems.is_empty()),
+        _ => panic!("'--check-config' did not return Action::CheckConfig"),
+    }
+
+    env_state.env_vars.change("PY_PYTHON3", Some("three"));
+    env_state.env_vars.change("PY_RESOLVE_RETRY", Some("-1"));
+    env_state.write_config(
+        "default-version = \"3.x\"\nextra-paths = [\"/path/to/nowhere\"]\naliases = {}",
+    );
+
+    match Action::from_main(&["/path/to/py".to_string(), "--check-config".to_string()]) {
+        Ok(Action::CheckConfig(problems)) => {
+            assert_eq!(problems.len(), 5);
+            assert_eq!(problems[0], "Invalid PY_PYTHON3 value \"three\"");
+            assert_eq!(problems[1], "Invalid PY_RESOLVE_RETRY value \"-1\"");
+            assert!(problems[2].starts_with("Unknown key \"aliases\""));
+            assert!(problems[3].starts_with("Invalid default-version \"3.x\""));
+            assert_eq!(
+                problems[4],
+                "extra-paths directory /path/to/nowhere does not exist"
+            );
+        }
+        _ => panic!("'--check-config' did not return Action::CheckConfig"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_info() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+
+    let report = match Action::from_main(&["/path/to/py".to_string(), "--info".to_string()]) {
+        Ok(Action::Info(report)) => report,
+        _ => panic!("'--info' did not return Action::Info"),
+    };
+    let info: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+    let default_executable = match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => executable,
+        _ => panic!("No executable found in default case"),
+    };
+    assert_eq!(info["chosen"], default_executable.to_str().unwrap());
+    assert_eq!(info["default_request"], "*");
+    assert!(info["virtual_env"].is_null());
+    assert_eq!(info["path"].as_array().unwrap().len(), 2);
+    assert_eq!(info["path"][0]["source"], "PATH");
+
+    let interpreters = info["interpreters"].as_array().unwrap();
+    assert_eq!(interpreters.len(), 3);
+    assert_eq!(interpreters[0]["implementation"], "CPython");
+    assert_eq!(interpreters[0]["version"], "3.7");
+    assert_eq!(
+        interpreters[0]["path"],
+        env_state.python37.to_str().unwrap()
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_default() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let default = || match Action::from_main(&["/path/to/py".to_string(), "--default".to_string()])
+    {
+        Ok(Action::List(output)) => output,
+        result => panic!("'--default' returned {:?}", result),
+    };
+
+    assert_eq!(
+        default(),
+        format!("3.7\t{}\n", env_state.python37.display())
+    );
+
+    env_state.env_vars.change("PY_PYTHON", Some("3.6"));
+    assert_eq!(
+        default(),
+        format!("3.6\t{}\n", env_state.python36.display())
+    );
+
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let venv_python = common::touch_file(bin_dir.join("python"));
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    // Without a `pyvenv.cfg`, the version is unknown.
+    assert_eq!(default(), format!("?\t{}\n", venv_python.display()));
+    fs::write(venv_root.path().join("pyvenv.cfg"), "version = 3.11.4\n").unwrap();
+    assert_eq!(default(), format!("3.11\t{}\n", venv_python.display()));
+
+    env_state.env_vars.change("VIRTUAL_ENV", None);
+    env_state.env_vars.change("PY_PYTHON", None);
+    env_state.env_vars.change("PATH", Some(""));
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "--default".to_string()]),
+        Err(Error::NoExecutableFound(RequestedVersion::Any))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_python2() {
+    let mut env_state = EnvState::new();
+    let execute = |flag: &str| {
+        Action::from_main(&["/path/to/py".to_string(), flag.to_string()]).map(|action| match action
+        {
+            Action::Execute { executable, .. } => executable,
+            action => panic!("{:?} returned {:?}", flag, action),
+        })
+    };
+
+    assert_eq!(execute("-2"), Ok(env_state.python27.clone()));
+    assert_eq!(execute("-2.7"), Ok(env_state.python27.clone()));
+
+    let dir2 = env_state
+        .python37
+        .parent()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    env_state.env_vars.change("PATH", Some(&dir2));
+    for (flag, requested) in [
+        ("-2", RequestedVersion::MajorOnly(2)),
+        ("-2.7", RequestedVersion::Exact(2, 7)),
+    ] {
+        assert_eq!(
+            execute(flag),
+            Err(Error::Python2NotInstalled(requested)),
+            "{}",
+            flag
+        );
+    }
+    // Only Python 2 requests get the specific error.
+    assert!(matches!(
+        execute("-4"),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::MajorOnly(4),
+            _
+        ))
+    ));
+}
+
+#[test]
+#[serial]
+fn from_main_resolve_cached() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let cache_home = TempDir::new().unwrap();
+    env_state
+        .env_vars
+        .change("XDG_CACHE_HOME", cache_home.path().to_str());
+    let resolve_cached =
+        || match Action::from_main(&["/path/to/py".to_string(), "--resolve-cached".to_string()]) {
+            Ok(Action::List(output)) => output,
+            result => panic!("'--resolve-cached' returned {:?}", result),
+        };
+    let cache_dir = cache_home.path().join("py").join("resolved");
+    let entries = || -> Vec<PathBuf> {
+        fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect()
+    };
+
+    let expected = format!("3.7\t{}\n", env_state.python37.display());
+    assert_eq!(resolve_cached(), expected);
+    let cached = entries();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(fs::read_to_string(&cached[0]).unwrap(), expected);
+
+    // With nothing changed, the cached entry is used as-is.
+    let planted = format!("3.6\t{}\n", env_state.python36.display());
+    fs::write(&cached[0], &planted).unwrap();
+    assert_eq!(resolve_cached(), planted);
+
+    // Activating a virtual environment is a miss.
+    let venv_root = TempDir::new().unwrap();
+    let bin_dir = venv_root.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let venv_python = common::touch_file(bin_dir.join("python"));
+    fs::write(venv_root.path().join("pyvenv.cfg"), "version = 3.11.4\n").unwrap();
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    assert_eq!(
+        resolve_cached(),
+        format!("3.11\t{}\n", venv_python.display())
+    );
+    assert_eq!(entries().len(), 2);
+
+    // As is the cached executable going missing.
+    env_state.env_vars.change("VIRTUAL_ENV", None);
+    fs::write(&cached[0], "3.6\t/does/not/exist\n").unwrap();
+    assert_eq!(resolve_cached(), expected);
+    assert_eq!(fs::read_to_string(&cached[0]).unwrap(), expected);
+}
+
+#[test]
+#[serial]
+fn resolve_with_trace() {
+    use cli::ResolutionStep;
+
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+
+    assert_eq!(
+        cli::resolve_with_trace(RequestedVersion::Any, &SearchDirectories::default()),
+        (
+            Some(env_state.python37.clone()),
+            vec![
+                ResolutionStep::ActivatedVirtualEnv(None),
+                ResolutionStep::ActivatedCondaEnv(None),
+                ResolutionStep::VenvDirectory(None),
+                ResolutionStep::PythonVersionFile(None),
+                ResolutionStep::PyprojectVersion(None),
+                ResolutionStep::EnvVar("PY_PYTHON".to_string(), Vec::new()),
+                ResolutionStep::ConfigDefault(None),
+                ResolutionStep::PathScan(RequestedVersion::Any, Some(env_state.python37.clone())),
+            ]
+        )
+    );
+
+    env_state.env_vars.change("PY_PYTHON3", Some("3.12,3.6"));
+    assert_eq!(
+        cli::resolve_with_trace(
+            RequestedVersion::MajorOnly(3),
+            &SearchDirectories::default()
+        ),
+        (
+            Some(env_state.python36.clone()),
+            vec![
+                ResolutionStep::EnvVar(
+                    "PY_PYTHON3".to_string(),
+                    vec![
+                        RequestedVersion::Exact(3, 12),
+                        RequestedVersion::Exact(3, 6)
+                    ]
+                ),
+                ResolutionStep::PathScan(RequestedVersion::Exact(3, 12), None),
+                ResolutionStep::PathScan(
+                    RequestedVersion::Exact(3, 6),
+                    Some(env_state.python36.clone())
+                ),
+            ]
+        )
+    );
+
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", Some("/path/to/venv"));
+    let (executable, trace) =
+        cli::resolve_with_trace(RequestedVersion::Any, &SearchDirectories::default());
+    assert_eq!(executable, Some(PathBuf::from("/path/to/venv/bin/python")));
+    assert_eq!(
+        trace,
+        vec![ResolutionStep::ActivatedVirtualEnv(Some(PathBuf::from(
+            "/path/to/venv/bin/python"
+        )))]
+    );
+    assert_eq!(
+        trace[0].to_string(),
+        "Activated virtual environment: /path/to/venv/bin/python"
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_spec() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python311 = common::touch_file(dir.path().join("python3.11"));
+    let pypy39 = common::touch_file(dir.path().join("pypy3.9"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    for (argv, expected) in [
+        (vec!["-cpython@3.11"], &python311),
+        (vec!["-pypy@3.9"], &pypy39),
+        (vec!["--spec", "pypy@3"], &pypy39),
+        (vec!["--spec", "3.11"], &python311),
+    ] {
+        let mut full_argv = vec!["/path/to/py".to_string()];
+        full_argv.extend(argv.iter().map(ToString::to_string));
+        full_argv.push("-c".to_string());
+        match Action::from_main(&full_argv) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => {
+                assert_eq!(&executable, expected);
+                assert_eq!(args, vec!["-c".to_string()]);
+            }
+            result => panic!("{:?} returned {:?}", argv, result),
+        }
+    }
+
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-pypy@3.11".to_string()]),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 11),
+            vec![ExactVersion::new(3, 9)]
+        ))
+    );
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-jython@2.7".to_string()]),
+        Err(Error::UnknownImplementation("jython".to_string()))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_where() {
+    let env_state = EnvState::new();
+    let shadowed_python36 = env_state.python37.parent().unwrap().join("python3.6");
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--where".to_string(),
+        "3.6".to_string(),
+    ]) {
+        Ok(Action::List(output)) => assert_eq!(
+            output,
+            format!(
+                "{}\n{}\n",
+                env_state.python36.display(),
+                shadowed_python36.display()
+            )
+        ),
+        _ => panic!("'--where 3.6' did not return Action::List"),
+    }
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--where".to_string(),
+        "3".to_string(),
+    ]) {
+        Ok(Action::List(output)) => assert_eq!(
+            output,
+            format!(
+                "{}\n{}\n{}\n",
+                env_state.python36.display(),
+                env_state.python37.display(),
+                shadowed_python36.display()
+            )
+        ),
+        _ => panic!("'--where 3' did not return Action::List"),
+    }
+
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--where".to_string(),
+            "3.12".to_string(),
+        ]),
+        Err(Error::NoExecutableFound(RequestedVersion::Exact(3, 12)))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_venv_base() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+    let venv_root = TempDir::new().unwrap();
+    fs::write(
+        venv_root.path().join("pyvenv.cfg"),
+        "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.6.15\n",
+    )
+    .unwrap();
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--venv-base".to_string(),
+        venv_root.path().to_str().unwrap().to_string(),
+        "-m".to_string(),
+        "venv".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python36);
+            assert_eq!(args, vec!["-m".to_string(), "venv".to_string()]);
+        }
+        _ => panic!("No executable found for the virtual environment's base"),
+    }
+
+    let no_venv = TempDir::new().unwrap();
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--venv-base".to_string(),
+            no_venv.path().to_str().unwrap().to_string(),
+        ]),
+        Err(Error::InvalidPyvenvCfg(no_venv.path().join("pyvenv.cfg")))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_separator() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--".to_string(),
+        "-3.9".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python37);
+            assert_eq!(args, vec!["-3.9".to_string()]);
+        }
+        _ => panic!("No executable found in `py -- -3.9` case"),
+    }
+
+    let dir = TempDir::new().unwrap();
+    let python311 = common::touch_file(dir.path().join("python3.11"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "-3.11".to_string(),
+        "--".to_string(),
+        "script.py".to_string(),
+        "--list".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, python311);
+            assert_eq!(args, vec!["script.py".to_string(), "--list".to_string()]);
+        }
+        _ => panic!("No executable found in `py -3.11 -- script.py --list` case"),
+    }
+
+    // Only a `--` in place of a launcher argument is consumed.
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "-3.11".to_string(),
+        "script.py".to_string(),
+        "--".to_string(),
+        "--list".to_string(),
+    ]) {
+        Ok(Action::Execute { args, .. }) => {
+            assert_eq!(
+                args,
+                vec![
+                    "script.py".to_string(),
+                    "--".to_string(),
+                    "--list".to_string()
+                ]
+            );
+        }
+        _ => panic!("No executable found in `py -3.11 script.py -- --list` case"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_explicit_python() {
+    let _working_dir = common::CurrentDir::new();
+    let _env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python = common::fake_python(dir.path().join("python"), "Python 3.12.0");
+    let script_path = dir.path().join("script.py");
+    fs::write(&script_path, "#! /usr/bin/env python2.7 -u\n").unwrap();
+    let script = script_path.to_str().unwrap().to_string();
+
+    // The shebang's version is ignored, but not its arguments.
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--python".to_string(),
+        python.to_str().unwrap().to_string(),
+        script.clone(),
+        "--verbose".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, python);
+            assert_eq!(
+                args,
+                vec!["-u".to_string(), script, "--verbose".to_string()]
+            );
+        }
+        _ => panic!("'--python' did not return Action::Execute"),
+    }
+
+    // Not executable.
+    let not_executable = common::touch_file(dir.path().join("python3.12"));
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--python".to_string(),
+            not_executable.to_str().unwrap().to_string(),
+        ]),
+        Err(Error::NotExecutable(not_executable))
+    );
+
+    // Not a file.
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--python".to_string(),
+            dir.path().to_str().unwrap().to_string(),
+        ]),
+        Err(Error::NotExecutable(dir.path().to_path_buf()))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_python_override() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python = common::fake_python(dir.path().join("python"), "Python 3.12.0");
+    let script_path = dir.path().join("script.py");
+    fs::write(&script_path, "#! /usr/bin/env python2.7 -u\n").unwrap();
+    let script = script_path.to_str().unwrap().to_string();
+    env_state
+        .env_vars
+        .change("PYLAUNCHER_PYTHON", Some(python.to_str().unwrap()));
+    // Neither an activated virtual environment nor PY_PYTHON is consulted.
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", Some("/path/to/venv"));
+    env_state.env_vars.change("PY_PYTHON", Some("3.6"));
+
+    for argv in [
+        vec![script.clone()],
+        vec!["-2.7".to_string(), script.clone()],
+        vec!["--pypy".to_string(), script.clone()],
+    ] {
+        let mut full_argv = vec!["/path/to/py".to_string()];
+        full_argv.extend(argv.clone());
+        match Action::from_main(&full_argv) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => {
+                assert_eq!(executable, python, "{:?}", argv);
+                assert_eq!(args, vec!["-u".to_string(), script.clone()], "{:?}", argv);
+            }
+            _ => panic!("{:?} did not return Action::Execute", argv),
+        }
+    }
+
+    // `--python` takes precedence.
+    let other_python = common::fake_python(dir.path().join("python3"), "Python 3.11.0");
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--python".to_string(),
+        other_python.to_str().unwrap().to_string(),
+    ]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, other_python),
+        _ => panic!("'--python' did not return Action::Execute"),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--precedence".to_string()]) {
+        Ok(Action::Precedence(output)) => assert!(output.contains(&format!(
+            "[x] PYLAUNCHER_PYTHON environment variable: {}",
+            python.display()
+        ))),
+        _ => panic!("'--precedence' did not return Action::Precedence"),
+    }
+
+    // Not executable.
+    let not_executable = common::touch_file(dir.path().join("python3.12"));
+    env_state
+        .env_vars
+        .change("PYLAUNCHER_PYTHON", Some(not_executable.to_str().unwrap()));
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string()]),
+        Err(Error::NotExecutable(not_executable))
+    );
+
+    // Empty is the same as unset.
+    env_state.env_vars.change("PYLAUNCHER_PYTHON", Some(""));
+    env_state.env_vars.change("VIRTUAL_ENV", None);
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, env_state.python36),
+        _ => panic!("No executable found with an empty PYLAUNCHER_PYTHON"),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_version_flag_position() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+    let from_main = |args: &[&str]| {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(args.iter().map(ToString::to_string));
+        Action::from_main(&argv)
+    };
+    let execute = |args: &[&str]| match from_main(args) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => (executable, args),
+        result => panic!("{:?} returned {:?}", args, result),
+    };
+
+    // First, or right after launcher-wide flags.
+    assert_eq!(
+        execute(&["-3.6", "-u", "script.py"]),
+        (
+            env_state.python36.clone(),
+            vec!["-u".to_string(), "script.py".to_string()]
+        )
+    );
+    assert_eq!(execute(&["--quiet", "-3.6"]).0, env_state.python36);
+    assert_eq!(execute(&["--isolated", "-3.6"]).0, env_state.python36);
+    // After a script, it's the script's argument.
+    assert_eq!(
+        execute(&["script.py", "-3.6"]),
+        (
+            env_state.python37.clone(),
+            vec!["script.py".to_string(), "-3.6".to_string()]
+        )
+    );
+    // After `--`, it's passed along as-is.
+    assert_eq!(
+        execute(&["--", "-u", "-3.6"]),
+        (
+            env_state.python37.clone(),
+            vec!["-u".to_string(), "-3.6".to_string()]
+        )
+    );
+
+    // Among the interpreter's options, Python would reject it.
+    for args in [vec!["-u", "-3.6", "script.py"], vec!["-3.7", "-u", "-3.6"]] {
+        assert_eq!(
+            from_main(&args),
+            Err(Error::MisplacedVersionFlag(
+                PathBuf::from("/path/to/py"),
+                "-3.6".to_string()
+            )),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_only_unversioned_executables() {
+    let _working_dir = common::CurrentDir::new();
+    let fake_path = common::FakePath::new();
+    let python2 = fake_path.executable("python2", "Python 2.7.18");
+    let python3 = fake_path.executable("python3", "Python 3.9.2");
+
+    for (flag, expected) in [("-2", &python2), ("-3", &python3), ("-3.9", &python3)] {
+        match Action::from_main(&["/path/to/py".to_string(), flag.to_string()]) {
+            Ok(Action::Execute { executable, .. }) => assert_eq!(&executable, expected),
+            result => panic!("'{}' returned {:?}", flag, result),
+        }
+    }
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python3),
+        result => panic!("No flag returned {:?}", result),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert!(output.contains(python3.to_str().unwrap()));
+            assert!(output.contains(python2.to_str().unwrap()));
+        }
+        result => panic!("'--list' returned {:?}", result),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_list_unversioned_executables() {
+    let _working_dir = common::CurrentDir::new();
+    let fake_path = common::FakePath::new();
+    let python311 = fake_path.python(3, 11);
+    let python2 = fake_path.executable("python2", "Python 2.7.18");
+    let python3 = fake_path.executable("python3", "hello");
+    // The version column of the row for `path`.
+    let row = |output: &str, path: &Path| {
+        output.lines().find_map(|line| {
+            let cells: Vec<&str> = line.split('│').map(str::trim).collect();
+            (cells[1] == path.to_str().unwrap()).then(|| cells[0].to_string())
+        })
+    };
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert_eq!(row(&output, &python311).as_deref(), Some("3.11"));
+            // The probed version, or an explicit label when there isn't one.
+            assert_eq!(row(&output, &python2).as_deref(), Some("2.7"));
+            assert_eq!(row(&output, &python3).as_deref(), Some("unknown"));
+            // Listed last.
+            assert!(output.trim_end().ends_with(python3.to_str().unwrap()));
+        }
+        result => panic!("'--list' returned {:?}", result),
+    }
+
+    // An unknown version doesn't satisfy a specific request.
+    let args = [
+        "/path/to/py".to_string(),
+        "-3".to_string(),
+        "--list".to_string(),
+    ];
+    match Action::from_main(&args) {
+        Ok(Action::ListTable(table)) => assert_eq!(row(&table.to_string(), &python3), None),
+        result => panic!("'-3 --list' returned {:?}", result),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_unset_path() {
+    let working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    env_state.env_vars.change("PATH", None);
+    // An empty `PATH` would mean the current directory.
+    common::touch_file(working_dir.dir.path().join("python3.11"));
+
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string()]),
+        Err(Error::NoExecutableFound(RequestedVersion::Any))
+    );
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]),
+        Err(Error::NoExecutableFound(RequestedVersion::Any))
+    );
+    assert!(python_launcher::path_entries(&SearchDirectories::default()).is_empty());
+
+    // Directories from elsewhere are still searched.
+    let launcher_path = TempDir::new().unwrap();
+    let python312 = common::touch_file(launcher_path.path().join("python3.12"));
+    env_state
+        .env_vars
+        .change("PYLAUNCHER_PATH", launcher_path.path().to_str());
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python312),
+        result => panic!("PYLAUNCHER_PATH returned {:?}", result),
+    }
+}
+
+#[test]
+#[serial]
+fn from_main_json_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let tree = TempDir::new().unwrap();
+    let mut directory = tree.path().as_os_str().as_bytes().to_vec();
+    directory.extend_from_slice(b"/bin\xff");
+    let directory = PathBuf::from(OsStr::from_bytes(&directory));
+    fs::create_dir(&directory).unwrap();
+    let python = common::touch_file(directory.join("python3.11"));
+    env_state
+        .env_vars
+        .change_os("PATH", Some(directory.as_os_str()));
+    let json = |args: &[&str]| {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(args.iter().map(ToString::to_string));
+        match Action::from_main(&argv) {
+            Ok(Action::List(output)) | Ok(Action::Info(output)) => {
+                serde_json::from_str::<serde_json::Value>(&output).unwrap()
+            }
+            result => panic!("{:?} returned {:?}", args, result),
+        }
+    };
+    let expected_path = serde_json::json!({
+        "lossy": python.to_string_lossy(),
+        "bytes": python.as_os_str().as_bytes(),
+    });
+
+    assert_eq!(
+        json(&["--json", "--list"]),
+        serde_json::json!([{"version": "3.11", "path": expected_path}])
+    );
+    assert_eq!(
+        json(&["--json", "--where", "3.11"]),
+        serde_json::json!([expected_path])
+    );
+    let info = json(&["--info"]);
+    assert_eq!(info["interpreters"][0]["path"], expected_path);
+    assert_eq!(info["chosen"], expected_path);
+    // The bytes round-trip to the exact path.
+    let bytes: Vec<u8> = expected_path["bytes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|byte| byte.as_u64().unwrap() as u8)
+        .collect();
+    assert_eq!(Path::new(OsStr::from_bytes(&bytes)), python);
+}
+
+#[test]
+#[serial]
+fn from_main_json() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+    let json = |args: &[&str]| {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(args.iter().map(ToString::to_string));
+        match Action::from_main(&argv) {
+            Ok(Action::List(output)) | Ok(Action::Info(output)) => {
+                serde_json::from_str::<serde_json::Value>(&output).unwrap()
+            }
+            result => panic!("{:?} returned {:?}", args, result),
+        }
+    };
+
+    assert_eq!(
+        json(&["--json", "--list"]),
+        serde_json::json!([
+            {"version": "3.7", "path": env_state.python37.to_str().unwrap()},
+            {"version": "3.6", "path": env_state.python36.to_str().unwrap()},
+            {"version": "2.7", "path": env_state.python27.to_str().unwrap()},
+        ])
+    );
+    assert_eq!(
+        json(&[
+            "--quiet",
+            "--json",
+            "-3",
+            "--list",
+            "--sort",
+            "version-asc",
+            "--full"
+        ]),
+        serde_json::json!([
+            {"version": "3.6", "path": env_state.python36.to_str().unwrap(), "full_version": null},
+            {"version": "3.7", "path": env_state.python37.to_str().unwrap(), "full_version": null},
+        ])
+    );
+    // `--json` may come anywhere among the launcher's own arguments.
+    assert_eq!(json(&["--list", "--json"]), json(&["--json", "--list"]));
+    assert_eq!(
+        json(&["-3", "--list", "--json", "--sort", "version-asc", "--full"]),
+        json(&["--json", "-3", "--list", "--sort", "version-asc", "--full"])
+    );
+    let shadowed_python36 = env_state.python37.parent().unwrap().join("python3.6");
+    for args in [["--json", "--where", "3.6"], ["--where", "3.6", "--json"]] {
+        assert_eq!(
+            json(&args),
+            serde_json::json!([
+                env_state.python36.to_str().unwrap(),
+                shadowed_python36.to_str().unwrap()
+            ])
+        );
+    }
+    assert!(json(&["--json", "--info"]).is_object());
+
+    for args in [
+        vec!["--json"],
+        vec!["--json", "-3"],
+        vec!["--json", "--list", "--direnv"],
+        vec!["--list", "--direnv", "--json"],
+        vec!["--json", "--launcher-version"],
+    ] {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(args.iter().map(ToString::to_string));
+        assert_eq!(
+            Action::from_main(&argv),
+            Err(Error::IllegalArgument(
+                PathBuf::from("/path/to/py"),
+                "--json".to_string()
+            )),
+            "{:?}",
+            args
+        );
+    }
+
+    // Errors list the versions which are available instead, including those in
+    // the directories the command line adds.
+    let report = |argv: &[&str]| -> serde_json::Value {
+        let argv: Vec<String> = argv.iter().map(ToString::to_string).collect();
+        let error = Action::from_main(&argv).unwrap_err();
+        serde_json::from_str(&python_launcher::cli::json_error(&error, &argv)).unwrap()
+    };
+    assert_eq!(
+        report(&["/path/to/py", "--json", "-4", "--list"]),
+        serde_json::json!({
+            "error": "No executable found for Python 4",
+            "available": ["3.7", "3.6", "2.7"],
+        })
+    );
+    let extra_dir = TempDir::new().unwrap();
+    common::touch_file(extra_dir.path().join("python3.12"));
+    assert_eq!(
+        report(&[
+            "/path/to/py",
+            "--append-path",
+            extra_dir.path().to_str().unwrap(),
+            "--json",
+            "-4",
+            "--list",
+        ])["available"],
+        serde_json::json!(["3.12", "3.7", "3.6", "2.7"])
+    );
+    let report: serde_json::Value = serde_json::from_str(&python_launcher::cli::json_error(
+        &Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 12),
+            vec![ExactVersion::new(3, 6), ExactVersion::new(3, 11)],
+        ),
+        &["/path/to/py".to_string()],
+    ))
+    .unwrap();
+    assert_eq!(report["available"], serde_json::json!(["3.11", "3.6"]));
+}
+
+#[test]
+#[serial]
+fn from_main_isolated() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let script_path = dir.path().join("script.py");
+    let script = script_path.to_str().unwrap().to_string();
+
+    for (shebang, expected_args) in [
+        ("#! /usr/bin/env python3.6\n", vec!["-I"]),
+        ("#! /usr/bin/env python3.6 -u\n", vec!["-I", "-u"]),
+        ("#! /usr/bin/env python3.6 -I\n", vec!["-I"]),
+        ("#! /usr/bin/env python3.6 -u -I\n", vec!["-u", "-I"]),
+    ] {
+        fs::write(&script_path, shebang).unwrap();
+        match Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--isolated".to_string(),
+            script.clone(),
+            "-I".to_string(),
+        ]) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => {
+                assert_eq!(executable, env_state.python36);
+                let mut expected_args: Vec<String> =
+                    expected_args.into_iter().map(String::from).collect();
+                expected_args.extend([script.clone(), "-I".to_string()]);
+                assert_eq!(args, expected_args, "{:?}", shebang);
+                // The script's own `-I` argument is left alone.
+                let interpreter_args = args.iter().take_while(|arg| **arg != script);
+                assert_eq!(interpreter_args.filter(|arg| *arg == "-I").count(), 1);
+            }
+            _ => panic!("{:?} did not return Action::Execute", shebang),
+        }
+    }
+
+    // Composes with the other launcher flags.
+    match Action::from_main(&[
+        "/path/to/py".to_string(),
+        "--quiet".to_string(),
+        "--isolated".to_string(),
+        "-3.7".to_string(),
+        "-c".to_string(),
+        "pass".to_string(),
+    ]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            assert_eq!(executable, env_state.python37);
+            assert_eq!(args, vec!["-I", "-c", "pass"]);
+        }
+        _ => panic!("'--isolated -3.7' did not return Action::Execute"),
+    }
+
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--isolated".to_string(),
+            "--launcher-version".to_string(),
+        ]),
+        Err(Error::IllegalArgument(
+            PathBuf::from("/path/to/py"),
+            "--isolated".to_string()
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_show_command() {
+    let _working_dir = common::CurrentDir::new();
+    let env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let script_path = dir.path().join("script.py");
+    fs::write(&script_path, "#! /usr/bin/env python3.6 -u\n").unwrap();
+    let script = script_path.to_str().unwrap().to_string();
+    let argv = |flags: &[&str]| {
+        let mut argv = vec!["/path/to/py".to_string()];
+        argv.extend(flags.iter().map(ToString::to_string));
+        argv.extend([script.clone(), "--name".to_string(), "it's".to_string()]);
+        argv
+    };
+
+    let (executable, args) = match Action::from_main(&argv(&[])) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => (executable, args),
+        result => panic!("the script returned {:?}", result),
+    };
+    assert_eq!(executable, env_state.python36);
+    assert_eq!(args, vec!["-u", script.as_str(), "--name", "it's"]);
+    let expected = format!(
+        "'{}' '-u' '{}' '--name' 'it'\\''s'",
+        executable.display(),
+        script
+    );
+    assert_eq!(
+        Action::from_main(&argv(&["--show-command"])),
+        Ok(Action::ShowCommand(expected))
+    );
+
+    // The launcher-wide flags may come in any order.
+    for flags in [
+        ["--quiet", "--show-command", "--isolated"],
+        ["--isolated", "--show-command", "--quiet"],
+        ["--isolated", "--quiet", "--show-command"],
+    ] {
+        assert_eq!(
+            Action::from_main(&argv(&flags)),
+            Ok(Action::ShowCommand(format!(
+                "'{}' '-I' '-u' '{}' '--name' 'it'\\''s'",
+                env_state.python36.display(),
+                script
+            ))),
+            "{:?}",
+            flags
+        );
+    }
+
+    assert_eq!(
+        Action::from_main(&[
+            "/path/to/py".to_string(),
+            "--show-command".to_string(),
+            "--list".to_string(),
+        ]),
+        Err(Error::IllegalArgument(
+            PathBuf::from("/path/to/py"),
+            "--show-command".to_string()
+        ))
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_quiet_anywhere() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+
+    // Wherever it comes among the launcher's own arguments, `--quiet` isn't
+    // passed on to the interpreter.
+    for argv in [
+        ["--quiet", "-3", "-c", "pass"],
+        ["-3", "--quiet", "-c", "pass"],
+    ] {
+        let mut full_argv = vec!["/path/to/py".to_string()];
+        full_argv.extend(argv.iter().map(ToString::to_string));
+        match Action::from_main(&full_argv) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => {
+                assert_eq!(executable, env_state.python37);
+                assert_eq!(args, vec!["-c".to_string(), "pass".to_string()]);
+            }
+            result => panic!("{:?} returned {:?}", argv, result),
+        }
+    }
+    let argv = [
+        "/path/to/py".to_string(),
+        "-3".to_string(),
+        "--quiet".to_string(),
+    ];
+    assert!(cli::quiet_requested(&argv));
+
+    // Explicitly asking for logging wins over `--quiet`.
+    env_state.env_vars.change("PYLAUNCH_DEBUG", Some("1"));
+    assert!(!cli::quiet_requested(&argv));
+    env_state.env_vars.change("PYLAUNCH_DEBUG", Some("0"));
+    assert!(cli::quiet_requested(&argv));
+}
+
+#[test]
+#[serial]
+fn from_main_search_directory_flags() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let prepended_python36 = common::touch_file(dir.path().join("python3.6"));
+    let python312 = common::touch_file(dir.path().join("python3.12"));
+    let directory = dir.path().to_str().unwrap();
+    let missing = dir.path().join("missing");
+    env_state.env_vars.change("HOME", Some(directory));
+
+    for (argv, expected) in [
+        (
+            vec!["--prepend-path", directory, "-3.6"],
+            &prepended_python36,
+        ),
+        (
+            vec!["--append-path", directory, "-3.6"],
+            &env_state.python36,
+        ),
+        (vec!["--append-path", directory, "-3.12"], &python312),
+        (
+            vec![
+                "--prepend-path",
+                missing.to_str().unwrap(),
+                "--prepend-path",
+                directory,
+                "-3.6",
+            ],
+            &prepended_python36,
+        ),
+        (
+            vec!["--quiet", "--append-path", directory, "-3.12"],
+            &python312,
+        ),
+        (vec!["--prepend-path", "~", "-3.6"], &prepended_python36),
+        (vec!["--append-path", "$HOME/", "-3.12"], &python312),
+    ] {
+        let mut full_argv = vec!["/path/to/py".to_string()];
+        full_argv.extend(argv.iter().map(ToString::to_string));
+        full_argv.push("-c".to_string());
+        match Action::from_main(&full_argv) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => {
+                assert_eq!(&executable, expected);
+                assert_eq!(args, vec!["-c".to_string()]);
+            }
+            result => panic!("{:?} returned {:?}", argv, result),
+        }
+    }
+
+    // The directories only apply to the invocation which specified them, and
+    // never to the library's own searches.
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-3.12".to_string()]),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 12),
+            vec![
+                ExactVersion::new(2, 7),
+                ExactVersion::new(3, 6),
+                ExactVersion::new(3, 7)
+            ]
+        ))
+    );
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 12)),
+        None
+    );
+    assert_eq!(
+        python_launcher::find_executable_for(
+            python_launcher::Implementation::CPython,
+            RequestedVersion::Exact(3, 12),
+            &SearchDirectories::new(&[], &[dir.path().to_path_buf()])
+        ),
+        Some(python312)
+    );
+}
+
+#[test]
+#[serial]
+fn from_main_bare_invocation() {
+    let _working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    let bare = || match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute {
+            executable, args, ..
+        }) => {
+            // No arguments means the interpreter starts its REPL.
+            assert!(args.is_empty());
+            executable
+        }
+        result => panic!("'py' returned {:?}", result),
+    };
+
+    // Neither a virtual environment nor PY_PYTHON: the newest interpreter.
+    assert_eq!(bare(), env_state.python37);
+
+    env_state.env_vars.change("PY_PYTHON", Some("3.6"));
+    assert_eq!(bare(), env_state.python36);
+
+    // An activated virtual environment wins over PY_PYTHON.
+    let venv_root = TempDir::new().unwrap();
+    fs::create_dir(venv_root.path().join("bin")).unwrap();
+    let venv_python = common::touch_file(venv_root.path().join("bin").join("python"));
+    env_state
+        .env_vars
+        .change("VIRTUAL_ENV", venv_root.path().to_str());
+    assert_eq!(bare(), venv_python);
+
+    env_state.env_vars.change("PY_PYTHON", None);
+    assert_eq!(bare(), venv_python);
+}
+
+#[test]
+#[serial]
+fn from_main_micro_version_file_name() {
+    let fake_path = common::FakePath::new();
+    let python3114 = fake_path.executable("python3.11.4", "Python 3.11.4");
+
+    let executable_for =
+        |flag: &str| match Action::from_main(&["/path/to/py".to_string(), flag.to_string()]) {
+            Ok(Action::Execute {
+                executable, args, ..
+            }) => (executable, args),
+            result => panic!("{:?} returned {:?}", flag, result),
+        };
+
+    assert_eq!(executable_for("-3.11"), (python3114, vec![]));
+    // Only `X.Y` may be requested, so a micro version is an error rather than
+    // being passed on to the interpreter.
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-3.11.4".to_string()]),
+        Err(Error::MicroVersionFlag(
+            PathBuf::from("/path/to/py"),
+            "-3.11.4".to_string()
+        ))
+    );
+
+    // The plain `X.Y` name wins within the same directory.
+    let python311 = fake_path.python(3, 11);
+    assert_eq!(executable_for("-3.11"), (python311, vec![]));
+}
+
+#[test]
+#[serial]
+fn requested_version_from_env_default() {
+    let working_dir = common::CurrentDir::new();
+    let mut env_state = EnvState::new();
+    // Running the launcher searches for exactly what's shown as the default.
+    let executed = || match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => Ok(executable),
+        Err(Error::NoMatchingExecutable(requested, _)) => Err(requested),
+        result => panic!("'py' returned {:?}", result),
+    };
+
+    assert_eq!(RequestedVersion::from_env_default(), RequestedVersion::Any);
+    assert_eq!(executed(), Ok(env_state.python37.clone()));
+
+    env_state.write_config("default-version = \"3.10\"\n");
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::Exact(3, 10)
+    );
+    assert_eq!(executed(), Err(RequestedVersion::Exact(3, 10)));
+
+    env_state.env_vars.change("PY_PYTHON", Some("3.11"));
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::Exact(3, 11)
+    );
+
+    // A major-only version from `.python-version` is refined by PY_PYTHON3.
+    fs::write(working_dir.dir.path().join(".python-version"), "3\n").unwrap();
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::MajorOnly(3)
+    );
+    env_state.env_vars.change("PY_PYTHON3", Some("3.6"));
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::Exact(3, 6)
+    );
+    assert_eq!(executed(), Ok(env_state.python36.clone()));
+
+    fs::write(working_dir.dir.path().join(".python-version"), "3.12\n").unwrap();
+    assert_eq!(
+        RequestedVersion::from_env_default(),
+        RequestedVersion::Exact(3, 12)
+    );
+    assert_eq!(executed(), Err(RequestedVersion::Exact(3, 12)));
+}
+
+#[test]
+#[serial]
+fn from_main_skips_broken_symlinks() {
+    let fake_path = common::FakePath::new();
+    let python39 = fake_path.python(3, 9);
+    let python311 = fake_path.dir.path().join("python3.11");
+    std::os::unix::fs::symlink(&python39, &python311).unwrap();
+    let python312 = fake_path.dir.path().join("python3.12");
+    std::os::unix::fs::symlink(fake_path.dir.path().join("missing"), &python312).unwrap();
+
+    match Action::from_main(&["/path/to/py".to_string(), "--list".to_string()]) {
+        Ok(Action::ListTable(table)) => {
+            let output = table.to_string();
+            assert!(!output.contains(python312.to_str().unwrap()));
+            assert!(output.contains(python311.to_str().unwrap()));
+            assert!(output.contains(python39.to_str().unwrap()));
+        }
+        result => panic!("'--list' returned {:?}", result),
+    }
+
+    match Action::from_main(&["/path/to/py".to_string()]) {
+        Ok(Action::Execute { executable, .. }) => assert_eq!(executable, python311),
+        result => panic!("'py' returned {:?}", result),
+    }
+
+    assert_eq!(
+        Action::from_main(&["/path/to/py".to_string(), "-3.12".to_string()]),
+        Err(Error::NoMatchingExecutable(
+            RequestedVersion::Exact(3, 12),
+            vec![ExactVersion::new(3, 9), ExactVersion::new(3, 11)]
+        ))
     );
 }
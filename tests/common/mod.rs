@@ -1,11 +1,38 @@
+// Each test binary compiles this module separately and uses only some of it.
+#![allow(dead_code)]
+
 use std::collections::HashMap;
 use std::env;
 use std::ffi::{OsStr, OsString};
+use std::fs;
 use std::fs::File;
-use std::path::PathBuf;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
 
 use tempfile::TempDir;
 
+/// Environment variables which influence the launcher and are unset for tests.
+const LAUNCHER_ENV_VARS: [&str; 18] = [
+    "VIRTUAL_ENV",
+    "CONDA_PREFIX",
+    "CLICOLOR_FORCE",
+    "PY_PYTHON",
+    "PY_PYTHON3",
+    "PY_PYTHON2",
+    "PY_RESOLVE_RETRY",
+    "PY_SHEBANG_FALLBACK",
+    "PY_SHEBANG_UPGRADE_PYTHON2",
+    "PYLAUNCH_DEBUG",
+    "PYLAUNCHER_ALLOW_PRERELEASES",
+    "PYLAUNCHER_CASE_INSENSITIVE",
+    "PYLAUNCHER_NO_PYTHON2_NOTE",
+    "PYLAUNCHER_PATH",
+    "PYLAUNCHER_PREFER",
+    "PYLAUNCHER_PREFER_UNVERSIONED",
+    "PYLAUNCHER_PYTHON",
+    "PYLAUNCHER_SELECT",
+];
+
 pub struct EnvVarState {
     changed: HashMap<OsString, Option<OsString>>,
 }
@@ -13,8 +40,8 @@ pub struct EnvVarState {
 impl Drop for EnvVarState {
     fn drop(&mut self) {
         self.changed.iter().for_each(|(k, v)| match &v {
-            Some(original_v) => env::set_var(&k, original_v),
-            None => env::remove_var(&k),
+            Some(original_v) => env::set_var(k, original_v),
+            None => env::remove_var(k),
         });
     }
 }
@@ -31,7 +58,9 @@ impl EnvVarState {
     pub fn empty() -> Self {
         let mut state = Self::new();
         state.change("PATH", None);
-        for env_var in ["VIRTUAL_ENV", "PY_PYTHON", "PY_PYTHON3", "PY_PYTHON2"].iter() {
+        // Point at a directory which doesn't exist so no config file is found.
+        state.change("XDG_CONFIG_HOME", Some("/path/to/nowhere"));
+        for env_var in LAUNCHER_ENV_VARS.iter() {
             state.change(env_var, None);
         }
 
@@ -39,6 +68,11 @@ impl EnvVarState {
     }
 
     pub fn change(&mut self, k: &str, v: Option<&str>) {
+        self.change_os(k, v.map(OsStr::new));
+    }
+
+    /// Like [`EnvVarState::change`], but for a value which may not be UTF-8.
+    pub fn change_os(&mut self, k: &str, v: Option<&OsStr>) {
         let os_k = OsStr::new(k);
         if !self.changed.contains_key(os_k) {
             let original_v = env::var_os(k);
@@ -79,9 +113,38 @@ pub fn touch_file(path: PathBuf) -> PathBuf {
     path
 }
 
+/// Creates an executable at `path` which prints `version_output` (e.g.
+/// `Python 3.14.0b2`) like `python --version` would.
+pub fn fake_python(path: PathBuf, version_output: &str) -> PathBuf {
+    fake_executable(path, &format!("echo '{}'", version_output))
+}
+
+/// Creates an executable shell script at `path` which runs `script`.
+pub fn fake_executable(path: PathBuf, script: &str) -> PathBuf {
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// Creates a pyenv-style shim named `name` in `pyenv_root/shims` which, in
+/// place of running pyenv, prints `version_output` like `python --version`.
+pub fn pyenv_shim(pyenv_root: &Path, name: &str, version_output: &str) -> PathBuf {
+    let shims = pyenv_root.join("shims");
+    fs::create_dir_all(&shims).unwrap();
+    fake_executable(
+        shims.join(name),
+        &format!(
+            "export PYENV_ROOT=\"{}\"\n# Stands in for `exec pyenv exec \"$program\" \"$@\"`.\necho '{}'",
+            pyenv_root.display(),
+            version_output
+        ),
+    )
+}
+
 pub struct EnvState {
     _dir1: TempDir,
     _dir2: TempDir,
+    pub config_home: TempDir,
     pub env_vars: EnvVarState,
     pub python27: PathBuf,
     pub python36: PathBuf,
@@ -96,10 +159,12 @@ impl EnvState {
     /// - `dir2/python3.6`
     /// - `dir2/python3.7`
     /// - `PATH` environment variable is set to `dir1` and `dir2`
-    /// - `VIRTUAL_ENV` is unset
+    /// - `XDG_CONFIG_HOME` is set to an empty temp directory
+    /// - `VIRTUAL_ENV` and `CONDA_PREFIX` are unset
     /// - `PY_PYTHON` is unset
     /// - `PY_PYTHON3` is unset
     /// - `PY_PYTHON2` is unset
+    /// - Any other environment variable which influences the launcher is unset
     pub fn new() -> Self {
         let dir1 = TempDir::new().unwrap();
         let dir2 = TempDir::new().unwrap();
@@ -109,20 +174,85 @@ impl EnvState {
         touch_file(dir2.path().join("python3.6"));
         let python37 = touch_file(dir2.path().join("python3.7"));
 
+        let config_home = TempDir::new().unwrap();
+
         let new_path = env::join_paths([dir1.path(), dir2.path()].iter()).unwrap();
         let mut env_changes = EnvVarState::new();
-        env_changes.change("PATH", Some(&new_path.to_str().unwrap()));
-        for env_var in ["VIRTUAL_ENV", "PY_PYTHON", "PY_PYTHON3", "PY_PYTHON2"].iter() {
+        env_changes.change("PATH", Some(new_path.to_str().unwrap()));
+        env_changes.change("XDG_CONFIG_HOME", config_home.path().to_str());
+        for env_var in LAUNCHER_ENV_VARS.iter() {
             env_changes.change(env_var, None);
         }
 
         Self {
             _dir1: dir1,
             _dir2: dir2,
+            config_home,
             env_vars: env_changes,
             python27,
             python36,
             python37,
         }
     }
+
+    /// Write `contents` as the config file found via `XDG_CONFIG_HOME`.
+    ///
+    /// The launcher only loads the config file once for each location, so
+    /// each call points `XDG_CONFIG_HOME` at a new directory.
+    pub fn write_config(&mut self, contents: &str) {
+        self.config_home = TempDir::new().unwrap();
+        self.env_vars
+            .change("XDG_CONFIG_HOME", self.config_home.path().to_str());
+        let config_dir = self.config_home.path().join("py");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("config.toml"), contents).unwrap();
+    }
+}
+
+/// A `PATH` made up of a single temporary directory of fake interpreters,
+/// with every other environment variable which influences the launcher unset
+/// for as long as it lives.
+pub struct FakePath {
+    pub dir: TempDir,
+    pub env_vars: EnvVarState,
+}
+
+impl FakePath {
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let mut env_vars = EnvVarState::empty();
+        env_vars.change("PATH", dir.path().to_str());
+        Self { dir, env_vars }
+    }
+
+    /// Creates an executable `pythonX.Y` which reports itself as `X.Y.0`.
+    pub fn python(&self, major: u16, minor: u16) -> PathBuf {
+        self.executable(
+            &format!("python{}.{}", major, minor),
+            &format!("Python {}.{}.0", major, minor),
+        )
+    }
+
+    /// Creates an executable `name` which prints `version_output` like
+    /// `python --version` would.
+    pub fn executable(&self, name: &str, version_output: &str) -> PathBuf {
+        fake_python(self.dir.path().join(name), version_output)
+    }
+
+    /// Creates an empty file named `name` without the executable bit set.
+    pub fn non_executable(&self, name: &str) -> PathBuf {
+        touch_file(self.dir.path().join(name))
+    }
+}
+
+/// Asserts that `requested` resolves to `expected` via
+/// [`python_launcher::find_executable`].
+pub fn assert_selected(requested: python_launcher::RequestedVersion, expected: &Path) {
+    assert_eq!(
+        python_launcher::find_executable(requested),
+        Some(expected.to_path_buf()),
+        "{} did not select {}",
+        requested,
+        expected.display()
+    );
 }
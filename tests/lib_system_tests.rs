@@ -1,8 +1,14 @@
 mod common;
 
+use std::{collections::HashMap, fs, path::PathBuf};
+
 use serial_test_derive::serial;
 
-use python_launcher::{ExactVersion, RequestedVersion};
+use tempfile::TempDir;
+
+use python_launcher::{
+    ExactVersion, Implementation, Interpreter, RequestedVersion, SearchDirectories, VersionMatch,
+};
 
 use common::EnvState;
 
@@ -13,47 +19,769 @@ fn all_executables() {
 
     let executables = python_launcher::all_executables();
 
-    assert_eq!(executables.len(), 3);
+    // Newest first.
+    assert_eq!(
+        executables,
+        vec![
+            Interpreter {
+                implementation: Implementation::CPython,
+                exact_version: ExactVersion { major: 3, minor: 7 },
+                path: env_state.python37,
+                full_version: None,
+            },
+            Interpreter {
+                implementation: Implementation::CPython,
+                exact_version: ExactVersion { major: 3, minor: 6 },
+                path: env_state.python36,
+                full_version: None,
+            },
+            Interpreter {
+                implementation: Implementation::CPython,
+                exact_version: ExactVersion { major: 2, minor: 7 },
+                path: env_state.python27,
+                full_version: None,
+            },
+        ]
+    );
+}
+
+/// The CPython executables found by [`python_launcher::all_executables`],
+/// keyed by version.
+fn executables_by_version() -> HashMap<ExactVersion, PathBuf> {
+    python_launcher::all_executables()
+        .into_iter()
+        .map(|interpreter| (interpreter.exact_version, interpreter.path))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn find_executable() {
+    let env_state = EnvState::new();
+
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(env_state.python37)
+    );
+
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::MajorOnly(2)),
+        Some(env_state.python27)
+    );
 
-    let python27_version = ExactVersion { major: 2, minor: 7 };
-    assert!(executables.contains_key(&python27_version));
     assert_eq!(
-        executables.get(&python27_version),
-        Some(&env_state.python27)
+        python_launcher::find_executable(RequestedVersion::Exact(3, 6)),
+        Some(env_state.python36)
     );
+}
+
+#[test]
+#[serial]
+fn all_executables_config_extra_paths() {
+    let mut env_state = EnvState::new();
+    let extra_dir = TempDir::new().unwrap();
+    let python312 = common::touch_file(extra_dir.path().join("python3.12"));
+    env_state.write_config(&format!(
+        "extra-paths = [{:?}]",
+        extra_dir.path().to_str().unwrap()
+    ));
+
+    let executables = executables_by_version();
 
-    let python36_version = ExactVersion { major: 3, minor: 6 };
-    assert!(executables.contains_key(&python27_version));
+    assert_eq!(executables.len(), 4);
+    assert_eq!(executables.get(&ExactVersion::new(3, 12)), Some(&python312));
     assert_eq!(
-        executables.get(&python36_version),
-        Some(&env_state.python36)
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python312)
     );
+}
 
-    let python37_version = ExactVersion { major: 3, minor: 7 };
-    assert!(executables.contains_key(&python37_version));
+#[test]
+#[serial]
+fn all_executables_case_insensitive() {
+    let mut fake_path = common::FakePath::new();
+    let python311 = fake_path.executable("Python3.11", "Python 3.11.0");
+    let python39 = fake_path.python(3, 9);
+
+    assert_eq!(
+        executables_by_version(),
+        HashMap::from([(ExactVersion::new(3, 9), python39.clone())])
+    );
+
+    fake_path
+        .env_vars
+        .change("PYLAUNCHER_CASE_INSENSITIVE", Some("1"));
+    assert_eq!(
+        executables_by_version(),
+        HashMap::from([
+            (ExactVersion::new(3, 9), python39),
+            (ExactVersion::new(3, 11), python311.clone()),
+        ])
+    );
+    common::assert_selected(RequestedVersion::Any, &python311);
+}
+
+#[test]
+#[serial]
+fn all_executables_config_case_insensitive() {
+    let mut env_state = EnvState::new();
+    let python312 = common::touch_file(env_state.python37.parent().unwrap().join("Python3.12"));
+    assert_eq!(executables_by_version().len(), 3);
+
+    env_state.write_config("case-insensitive = true");
+    let executables = executables_by_version();
+    assert_eq!(executables.len(), 4);
+    assert_eq!(executables.get(&ExactVersion::new(3, 12)), Some(&python312));
+}
+
+#[test]
+#[serial]
+fn config_loaded_once() {
+    let mut env_state = EnvState::new();
+    let extra_dir = TempDir::new().unwrap();
+    let python312 = common::touch_file(extra_dir.path().join("python3.12"));
+    env_state.write_config(&format!(
+        "extra-paths = [{:?}]",
+        extra_dir.path().to_str().unwrap()
+    ));
+    assert_eq!(
+        python_launcher::config::Config::current().extra_paths,
+        vec![extra_dir.path().to_path_buf()]
+    );
+
+    // Changes to the file once it's been loaded aren't seen.
+    fs::write(
+        env_state.config_home.path().join("py").join("config.toml"),
+        "",
+    )
+    .unwrap();
+    assert_eq!(
+        executables_by_version().get(&ExactVersion::new(3, 12)),
+        Some(&python312)
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_config_home_expansion() {
+    let mut env_state = EnvState::new();
+    let home = TempDir::new().unwrap();
+    env_state.env_vars.change("HOME", home.path().to_str());
+    for (directory, minor) in [("tilde", 12), ("dollar", 13), ("braces", 14)] {
+        fs::create_dir(home.path().join(directory)).unwrap();
+        common::touch_file(
+            home.path()
+                .join(directory)
+                .join(format!("python3.{}", minor)),
+        );
+    }
+    env_state.write_config(
+        r#"extra-paths = ["~/tilde", "$HOME/dollar", "${HOME}/braces", "~user/bin"]"#,
+    );
+
+    let executables = executables_by_version();
+
+    assert_eq!(executables.len(), 6);
+    for (directory, minor) in [("tilde", 12), ("dollar", 13), ("braces", 14)] {
+        assert_eq!(
+            executables.get(&ExactVersion::new(3, minor)),
+            Some(
+                &home
+                    .path()
+                    .join(directory)
+                    .join(format!("python3.{}", minor))
+            )
+        );
+    }
+
+    // Priority paths are expanded too.
+    let preferred_python36 = common::touch_file(home.path().join("tilde").join("python3.6"));
+    env_state.write_config(
+        r#"extra-paths = ["~/tilde"]
+priority-paths = ["$HOME/tilde"]"#,
+    );
     assert_eq!(
-        executables.get(&python37_version),
+        python_launcher::find_executable(RequestedVersion::Exact(3, 6)),
+        Some(preferred_python36)
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_config_priority_paths() {
+    let mut env_state = EnvState::new();
+    // `dir1/python3.6` comes first in PATH.
+    let dir2 = env_state.python37.parent().unwrap();
+    let dir2_python36 = dir2.join("python3.6");
+    let unlisted_dir = TempDir::new().unwrap();
+    env_state.write_config(&format!(
+        "priority-paths = [{:?}, {:?}]",
+        unlisted_dir.path().to_str().unwrap(),
+        dir2.to_str().unwrap()
+    ));
+
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 6)),
+        Some(dir2_python36.clone())
+    );
+    assert_eq!(
+        python_launcher::all_sourced_executables_for(
+            Implementation::CPython,
+            &SearchDirectories::default()
+        )[&ExactVersion::new(3, 6)]
+            .0,
+        dir2_python36
+    );
+    // Versions only provided once are unaffected.
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(2, 7)),
+        Some(env_state.python27.clone())
+    );
+
+    // Without a matching directory, PATH order is used.
+    env_state.write_config(&format!(
+        "priority-paths = [{:?}]",
+        unlisted_dir.path().to_str().unwrap()
+    ));
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 6)),
+        Some(env_state.python36.clone())
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_pylauncher_path() {
+    let mut env_state = EnvState::new();
+    let launcher_dir = TempDir::new().unwrap();
+    let python312 = common::touch_file(launcher_dir.path().join("python3.12"));
+    // Shadowed by the copy on PATH.
+    common::touch_file(launcher_dir.path().join("python3.7"));
+    env_state
+        .env_vars
+        .change("PYLAUNCHER_PATH", launcher_dir.path().to_str());
+
+    let executables = executables_by_version();
+
+    assert_eq!(executables.len(), 4);
+    assert_eq!(executables.get(&ExactVersion::new(3, 12)), Some(&python312));
+    assert_eq!(
+        executables.get(&ExactVersion::new(3, 7)),
         Some(&env_state.python37)
     );
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python312)
+    );
 }
 
 #[test]
 #[serial]
-fn find_executable() {
-    let env_state = EnvState::new();
+fn find_executable_pypy() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python39 = common::touch_file(dir.path().join("python3.9"));
+    let pypy39 = common::touch_file(dir.path().join("pypy3.9"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    // CPython remains the default.
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 9)),
+        Some(python39)
+    );
+    assert_eq!(
+        python_launcher::find_executable_for(
+            Implementation::PyPy,
+            RequestedVersion::Exact(3, 9),
+            &SearchDirectories::default()
+        ),
+        Some(pypy39.clone())
+    );
+    assert_eq!(
+        python_launcher::find_executable_for(
+            Implementation::PyPy,
+            RequestedVersion::Any,
+            &SearchDirectories::default()
+        ),
+        Some(pypy39)
+    );
+}
+
+#[test]
+#[serial]
+fn find_executable_two_digit_minor() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    common::touch_file(dir.path().join("python3.9"));
+    let python310 = common::touch_file(dir.path().join("python3.10"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
 
     assert_eq!(
         python_launcher::find_executable(RequestedVersion::Any),
-        Some(env_state.python37)
+        Some(python310.clone())
+    );
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::MajorOnly(3)),
+        Some(python310)
     );
+}
 
+#[test]
+#[serial]
+fn find_executable_prerelease() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python313 = common::fake_python(dir.path().join("python3.13"), "Python 3.13.1");
+    let python314 = common::fake_python(dir.path().join("python3.14"), "Python 3.14.0b2");
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    // Pre-releases are skipped when choosing the highest version ...
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python313.clone())
+    );
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::MajorOnly(3)),
+        Some(python313)
+    );
+    // ... but can still be requested explicitly.
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 14)),
+        Some(python314.clone())
+    );
+
+    env_state
+        .env_vars
+        .change("PYLAUNCHER_ALLOW_PRERELEASES", Some("1"));
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python314)
+    );
+}
+
+#[test]
+#[serial]
+fn find_executable_probes_lazily() {
+    let fake_path = common::FakePath::new();
+    let python39 = fake_path.python(3, 9);
+    let marker = fake_path.dir.path().join("probed");
+    // Would hang past the probe timeout if it were ever run (`PATH` only has
+    // the fakes, hence the full paths).
+    common::fake_executable(
+        fake_path.dir.path().join("python3.5"),
+        &format!("/usr/bin/touch '{}'\n/bin/sleep 5", marker.display()),
+    );
+
+    let start = std::time::Instant::now();
+    common::assert_selected(RequestedVersion::Any, &python39);
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python39);
+    assert!(!marker.exists(), "the older interpreter was probed");
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[test]
+#[serial]
+fn find_executable_only_prereleases() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python314 = common::fake_python(dir.path().join("python3.14"), "Python 3.14.0rc1");
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::MajorOnly(3)),
+        Some(python314)
+    );
+}
+
+#[test]
+#[serial]
+fn find_executable_unknown_release_level() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python310 = common::fake_python(dir.path().join("python3.10"), "Python 3.10.13");
+    let python311 = common::fake_python(dir.path().join("python3.11"), "hello");
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    // Only a positively identified pre-release is passed over.
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python311.clone())
+    );
+
+    fs::remove_file(python310).unwrap();
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::MajorOnly(3)),
+        Some(python311)
+    );
+}
+
+#[test]
+#[serial]
+fn find_executable_version_on_stderr() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python313 =
+        common::fake_executable(dir.path().join("python3.13"), "echo 'Python 3.13.1' >&2");
+    common::fake_executable(dir.path().join("python3.14"), "echo 'Python 3.14.0b2' >&2");
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python313)
+    );
+}
+
+#[test]
+#[serial]
+fn find_executable_version_probe_timeout() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    common::fake_python(dir.path().join("python3.13"), "Python 3.13.1");
+    let python314 = common::fake_executable(dir.path().join("python3.14"), "/bin/sleep 30");
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    // The hung executable isn't waited on, and isn't known to be a pre-release.
+    let start = std::time::Instant::now();
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Any),
+        Some(python314)
+    );
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[test]
+#[serial]
+fn all_executables_empty_path_entry() {
+    let mut env_state = EnvState::new();
+    let working_dir = common::CurrentDir::new();
+    let python312 = common::touch_file(working_dir.dir.path().join("python3.12"));
+    env_state.env_vars.change("PATH", Some(":/path/to/nowhere"));
+
+    let executables = executables_by_version();
+
+    assert_eq!(executables.len(), 1);
+    assert_eq!(
+        executables
+            .get(&ExactVersion::new(3, 12))
+            .map(|path| path.canonicalize().unwrap()),
+        Some(python312.canonicalize().unwrap())
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_file_path_entry() {
+    let mut env_state = EnvState::new();
+    let dir1 = env_state.python27.parent().unwrap().to_path_buf();
+    let dir2 = env_state.python37.parent().unwrap().to_path_buf();
+    let stray_file = TempDir::new().unwrap();
+    let not_a_directory = common::touch_file(stray_file.path().join("python3.12"));
+    let new_path = std::env::join_paths([&not_a_directory, &dir1, &dir2].iter()).unwrap();
+    env_state
+        .env_vars
+        .change("PATH", Some(new_path.to_str().unwrap()));
+
+    assert_eq!(
+        executables_by_version(),
+        HashMap::from([
+            (ExactVersion::new(2, 7), env_state.python27.clone()),
+            (ExactVersion::new(3, 6), env_state.python36.clone()),
+            (ExactVersion::new(3, 7), env_state.python37.clone()),
+        ])
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_duplicate_path_entries() {
+    let mut env_state = EnvState::new();
+    let dir1 = env_state.python27.parent().unwrap().to_path_buf();
+    let dir2 = env_state.python37.parent().unwrap().to_path_buf();
+    let new_path = std::env::join_paths([&dir2, &dir1, &dir2].iter()).unwrap();
+    env_state
+        .env_vars
+        .change("PATH", Some(new_path.to_str().unwrap()));
+
+    let executables = executables_by_version();
+
+    assert_eq!(executables.len(), 3);
+    // `dir2` comes first, so its copy of Python 3.6 wins.
+    assert_eq!(
+        executables.get(&ExactVersion::new(3, 6)),
+        Some(&dir2.join("python3.6"))
+    );
+}
+
+#[test]
+#[serial]
+fn all_executables_pyenv_shims() {
+    let mut env_state = EnvState::new();
+    let pyenv_root = TempDir::new().unwrap();
+    let shim311 = common::pyenv_shim(pyenv_root.path(), "python3.11", "Python 3.11.10");
+    let shim312 = common::pyenv_shim(pyenv_root.path(), "python3.12", "Python 3.12.1");
+    // Every CPython 3 install provides `python3`, so its shim resolves to the
+    // install of the version it reports.
+    common::pyenv_shim(pyenv_root.path(), "python3", "Python 3.11.10");
+    for version in ["3.11.2", "3.11.10", "3.10.4", "3.12.1"] {
+        let bin = pyenv_root.path().join("versions").join(version).join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        common::touch_file(bin.join("python3"));
+        if version != "3.12.1" {
+            common::touch_file(bin.join(format!("python{}", version.rsplit_once('.').unwrap().0)));
+        }
+    }
+    env_state
+        .env_vars
+        .change("PATH", Some(shim311.parent().unwrap().to_str().unwrap()));
+
+    assert!(python_launcher::is_pyenv_shim(&shim311));
+    assert!(!python_launcher::is_pyenv_shim(&env_state.python37));
+
+    let executables = executables_by_version();
+
+    assert_eq!(executables.len(), 2);
+    // The newest micro version pyenv installed.
+    assert_eq!(
+        executables.get(&ExactVersion::new(3, 11)),
+        Some(&pyenv_root.path().join("versions/3.11.10/bin/python3.11"))
+    );
+    // Nothing to resolve to, so the shim is used as-is.
+    assert_eq!(executables.get(&ExactVersion::new(3, 12)), Some(&shim312));
+
+    // Every search sees the same resolved paths.
+    let directories = SearchDirectories::default();
+    let resolved311 = pyenv_root.path().join("versions/3.11.10/bin/python3.11");
+    assert_eq!(
+        python_launcher::all_matching_executables_for(
+            Implementation::CPython,
+            RequestedVersion::Exact(3, 12),
+            &directories
+        ),
+        vec![shim312.clone()]
+    );
+    let in_search_order: Vec<PathBuf> = python_launcher::all_sourced_executables_in_search_order(
+        Implementation::CPython,
+        &directories,
+    )
+    .into_iter()
+    .map(|(_, path, _)| path)
+    .collect();
+    assert_eq!(
+        in_search_order,
+        vec![
+            pyenv_root.path().join("versions/3.11.10/bin/python3"),
+            resolved311,
+            shim312
+        ]
+    );
+}
+
+#[test]
+#[serial]
+fn fake_path_selection() {
+    let fake_path = common::FakePath::new();
+    let python27 = fake_path.python(2, 7);
+    let python39 = fake_path.python(3, 9);
+    let python311 = fake_path.python(3, 11);
+    let not_executable = fake_path.non_executable("python3.10");
+
+    let executables = executables_by_version();
+    assert_eq!(executables.len(), 4);
+    assert_eq!(
+        executables.get(&ExactVersion::new(3, 10)),
+        Some(&not_executable)
+    );
+
+    common::assert_selected(RequestedVersion::Any, &python311);
+    common::assert_selected(RequestedVersion::MajorOnly(2), &python27);
+    common::assert_selected(RequestedVersion::Exact(3, 9), &python39);
+    common::assert_selected(RequestedVersion::AtLeast(3, 9), &python311);
+    assert_eq!(
+        python_launcher::find_executable(RequestedVersion::Exact(3, 8)),
+        None
+    );
+}
+
+#[test]
+#[serial]
+fn prefer_unversioned_executable() {
+    let mut fake_path = common::FakePath::new();
+    fake_path.python(3, 11);
+    let python312 = fake_path.python(3, 12);
+    let python3 = fake_path.executable("python3", "Python 3.11.4");
+    fake_path.executable("python2", "Python 3.12.0");
+
+    // By default the newest version wins.
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python312);
+
+    fake_path
+        .env_vars
+        .change("PYLAUNCHER_PREFER_UNVERSIONED", Some("1"));
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python3);
+    // Only requests for a major version are affected.
+    common::assert_selected(RequestedVersion::Any, &python312);
+    common::assert_selected(RequestedVersion::Exact(3, 12), &python312);
+    // `python2` which isn't actually Python 2 is ignored.
     assert_eq!(
         python_launcher::find_executable(RequestedVersion::MajorOnly(2)),
-        Some(env_state.python27)
+        None
+    );
+    // Choosing the oldest version takes precedence.
+    assert_eq!(
+        python_launcher::find_executable_with_retries(
+            Implementation::CPython,
+            RequestedVersion::MajorOnly(3),
+            python_launcher::Selection::Oldest,
+            0,
+            &SearchDirectories::default()
+        ),
+        Some(fake_path.dir.path().join("python3.11"))
     );
+}
+
+#[test]
+#[serial]
+fn only_unversioned_executables() {
+    let fake_path = common::FakePath::new();
+    let python2 = fake_path.executable("python2", "Python 2.7.18");
+    let python3 = fake_path.executable("python3", "Python 3.9.2");
 
+    common::assert_selected(RequestedVersion::MajorOnly(2), &python2);
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python3);
+    // The version probed for is found like any other.
+    common::assert_selected(RequestedVersion::Any, &python3);
+    common::assert_selected(RequestedVersion::Exact(3, 9), &python3);
     assert_eq!(
-        python_launcher::find_executable(RequestedVersion::Exact(3, 6)),
-        Some(env_state.python36)
+        python_launcher::find_executable(RequestedVersion::MajorOnly(4)),
+        None
+    );
+    assert_eq!(
+        executables_by_version(),
+        HashMap::from([
+            (ExactVersion::new(2, 7), python2),
+            (ExactVersion::new(3, 9), python3.clone()),
+        ])
     );
+    // The newest version still wins ...
+    fake_path.python(3, 8);
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python3);
+    // ... with a `pythonX.Y` preferred for the same version.
+    let python39 = fake_path.python(3, 9);
+    common::assert_selected(RequestedVersion::MajorOnly(3), &python39);
+}
+
+#[test]
+#[serial]
+fn all_executables_from_directories() {
+    // PATH isn't searched.
+    let _env_state = EnvState::new();
+    let tree = TempDir::new().unwrap();
+    let first = tree.path().join("first");
+    let second = tree.path().join("nested").join("second");
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+    let python39 = common::touch_file(first.join("python3.9"));
+    let python310 = common::touch_file(first.join("python3.10"));
+    common::touch_file(first.join("python3.10-config"));
+    common::touch_file(first.join("pypy3.10"));
+    common::touch_file(second.join("python3.9"));
+    let python27 = common::touch_file(second.join("python2.7"));
+    let missing = tree.path().join("missing");
+
+    let executables = python_launcher::all_executables_from(vec![first, missing, second]);
+
+    assert_eq!(
+        executables,
+        HashMap::from([
+            (ExactVersion::new(2, 7), python27),
+            (ExactVersion::new(3, 9), python39),
+            (ExactVersion::new(3, 10), python310),
+        ])
+    );
+    assert!(python_launcher::all_executables_from(Vec::new()).is_empty());
+}
+
+#[test]
+#[serial]
+fn all_interpreters() {
+    let mut env_state = EnvState::new();
+    let dir = TempDir::new().unwrap();
+    let python310 = common::fake_executable(dir.path().join("python3.10"), "echo 'Python 3.10.12'");
+    let pypy310 = common::fake_executable(
+        dir.path().join("pypy3.10"),
+        "echo 'Python 3.10.14 (PyPy 7.3.17)'",
+    );
+    let python39 = common::touch_file(dir.path().join("python3.9"));
+    env_state
+        .env_vars
+        .change("PATH", Some(dir.path().to_str().unwrap()));
+
+    let mut interpreters = python_launcher::all_interpreters();
+
+    assert_eq!(
+        interpreters,
+        vec![
+            Interpreter {
+                implementation: Implementation::CPython,
+                exact_version: ExactVersion::new(3, 10),
+                path: python310,
+                full_version: None,
+            },
+            Interpreter {
+                implementation: Implementation::PyPy,
+                exact_version: ExactVersion::new(3, 10),
+                path: pypy310,
+                full_version: None,
+            },
+            Interpreter {
+                implementation: Implementation::CPython,
+                exact_version: ExactVersion::new(3, 9),
+                path: python39,
+                full_version: None,
+            },
+        ]
+    );
+    // Only probed when asked for.
+    let full_versions: Vec<_> = interpreters
+        .iter_mut()
+        .map(|interpreter| interpreter.probe().map(String::from))
+        .collect();
+    assert_eq!(
+        full_versions,
+        vec![
+            Some("3.10.12".to_string()),
+            Some("3.10.14".to_string()),
+            None
+        ]
+    );
+    assert_eq!(interpreters[0].full_version.as_deref(), Some("3.10.12"));
+    let exact_matches: Vec<_> = interpreters
+        .iter()
+        .filter(|interpreter| {
+            interpreter.matches(RequestedVersion::Exact(3, 10)) == VersionMatch::Exactly
+        })
+        .map(|interpreter| interpreter.implementation)
+        .collect();
+    assert_eq!(
+        exact_matches,
+        vec![Implementation::CPython, Implementation::PyPy]
+    );
+
+    env_state.env_vars.change("PATH", Some(""));
+    let _working_dir = common::CurrentDir::new();
+    assert!(python_launcher::all_interpreters().is_empty());
 }
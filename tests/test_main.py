@@ -6,8 +6,11 @@ exercise main.rs. All code contained elsewhere in this project is expected to
 be tested using Rust code.
 """
 
+import json
 import os
 import pathlib
+import re
+import signal
 import subprocess
 import sys
 
@@ -30,14 +33,15 @@ def py(monkeypatch):
     monkeypatch.delenv("PYLAUNCH_DEBUG", raising=False)
     monkeypatch.setenv("PATH", os.fspath(python_executable.parent))
     monkeypatch.delenv("VIRTUAL_ENV", raising=False)
+    monkeypatch.delenv("CONDA_PREFIX", raising=False)
     py_path = pathlib.Path(__file__).parent.parent / "target" / "debug" / "py"
 
-    def call_py(*args, debug=False):
+    def call_py(*args, debug=None):
         call = [py_path]
         call.extend(args)
         env = os.environ.copy()
-        if debug:
-            env["PYLAUNCH_DEBUG"] = "1"
+        if debug is not None:
+            env["PYLAUNCH_DEBUG"] = debug
         return subprocess.run(call, capture_output=True, text=True, env=env)
 
     call_py.path = py_path
@@ -53,6 +57,17 @@ def test_help(py, flag):
     assert not call.stderr
 
 
+def test_launcher_version(py):
+    cargo_toml = pathlib.Path(__file__).parent.parent / "Cargo.toml"
+    version = re.search(
+        r'^version = "(.+)"$', cargo_toml.read_text(encoding="utf-8"), re.MULTILINE
+    ).group(1)
+    call = py("--launcher-version")
+    assert not call.returncode
+    assert call.stdout == f"{version}\n"
+    assert not call.stderr
+
+
 def test_list(py):
     call = py("--list")
     assert not call.returncode
@@ -61,6 +76,20 @@ def test_list(py):
     assert not call.stderr
 
 
+@pytest.mark.parametrize("args", [["--list"], ["--info"], ["--launcher-version"]])
+def test_broken_pipe(py, args):
+    read_end, write_end = os.pipe()
+    os.close(read_end)
+    try:
+        call = subprocess.run(
+            [py.path, *args], stdout=write_end, stderr=subprocess.PIPE, text=True
+        )
+    finally:
+        os.close(write_end)
+    assert call.returncode == 128 + signal.SIGPIPE
+    assert not call.stderr
+
+
 @pytest.mark.parametrize(
     "python_version",
     [None, f"-{sys.version_info[0]}", f"-{sys.version_info[0]}.{sys.version_info[1]}"],
@@ -103,16 +132,187 @@ class TestExitCode:
 
         self.call_failed(py())
 
+    def test_exec_failure_message(self, py, tmp_path, monkeypatch):
+        venv_python = tmp_path / "bin" / "python"
+        venv_python.parent.mkdir()
+        venv_python.touch()
+        monkeypatch.setenv("VIRTUAL_ENV", os.fspath(tmp_path))
+
+        call = py()
+        self.call_failed(call)
+        assert not call.stdout
+        assert call.stderr == (
+            f"failed to execute {venv_python}: permission denied\n"
+        )
+
+    def test_nul_in_argument(self, py, tmp_path):
+        # A NUL can't be passed on the command line, but a shebang can hold one.
+        script = tmp_path / "script.py"
+        script.write_bytes(b"#! /usr/bin/env python3 -X\0y\nprint('hi')\n")
+
+        call = py(os.fspath(script))
+        self.call_failed(call)
+        assert not call.stdout
+        assert "contains a NUL byte" in call.stderr
+        assert "panicked" not in call.stderr
+
+    def test_no_executable_found(self, py, tmp_path, monkeypatch):
+        monkeypatch.setenv("PATH", os.fspath(tmp_path))
+
+        call = py("-c", "pass")
+        assert call.returncode == 64  # EX_USAGE
+        assert not call.stdout
+        assert call.stderr == "No executable found for Python\n"
+
+    def test_json_error(self, py, tmp_path, monkeypatch):
+        monkeypatch.setenv("PATH", os.fspath(tmp_path))
+
+        call = py("--json", "--where", "3.11")
+        assert call.returncode == 64  # EX_USAGE
+        assert json.loads(call.stdout) == {
+            "error": "No executable found for Python 3.11",
+            "available": [],
+        }
+        assert not call.stderr
+
+    def test_unset_PATH(self, py, monkeypatch):
+        monkeypatch.delenv("PATH")
+
+        call = py("-c", "pass")
+        assert call.returncode == 64  # EX_USAGE
+        assert call.stderr == "No executable found for Python\n"
+
+    @pytest.mark.parametrize("args", [["--quiet", "-0.9"], ["-0.9", "--quiet"]])
+    def test_quiet(self, py, args):
+        call = py(*args)
+        assert call.returncode == 64  # EX_USAGE
+        assert not call.stderr
+
+    def test_quiet_with_PYLAUNCH_DEBUG(self, py):
+        self.call_failed(py("--quiet", "-0.9", debug="1"))
+
+    def test_quiet_other_errors(self, py):
+        # Only an interpreter not being found is left to the exit status.
+        call = py("--quiet", "--list", "--bogus")
+        assert call.returncode == 64  # EX_USAGE
+        assert call.stderr
+
     def test_directory(self, py, tmp_path, monkeypatch):
         dir_path = tmp_path / "bin" / "python"
         monkeypatch.setenv("VIRTUAL_ENV", os.fspath(tmp_path))
         self.call_failed(py())
 
 
-def test_PYLAUNCH_DEBUG(py):
-    call = py("-c", "pass", debug=True)
+def test_quiet_after_version_flag(py):
+    call = py("-3", "--quiet", "-c", "import sys; print(sys.argv)")
+    assert not call.returncode
+    assert call.stdout == "['-c']\n"
+    assert not call.stderr
+
+
+def test_shell_script_fallback(py, tmp_path, monkeypatch):
+    # No shebang, so `execv()` fails with ENOEXEC.
+    wrapper = tmp_path / "python3.99"
+    wrapper.write_text('echo "wrapper: $*"\n', encoding="utf-8")
+    wrapper.chmod(0o755)
+    monkeypatch.setenv("PATH", os.fspath(tmp_path), prepend=os.pathsep)
+
+    call = py("-3.99", "-c", "pass")
+    assert not call.returncode
+    assert call.stdout == "wrapper: -c pass\n"
+    assert not call.stderr
+
+
+@pytest.mark.parametrize(
+    "venv_version,warned",
+    [(".".join(map(str, sys.version_info[:3])), False), ("2.7.18", True)],
+)
+@pytest.mark.parametrize("quiet", [False, True])
+def test_venv_version_conflict(py, tmp_path, monkeypatch, venv_version, warned, quiet):
+    (tmp_path / "pyvenv.cfg").write_text(f"version = {venv_version}\n")
+    (tmp_path / "bin").mkdir()
+    (tmp_path / "bin" / "python").touch()
+    monkeypatch.setenv("VIRTUAL_ENV", os.fspath(tmp_path))
+    args = [f"-{sys.version_info[0]}.{sys.version_info[1]}", "-c", "pass"]
+    if quiet:
+        args.insert(0, "--quiet")
+
+    call = py(*args)
+    assert not call.returncode
+    if warned and not quiet:
+        assert "activated virtual environment" in call.stderr
+        assert len(call.stderr.splitlines()) == 1
+    else:
+        assert not call.stderr
+
+
+@pytest.mark.parametrize(
+    "args,env,noted",
+    [
+        (["-2"], {}, True),
+        (["-2.7"], {}, True),
+        (["--quiet", "-2"], {}, False),
+        (["-2"], {"PYLAUNCHER_NO_PYTHON2_NOTE": "1"}, False),
+    ],
+)
+def test_python2_note(py, tmp_path, monkeypatch, args, env, noted):
+    python2 = tmp_path / "python2.7"
+    python2.write_text("#!/bin/sh\necho Python 2.7.18\n")
+    python2.chmod(0o755)
+    monkeypatch.setenv("PATH", os.fspath(tmp_path))
+    for name, value in env.items():
+        monkeypatch.setenv(name, value)
+
+    call = py(*args)
+    assert not call.returncode
+    if noted:
+        assert "end of life" in call.stderr
+        assert len(call.stderr.splitlines()) == 1
+    else:
+        assert not call.stderr
+
+
+def test_python2_not_installed(py, tmp_path, monkeypatch):
+    monkeypatch.setenv("PATH", os.fspath(tmp_path))
+    call = py("-2", "-c", "pass")
+    assert call.returncode
+    assert call.stderr.startswith("Python 2 isn't installed;")
+
+
+@pytest.mark.parametrize("level", ["1", "2", "yes"])
+def test_PYLAUNCH_DEBUG(py, level):
+    call = py("-c", "pass", debug=level)
     assert not call.returncode
     assert call.stderr
+    assert "Chose " in call.stderr
+
+
+@pytest.mark.parametrize("level,verbose", [("1", False), ("2", True), ("yes", True)])
+def test_PYLAUNCH_DEBUG_levels(py, level, verbose):
+    python_dir = pathlib.Path(sys.executable).parent
+    call = py("-c", "pass", debug=level)
+    assert not call.returncode
+    assert (f"Searching {python_dir}" in call.stderr) == verbose
+    assert ("Checking " in call.stderr) == verbose
+    assert "Chose " in call.stderr
+
+
+def test_PYLAUNCH_DEBUG_summary(py, monkeypatch):
+    version = ".".join(map(str, sys.version_info[:2]))
+    executable = pathlib.Path(sys.executable).parent / f"python{version}"
+    monkeypatch.setenv("PY_PYTHON", version)
+    call = py("-c", "pass", debug="1")
+    assert not call.returncode
+    assert call.stderr.splitlines()[:2] == [
+        f"Using {version} from PY_PYTHON",
+        f"Chose {executable} as the best match for Python {version}",
+    ]
+
+
+def test_PYLAUNCH_DEBUG_zero(py):
+    call = py("-c", "pass", debug="0")
+    assert not call.returncode
+    assert not call.stderr
 
 
 if __name__ == "__main__":